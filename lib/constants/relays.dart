@@ -53,7 +53,6 @@ Future<void> setGossipMode(GossipMode mode) async {
   await prefs.setString('gossip_mode', mode.name);
 }
 
-const String primalCacheUrl = 'wss://cache2.primal.net/v1';
 const String vertexRelayUrl = 'wss://relay.vertexlab.io';
 
 Future<List<String>> getRelaySetMainSockets() async {
@@ -6,18 +6,15 @@ import 'package:rxdart/rxdart.dart';
 
 import '../../domain/entities/user_profile.dart';
 import '../../src/rust/api/database.dart' as rust_db;
-import '../services/primal_cache_service.dart';
+import '../../src/rust/api/relay.dart' as rust_relay;
 import '../services/rust_database_service.dart';
 
 class ProfileRepository {
   final RustDatabaseService _events;
-  final PrimalCacheService _primalCacheService;
 
   ProfileRepository({
     required RustDatabaseService events,
-    PrimalCacheService? primalCacheService,
-  })  : _events = events,
-        _primalCacheService = primalCacheService ?? PrimalCacheService.instance;
+  }) : _events = events;
 
   UserProfile _toProfile(String pubkey, Map<String, dynamic> m) {
     return UserProfile(
@@ -122,11 +119,21 @@ class ProfileRepository {
   }
 
   Future<int> getFollowerCount(String pubkeyHex) async {
-    return await _primalCacheService.fetchFollowerCount(pubkeyHex);
+    final counts = await getFollowerCounts([pubkeyHex]);
+    return counts[pubkeyHex] ?? 0;
   }
 
   Future<Map<String, int>> getFollowerCounts(List<String> pubkeyHexes) async {
-    return await _primalCacheService.fetchFollowerCounts(pubkeyHexes);
+    if (pubkeyHexes.isEmpty) return {};
+    try {
+      final json =
+          await rust_relay.fetchFollowerCounts(pubkeyHexes: pubkeyHexes);
+      final decoded = jsonDecode(json) as Map<String, dynamic>;
+      return decoded.map((k, v) => MapEntry(k, (v as num).toInt()));
+    } catch (e) {
+      if (kDebugMode) print('[ProfileRepository] getFollowerCounts error: $e');
+      return {};
+    }
   }
 
   Future<List<Map<String, dynamic>>> getSuggestedUsers({int limit = 50}) async {
@@ -4,6 +4,7 @@ import 'package:flutter/foundation.dart';
 import 'package:path_provider/path_provider.dart';
 import 'package:shared_preferences/shared_preferences.dart';
 import 'package:qiqstr/constants/relays.dart';
+import '../../src/rust/api/operations.dart' as rust_operations;
 import '../../src/rust/api/relay.dart' as rust_relay;
 
 class RustRelayService {
@@ -249,10 +250,15 @@ class RustRelayService {
   Stream<Map<String, dynamic>> streamBroadcastEvents(
     List<Map<String, dynamic>> events, {
     List<String>? relayUrls,
+    String? operationId,
   }) {
     final eventsJson = jsonEncode(events);
     return rust_relay
-        .streamBroadcastEvents(eventsJson: eventsJson, relayUrls: relayUrls)
+        .streamBroadcastEvents(
+          eventsJson: eventsJson,
+          relayUrls: relayUrls,
+          operationId: operationId,
+        )
         .map((json) => jsonDecode(json) as Map<String, dynamic>);
   }
 
@@ -307,9 +313,12 @@ class RustRelayService {
     }
   }
 
-  Stream<Map<String, dynamic>> fetchAllEventsForAuthor(String authorHex) {
+  Stream<Map<String, dynamic>> fetchAllEventsForAuthor(
+    String authorHex, {
+    String? operationId,
+  }) {
     return rust_relay
-        .fetchAllEventsForAuthor(authorHex: authorHex)
+        .fetchAllEventsForAuthor(authorHex: authorHex, operationId: operationId)
         .map((json) => jsonDecode(json) as Map<String, dynamic>);
   }
 
@@ -327,10 +336,35 @@ class RustRelayService {
         .map((json) => jsonDecode(json) as Map<String, dynamic>);
   }
 
+  /// Rust sends one snapshot followed by diffs; this folds them back into a
+  /// full `summary`/`relays` map so listeners always see the current state.
   Stream<Map<String, dynamic>> streamRelayStatus() {
-    return rust_relay.streamRelayStatus().map(
-      (json) => jsonDecode(json) as Map<String, dynamic>,
-    );
+    final relays = <String, Map<String, dynamic>>{};
+    return rust_relay.streamRelayStatus().map((json) {
+      final update = jsonDecode(json) as Map<String, dynamic>;
+      final changes = update['changes'] as List<dynamic>? ?? const [];
+      if (update['type'] == 'snapshot') {
+        relays.clear();
+        for (final relay in update['relays'] as List<dynamic>? ?? const []) {
+          final r = relay as Map<String, dynamic>;
+          relays[r['url'] as String] = r;
+        }
+      }
+      for (final change in changes) {
+        final c = change as Map<String, dynamic>;
+        final url = c['url'] as String;
+        if (c['change'] == 'removed') {
+          relays.remove(url);
+        } else if (c['relay'] != null) {
+          relays[url] = c['relay'] as Map<String, dynamic>;
+        }
+      }
+      return {
+        'summary': update['summary'],
+        'relays': relays.values.toList(),
+        'changes': changes,
+      };
+    });
   }
 
   Stream<Map<String, dynamic>> subscribeToEvents(Map<String, dynamic> filter) {
@@ -344,14 +378,31 @@ class RustRelayService {
     return await rust_relay.resolveThreadRoot(noteId: noteId);
   }
 
-  Future<int> syncRepliesRecursive(String noteId, {int maxDepth = 3}) async {
+  Future<int> syncRepliesRecursive(
+    String noteId, {
+    int maxDepth = 3,
+    String? operationId,
+    int? timeoutSecs,
+  }) async {
     final count = await rust_relay.syncRepliesRecursive(
       noteId: noteId,
       maxDepth: maxDepth,
+      operationId: operationId,
+      timeoutSecs: timeoutSecs == null ? null : BigInt.from(timeoutSecs),
     );
     return count;
   }
 
+  Future<bool> cancelOperation(String operationId) async {
+    return await rust_operations.cancelOperation(operationId: operationId);
+  }
+
+  Stream<Map<String, dynamic>> streamOperationProgress() {
+    return rust_operations.streamOperationProgress().map(
+      (json) => jsonDecode(json) as Map<String, dynamic>,
+    );
+  }
+
   Future<Map<String, dynamic>> buildThreadStructure(
     Map<String, dynamic> rootNote,
     List<Map<String, dynamic>> replies,
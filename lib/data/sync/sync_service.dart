@@ -1,5 +1,6 @@
 import 'dart:async';
 import 'dart:convert';
+import 'package:battery_plus/battery_plus.dart';
 import 'package:connectivity_plus/connectivity_plus.dart';
 import 'package:flutter/foundation.dart';
 import '../services/rust_database_service.dart';
@@ -13,6 +14,7 @@ import 'publishers/event_publisher.dart';
 import '../../domain/entities/article.dart';
 import '../../src/rust/api/database.dart' as rust_db;
 import '../../src/rust/api/relay.dart' as rust_relay;
+import '../../src/rust/api/sync_scheduler.dart' as rust_sync;
 
 final _relayService = RustRelayService.instance;
 
@@ -34,8 +36,15 @@ class SyncService {
   final RustDatabaseService _db;
   final EventPublisher _publisher;
 
-  Timer? _periodicTimer;
+  StreamSubscription<String>? _schedulerStatusSubscription;
+  StreamSubscription<List<ConnectivityResult>>? _connectivitySubscription;
+  StreamSubscription<BatteryState>? _batterySubscription;
+  final Battery _battery = Battery();
+  final Map<String, int> _schedulerLastRunAt = {};
   Timer? _lastSyncCleanupTimer;
+  Timer? _profileTimer;
+  static const _lowBatteryLevel = 20;
+  static const _profileCheckInterval = Duration(minutes: 5);
   final _syncStatusController =
       StreamController<SyncOperationStatus>.broadcast();
   final Map<String, DateTime> _lastSyncTime = {};
@@ -936,18 +945,21 @@ class SyncService {
     return event;
   }
 
-  void startPeriodicSync(String userPubkey,
-      {Duration interval = const Duration(minutes: 10)}) {
+  Future<void> startPeriodicSync(String userPubkey) async {
     stopPeriodicSync();
-    _periodicTimer = Timer.periodic(interval, (_) async {
-      if (_feedSubscription != null || _notificationSubscription != null) {
-        return;
-      }
-      await Future.wait([
-        syncFeed(userPubkey),
-        syncNotifications(userPubkey),
-      ]);
-    });
+    _schedulerStatusSubscription =
+        rust_sync.streamSyncStatus().listen(_onSchedulerStatus);
+    await rust_sync.startSyncScheduler(
+      userPubkeyHex: userPubkey,
+      configJson: jsonEncode({'profile': await _currentProfile()}),
+    );
+    _connectivitySubscription =
+        Connectivity().onConnectivityChanged.listen((_) => _updateProfile());
+    _batterySubscription =
+        _battery.onBatteryStateChanged.listen((_) => _updateProfile());
+    // The battery level has no change stream, so check it now and then.
+    _profileTimer =
+        Timer.periodic(_profileCheckInterval, (_) => _updateProfile());
     _lastSyncCleanupTimer?.cancel();
     _lastSyncCleanupTimer = Timer.periodic(const Duration(hours: 1), (_) {
       _cleanupLastSyncTime();
@@ -955,12 +967,83 @@ class SyncService {
   }
 
   void stopPeriodicSync() {
-    _periodicTimer?.cancel();
-    _periodicTimer = null;
+    _schedulerStatusSubscription?.cancel();
+    _schedulerStatusSubscription = null;
+    _connectivitySubscription?.cancel();
+    _connectivitySubscription = null;
+    _batterySubscription?.cancel();
+    _batterySubscription = null;
+    _profileTimer?.cancel();
+    _profileTimer = null;
+    _schedulerLastRunAt.clear();
+    rust_sync.stopSyncScheduler().catchError((_) {});
     _lastSyncCleanupTimer?.cancel();
     _lastSyncCleanupTimer = null;
   }
 
+  Future<void> _updateProfile() async {
+    try {
+      await rust_sync.setSyncProfile(profile: await _currentProfile());
+    } catch (_) {}
+  }
+
+  Future<String> _currentProfile() async {
+    final results = await Connectivity().checkConnectivity();
+    return _profileFor(results, await _isSavingBattery());
+  }
+
+  /// True in the OS power-save mode, or on battery below [_lowBatteryLevel].
+  Future<bool> _isSavingBattery() async {
+    try {
+      if (await _battery.isInBatterySaveMode) return true;
+      final state = await _battery.batteryState;
+      if (state == BatteryState.charging || state == BatteryState.full) {
+        return false;
+      }
+      return await _battery.batteryLevel <= _lowBatteryLevel;
+    } catch (_) {
+      return false;
+    }
+  }
+
+  String _profileFor(List<ConnectivityResult> results, bool savingBattery) {
+    if (results.every((r) => r == ConnectivityResult.none)) return 'paused';
+    if (savingBattery) return 'batterySaver';
+    if (results.contains(ConnectivityResult.wifi) ||
+        results.contains(ConnectivityResult.ethernet)) {
+      return 'normal';
+    }
+    return 'dataSaver';
+  }
+
+  void _onSchedulerStatus(String json) {
+    try {
+      final status = jsonDecode(json) as Map<String, dynamic>;
+      for (final task in status['tasks'] as List<dynamic>) {
+        final name = task['task'] as String;
+        final lastRunAt = task['lastRunAt'] as int?;
+        if (lastRunAt == null || task['running'] == true) continue;
+        if (_schedulerLastRunAt[name] == lastRunAt) continue;
+        _schedulerLastRunAt[name] = lastRunAt;
+
+        final received = (task['lastResult']?['received'] as int?) ?? 0;
+        if (received == 0) continue;
+        switch (name) {
+          case 'feed':
+            _db.notifyFeedChange();
+          case 'notifications':
+            _db.notifyNotificationChange();
+          case 'metadata':
+            _db.notifyProfileChange();
+          default:
+            _db.notifyChange();
+        }
+      }
+    } catch (e) {
+      if (kDebugMode) print('[SyncService] Scheduler status error: $e');
+    }
+  }
+
   void _cleanupLastSyncTime() {
     final now = DateTime.now();
     _lastSyncTime.removeWhere(
@@ -29,6 +29,9 @@ class FeedNote extends Equatable {
   final Map<String, dynamic>? embeddedNotes;
   final Map<String, dynamic>? embeddedArticles;
 
+  /// Set on replies whose author is outside the user's web of trust.
+  final bool isOther;
+
   const FeedNote({
     required this.id,
     required this.pubkey,
@@ -57,6 +60,7 @@ class FeedNote extends Equatable {
     this.quotedNote,
     this.embeddedNotes,
     this.embeddedArticles,
+    this.isOther = false,
   });
 
   factory FeedNote.fromMap(Map<String, dynamic> map) {
@@ -94,6 +98,7 @@ class FeedNote extends Equatable {
       quotedNote: map['quotedNote'] as Map<String, dynamic>?,
       embeddedNotes: map['embeddedNotes'] as Map<String, dynamic>?,
       embeddedArticles: map['embeddedArticles'] as Map<String, dynamic>?,
+      isOther: map['bucket'] == 'other',
     );
   }
 
@@ -125,6 +130,7 @@ class FeedNote extends Equatable {
     Map<String, dynamic>? quotedNote,
     Map<String, dynamic>? embeddedNotes,
     Map<String, dynamic>? embeddedArticles,
+    bool? isOther,
   }) {
     return FeedNote(
       id: id ?? this.id,
@@ -154,6 +160,7 @@ class FeedNote extends Equatable {
       quotedNote: quotedNote ?? this.quotedNote,
       embeddedNotes: embeddedNotes ?? this.embeddedNotes,
       embeddedArticles: embeddedArticles ?? this.embeddedArticles,
+      isOther: isOther ?? this.isOther,
     );
   }
 
@@ -209,6 +216,7 @@ class FeedNote extends Equatable {
         quotedNote?['id'],
         embeddedNotes?.length ?? 0,
         embeddedArticles?.length ?? 0,
+        isOther,
       ];
 
   Map<String, dynamic> toMap() {
@@ -240,6 +248,7 @@ class FeedNote extends Equatable {
       if (quotedNote != null) 'quotedNote': quotedNote,
       if (embeddedNotes != null) 'embeddedNotes': embeddedNotes,
       if (embeddedArticles != null) 'embeddedArticles': embeddedArticles,
+      if (isOther) 'bucket': 'other',
     };
   }
 }
@@ -20,6 +20,9 @@ class NotificationItem extends Equatable {
   final String? fromImage;
   final int? zapAmount;
 
+  /// Set when the sender is outside the user's web of trust.
+  final bool isOther;
+
   const NotificationItem({
     required this.id,
     required this.type,
@@ -30,6 +33,7 @@ class NotificationItem extends Equatable {
     this.fromName,
     this.fromImage,
     this.zapAmount,
+    this.isOther = false,
   });
 
   factory NotificationItem.fromMap(Map<String, dynamic> map) {
@@ -53,6 +57,7 @@ class NotificationItem extends Equatable {
       fromName: map['fromName'] as String?,
       fromImage: map['fromImage'] as String?,
       zapAmount: map['zapAmount'] as int?,
+      isOther: map['bucket'] == 'other',
     );
   }
 
@@ -66,6 +71,7 @@ class NotificationItem extends Equatable {
     String? fromName,
     String? fromImage,
     int? zapAmount,
+    bool? isOther,
   }) {
     return NotificationItem(
       id: id ?? this.id,
@@ -77,6 +83,7 @@ class NotificationItem extends Equatable {
       fromName: fromName ?? this.fromName,
       fromImage: fromImage ?? this.fromImage,
       zapAmount: zapAmount ?? this.zapAmount,
+      isOther: isOther ?? this.isOther,
     );
   }
 
@@ -91,6 +98,7 @@ class NotificationItem extends Equatable {
         fromName,
         fromImage,
         zapAmount,
+        isOther,
       ];
 
   DateTime get createdAtDateTime {
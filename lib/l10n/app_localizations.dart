@@ -2290,6 +2290,12 @@ abstract class AppLocalizations {
   /// **'{count, plural, =1{1 quote} other{{count} quotes}}'**
   String notificationQuoteCount(int count);
 
+  /// No description provided for @outsideYourNetwork.
+  ///
+  /// In en, this message translates to:
+  /// **'Outside your network ({count})'**
+  String outsideYourNetwork(int count);
+
   /// No description provided for @notificationZapCount.
   ///
   /// In en, this message translates to:
@@ -1233,6 +1233,11 @@ class AppLocalizationsDe extends AppLocalizations {
     return '$_temp0';
   }
 
+  @override
+  String outsideYourNetwork(int count) {
+    return 'Außerhalb deines Netzwerks ($count)';
+  }
+
   @override
   String notificationZapCount(int count) {
     String _temp0 = intl.Intl.pluralLogic(
@@ -1204,6 +1204,11 @@ class AppLocalizationsEn extends AppLocalizations {
     return '$_temp0';
   }
 
+  @override
+  String outsideYourNetwork(int count) {
+    return 'Outside your network ($count)';
+  }
+
   @override
   String notificationZapCount(int count) {
     String _temp0 = intl.Intl.pluralLogic(
@@ -1183,6 +1183,11 @@ class AppLocalizationsTr extends AppLocalizations {
     return '$count alıntı';
   }
 
+  @override
+  String outsideYourNetwork(int count) {
+    return 'Ağınızın dışında ($count)';
+  }
+
   @override
   String notificationZapCount(int count) {
     return '$count zap';
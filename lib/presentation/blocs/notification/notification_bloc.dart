@@ -97,10 +97,7 @@ class NotificationBloc
 
     final merged = _mergeWithOlder(event.notifications);
     final processedNotifications = _processNotifications(merged);
-    final unreadCount = processedNotifications.where((n) {
-      final isRead = n['isRead'] as bool? ?? false;
-      return !isRead;
-    }).length;
+    final unreadCount = _unreadCount(processedNotifications);
 
     emit(currentState.copyWith(
       notifications: processedNotifications,
@@ -230,15 +227,13 @@ class NotificationBloc
         fromName: n['fromName'] as String?,
         fromImage: n['fromImage'] as String?,
         zapAmount: n['zapAmount'] as int?,
+        isOther: n['bucket'] == 'other',
       );
     }).where((i) => liveIds.contains(i.id)).toList();
 
     final merged = _mergeWithOlder(liveAsItems);
     final processed = _processNotifications(merged);
-    final unreadCount = processed.where((n) {
-      final isRead = n['isRead'] as bool? ?? false;
-      return !isRead;
-    }).length;
+    final unreadCount = _unreadCount(processed);
 
     emit(latestState.copyWith(
       notifications: processed,
@@ -248,6 +243,15 @@ class NotificationBloc
     ));
   }
 
+  /// Unread notifications, not counting those from outside the user's web
+  /// of trust.
+  int _unreadCount(List<Map<String, dynamic>> notifications) {
+    return notifications.where((n) {
+      final isRead = n['isRead'] as bool? ?? false;
+      return !isRead && n['bucket'] != 'other';
+    }).length;
+  }
+
   NotificationType _parseType(String type) {
     return switch (type) {
       'reply' => NotificationType.reply,
@@ -276,6 +280,7 @@ class NotificationBloc
         'targetEventId': n.targetNoteId,
         'createdAt': n.createdAt,
         'zapAmount': n.zapAmount ?? 0,
+        'bucket': n.isOther ? 'other' : 'main',
         'isRead': _readNotificationIds.contains(id),
       };
     }).toList();
@@ -307,10 +312,7 @@ class NotificationBloc
         return n;
       }).toList();
 
-      final unreadCount = updatedNotifications.where((n) {
-        final isRead = n['isRead'] as bool? ?? false;
-        return !isRead;
-      }).length;
+      final unreadCount = _unreadCount(updatedNotifications);
 
       emit(currentState.copyWith(
         notifications: updatedNotifications,
@@ -6,7 +6,7 @@
 import '../frb_generated.dart';
 import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
 
-// These functions are ignored because they are not marked as `pub`: `_is_quote_of_root`, `_resolve_thread_parent`, `active_muted_pubkeys`, `active_muted_words`, `content_article_re`, `content_link_re`, `content_mention_re`, `content_quote_re`, `extract_bolt11_amount_sats`, `extract_content_references`, `extract_first_e_tag`, `extract_note_references`, `extract_reply_parent_id`, `extract_zap_amount_sats`, `extract_zap_comment`, `extract_zap_sender`, `hydrate_article_events`, `hydrate_notes_pub`, `hydrate_notes`, `hydrate_notification_events`, `hydrated_note_timestamp`, `is_event_muted`, `is_future_dated`, `is_media_url`, `json_tags_to_vecs`, `metadata_to_flat_json`, `mute_state`, `normalize_content_url`, `seen_ids`, `tags_from_event`, `truncate_hydrated_notes`
+// These functions are ignored because they are not marked as `pub`: `_is_quote_of_root`, `_resolve_thread_parent`, `active_muted_pubkeys`, `active_muted_words`, `content_article_re`, `content_link_re`, `content_mention_re`, `content_quote_re`, `extract_bolt11_amount_sats`, `extract_content_references`, `extract_first_e_tag`, `extract_note_references`, `extract_reply_parent_id`, `extract_zap_amount_sats`, `extract_zap_comment`, `extract_zap_sender`, `hydrate_article_events`, `hydrate_notes_pub`, `hydrate_notes`, `hydrate_notification_events`, `hydrated_note_timestamp`, `is_event_muted`, `is_future_dated`, `is_media_url`, `json_tags_to_vecs`, `metadata_to_flat_json`, `mute_state`, `normalize_content_url`, `scan_notes`, `seen_ids`, `tags_from_event`, `truncate_hydrated_notes`
 // These types are ignored because they are neither used by any `pub` functions nor (for structs and enums) marked `#[frb(unignore)]`: `MuteState`, `NoteReferences`
 
 Future<void> setActiveMuteList(
@@ -27,6 +27,9 @@ Future<String> dbGetProfiles({required List<String> pubkeysHex}) =>
 Future<bool> dbHasProfile({required String pubkeyHex}) =>
     RustLib.instance.api.crateApiDatabaseDbHasProfile(pubkeyHex: pubkeyHex);
 
+/// Profiles whose name, display name, NIP-05 or about text match `query`,
+/// allowing prefixes and small typos. Ranked by match quality and by how
+/// close each profile is to the signed-in user in the follow graph.
 Future<String> dbSearchProfiles({required String query, required int limit}) =>
     RustLib.instance.api
         .crateApiDatabaseDbSearchProfiles(query: query, limit: limit);
@@ -208,15 +211,129 @@ Future<int> dbSaveProfilesBatch({required String profilesJson}) =>
 Future<int> dbCountEvents({required String filterJson}) =>
     RustLib.instance.api.crateApiDatabaseDbCountEvents(filterJson: filterJson);
 
+/// Export stored events to a JSONL archive at `path`, one event per line,
+/// with a `<path>.manifest.json` holding the event count, a checksum and,
+/// if `user_pubkey_hex` is given, that user's relay list and lists.
+/// `filter_json` narrows the export (e.g. `{"authors":[<hex>]}` for only the
+/// user's own events); everything is exported without it. Emits
+/// `{"type":"progress","exported","total"}` while writing and a final
+/// `{"type":"done",...}` or `{"type":"error","error"}`.
+Stream<String> dbExportEvents(
+        {required String path,
+        String? filterJson,
+        String? userPubkeyHex,
+        String? operationId,
+        BigInt? timeoutSecs}) =>
+    RustLib.instance.api.crateApiDatabaseDbExportEvents(
+        path: path,
+        filterJson: filterJson,
+        userPubkeyHex: userPubkeyHex,
+        operationId: operationId,
+        timeoutSecs: timeoutSecs);
+
+/// Import a JSONL archive such as one written by [`db_export_events`]:
+/// events with a bad signature are skipped and events already stored are
+/// counted as duplicates. Emits `{"type":"progress","stage":"import",...}`
+/// with the import counters after every batch. If `rebroadcast_pubkey_hex`
+/// is given, that user's events from the archive are then republished to
+/// `relay_urls` (or the write relays), with `{"type":"progress",
+/// "stage":"broadcast","sent","failed","total"}` updates. Ends with
+/// `{"type":"done",...}` or `{"type":"error","error"}`.
+Stream<String> dbImportEvents(
+        {required String path,
+        String? rebroadcastPubkeyHex,
+        List<String>? relayUrls,
+        String? operationId,
+        BigInt? timeoutSecs}) =>
+    RustLib.instance.api.crateApiDatabaseDbImportEvents(
+        path: path,
+        rebroadcastPubkeyHex: rebroadcastPubkeyHex,
+        relayUrls: relayUrls,
+        operationId: operationId,
+        timeoutSecs: timeoutSecs);
+
 Future<void> dbWipe() => RustLib.instance.api.crateApiDatabaseDbWipe();
 
 Future<void> dbWipeDirectory() =>
     RustLib.instance.api.crateApiDatabaseDbWipeDirectory();
 
+/// What happened when the database was last opened, as JSON: `action` is
+/// `opened`, `staleLockRemoved` or `recovered`; a recovery also reports
+/// `openError`, `brokenPath` (where the unreadable store was moved),
+/// `salvaged`, `unreadable` and `salvageError`. `None` before `init_client`
+/// or without a database path.
+Future<String?> dbGetRecoveryReport() =>
+    RustLib.instance.api.crateApiDatabaseDbGetRecoveryReport();
+
+/// Delete the unreadable stores kept aside by earlier recoveries once the
+/// user no longer needs them. Returns how many were removed.
+Future<int> dbDeleteBrokenCopies() =>
+    RustLib.instance.api.crateApiDatabaseDbDeleteBrokenCopies();
+
+/// The database's persistence policy as JSON: `persistentKinds` and
+/// `persistentRanges` (inclusive `[from, to]`) are kept in LMDB and
+/// everything else in memory only; `memoryMaxEvents` caps the memory tier;
+/// `retentionDays` maps a kind to how many days its events are kept.
+Future<String> dbGetPersistencePolicy() =>
+    RustLib.instance.api.crateApiDatabaseDbGetPersistencePolicy();
+
+/// Change the persistence policy. Fields left out of `policy_json` keep
+/// their value, so `{"persistentKinds": [...]}` only replaces that list.
+/// Routing, retention and `memoryMaxEvents` apply from the next save.
+/// Returns the updated policy.
+Future<String> dbSetPersistencePolicy({required String policyJson}) =>
+    RustLib.instance.api
+        .crateApiDatabaseDbSetPersistencePolicy(policyJson: policyJson);
+
+/// Delete events past their kind's retention now instead of waiting for the
+/// periodic pass. Returns how many were deleted.
+Future<int> dbApplyRetention() =>
+    RustLib.instance.api.crateApiDatabaseDbApplyRetention();
+
+/// Disk usage of the event store as JSON: `usedBytes`, `mapSize` (grows
+/// automatically as the store fills), `quotaBytes`, `grows`,
+/// `lastGrowError` and `lastEviction`.
+Future<String> dbGetStorageStatus() =>
+    RustLib.instance.api.crateApiDatabaseDbGetStorageStatus();
+
+/// Cap the disk space the event store may use; `None` removes the cap.
+/// Once the store nears the quota the least valuable events are evicted
+/// first: other people's reactions, then reposts and zaps, then old notes
+/// from accounts the user doesn't follow. The user's own events, events
+/// mentioning them, DMs and lists are never evicted.
+Future<void> dbSetStorageQuota({BigInt? quotaBytes}) => RustLib.instance.api
+    .crateApiDatabaseDbSetStorageQuota(quotaBytes: quotaBytes);
+
+/// Evict events now if the store is over its quota. Returns the eviction
+/// report as JSON, or `None` if nothing needed to go.
+Future<String?> dbEnforceStorageQuota() =>
+    RustLib.instance.api.crateApiDatabaseDbEnforceStorageQuota();
+
 Future<String> dbSearchNotes({required String query, required int limit}) =>
     RustLib.instance.api
         .crateApiDatabaseDbSearchNotes(query: query, limit: limit);
 
+/// Ranked full-text search over stored notes and articles. Every word of
+/// `query` must match, the last one also as the start of a longer word;
+/// `"quoted words"` must appear as a phrase. The
+/// `authors`, `kinds`, `since` and `until` of `filter_json` narrow the
+/// results. Returns `{total, truncated, events}` with one page of events,
+/// best match first; `truncated` means only the best-ranked matches were
+/// considered.
+Future<String> dbSearchEvents(
+        {required String query,
+        required String filterJson,
+        required int offset,
+        required int limit}) =>
+    RustLib.instance.api.crateApiDatabaseDbSearchEvents(
+        query: query, filterJson: filterJson, offset: offset, limit: limit);
+
+/// Search index status as JSON: `open`, `documents`, `backfilled` (events
+/// stored before the index existed have all been indexed), `mapSize`,
+/// `lastError` and `droppedUpdates` (updates lost to failed writes).
+Future<String> dbGetSearchIndexStatus() =>
+    RustLib.instance.api.crateApiDatabaseDbGetSearchIndexStatus();
+
 Future<String> dbGetOldestEvents({required int limit}) =>
     RustLib.instance.api.crateApiDatabaseDbGetOldestEvents(limit: limit);
 
@@ -403,11 +520,40 @@ Future<String> dbGetHydratedReactionNotes(
         mutedWords: mutedWords,
         currentUserPubkeyHex: currentUserPubkeyHex);
 
+/// How many of the current user's follows follow `target_hex`, with their
+/// avatars, from the in-memory follow graph.
 Future<String> dbCalculateFollowScore(
         {required String currentUserHex, required String targetHex}) =>
     RustLib.instance.api.crateApiDatabaseDbCalculateFollowScore(
         currentUserHex: currentUserHex, targetHex: targetHex);
 
+/// Web-of-trust answers for each of `pubkeys_hex` relative to the current
+/// user, as JSON keyed by pubkey: `distance` (0 self, 1 follow, 2 follow of
+/// a follow, `null` further), `followedByFollows` and `trust` (0.0-1.0).
+Future<String> dbGetWebOfTrust(
+        {required String currentUserHex, required List<String> pubkeysHex}) =>
+    RustLib.instance.api.crateApiDatabaseDbGetWebOfTrust(
+        currentUserHex: currentUserHex, pubkeysHex: pubkeysHex);
+
+/// Size of the follow graph and whether it has finished loading from the
+/// database, as JSON.
+Future<String> dbGetFollowGraphStats() =>
+    RustLib.instance.api.crateApiDatabaseDbGetFollowGraphStats();
+
+/// The web-of-trust filter for replies and notifications as JSON: when
+/// `enabled`, authors further than `maxDistance` follow hops from the user
+/// or with a `trust` below `minTrust` get `"bucket": "other"` instead of
+/// `"main"` in [`db_get_hydrated_replies`], the thread structure and the
+/// notification queries. Off by default.
+/// Until the follow graph has loaded everything is `"main"`.
+Future<String> dbGetWotFilter() =>
+    RustLib.instance.api.crateApiDatabaseDbGetWotFilter();
+
+/// Change the web-of-trust filter. Fields left out of `filter_json` keep
+/// their value. Returns the updated filter.
+Future<String> dbSetWotFilter({required String filterJson}) =>
+    RustLib.instance.api.crateApiDatabaseDbSetWotFilter(filterJson: filterJson);
+
 Future<String> dbGetFollowSets(
         {required List<String> authorsHex,
         required int limit,
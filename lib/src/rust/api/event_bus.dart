@@ -0,0 +1,46 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+
+// These functions are ignored because they are not marked as `pub`: `as_str`, `bus_sink_state`, `bus_state`, `dispatch`, `emit`, `forward`, `hydrate_batch`, `insert`, `parse`, `register`, `start`, `subscribe_channel_to`, `subscribe_channel`, `unsubscribe_id`
+// These types are ignored because they are neither used by any `pub` functions nor (for structs and enums) marked `#[frb(unignore)]`: `BusItem`, `Bus`, `Hydration`, `RecentIds`, `Route`, `SinkState`
+
+/// Attach the Dart side to the event bus. Every subscription opened with
+/// [`event_bus_subscribe`] pushes its updates into this one stream; calling
+/// it again replaces the previous sink. Updates emitted while no stream was
+/// attached are delivered first. Once the stream is closed, subscriptions
+/// still open end as their next update fails to send.
+///
+/// Each message is a JSON object with `type` `event` (with `handle`, `kind`
+/// and the hydrated `item`), `eose` or `closed` (with `relay`), or `lagged`
+/// with the number of `skipped` updates. A `lagged` message means events
+/// were missed and the consumer should reload from the database.
+Stream<String> streamEventBus() =>
+    RustLib.instance.api.crateApiEventBusStreamEventBus();
+
+/// Open a long-lived subscription and return its handle. `hydration` is
+/// `raw`, `note`, `notification` or `article` and selects how events are
+/// shaped before they reach the stream; `notification` needs the user pubkey.
+Future<String> eventBusSubscribe(
+        {required String filterJson,
+        required String hydration,
+        String? currentUserPubkeyHex}) =>
+    RustLib.instance.api.crateApiEventBusEventBusSubscribe(
+        filterJson: filterJson,
+        hydration: hydration,
+        currentUserPubkeyHex: currentUserPubkeyHex);
+
+Future<void> eventBusUnsubscribe({required String handle}) =>
+    RustLib.instance.api.crateApiEventBusEventBusUnsubscribe(handle: handle);
+
+Future<void> eventBusUnsubscribeAll() =>
+    RustLib.instance.api.crateApiEventBusEventBusUnsubscribeAll();
+
+/// List open bus subscriptions with their filter, hydration mode and how many
+/// unique events each has delivered so far.
+Future<String> getEventBusSubscriptions() =>
+    RustLib.instance.api.crateApiEventBusGetEventBusSubscriptions();
@@ -0,0 +1,26 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+
+// These functions are ignored because they are not marked as `pub`: `decode`, `ensure_author_profile`, `error_json`, `fetch_with_hints`, `hydrate_event`, `resolve_address`, `resolve_event`, `resolve_profile`
+// These types are ignored because they are neither used by any `pub` functions nor (for structs and enums) marked `#[frb(unignore)]`: `Target`
+
+/// Resolve any `nostr:` URI or bare bech32 entity (npub, nprofile, note,
+/// nevent, naddr) into a hydrated profile, note or article.
+///
+/// The local database is checked first; on a miss the entity is fetched from
+/// its relay hints plus the user's read relays and saved. The result is a JSON
+/// object whose `type` is `profile`, `note`, `article` or `error` (with a
+/// `reason`), and whose `source` is `local` or `remote`.
+Future<String> resolveNostrUri(
+        {required String uri,
+        String? currentUserPubkeyHex,
+        required int timeoutSecs}) =>
+    RustLib.instance.api.crateApiNip21ResolveNostrUri(
+        uri: uri,
+        currentUserPubkeyHex: currentUserPubkeyHex,
+        timeoutSecs: timeoutSecs);
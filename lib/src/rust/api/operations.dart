@@ -0,0 +1,31 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+
+// These functions are ignored because they are not marked as `pub`: `drop`, `emit`, `is_stopped`, `outcome`, `progress_sink_state`, `progress`, `registry`, `run`, `start`, `status`, `stopped`, `to_json`
+// These types are ignored because they are neither used by any `pub` functions nor (for structs and enums) marked `#[frb(unignore)]`: `OperationInner`, `Operation`, `Progress`
+
+/// Receive a progress update for every running operation, plus a final
+/// update with its status when it ends. Operations are the calls that take
+/// an `operation_id` (streamed sync, interaction count streams, broadcasts,
+/// author and profile fetches, reply sync, archive export and import),
+/// `sync_events` and scheduled sync tasks. Other calls, such as
+/// `fetch_events` or `fetch_counts_from_relays`, don't report here. Calling
+/// it again replaces the previous sink.
+Stream<String> streamOperationProgress() =>
+    RustLib.instance.api.crateApiOperationsStreamOperationProgress();
+
+/// Cancel a running operation. Returns false if no such operation exists.
+Future<bool> cancelOperation({required String operationId}) =>
+    RustLib.instance.api
+        .crateApiOperationsCancelOperation(operationId: operationId);
+
+Future<int> cancelAllOperations() =>
+    RustLib.instance.api.crateApiOperationsCancelAllOperations();
+
+Future<String> getActiveOperations() =>
+    RustLib.instance.api.crateApiOperationsGetActiveOperations();
@@ -6,7 +6,7 @@
 import '../frb_generated.dart';
 import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
 
-// These functions are ignored because they are not marked as `pub`: `build_counts_json`, `build_thread_result_json`, `counting_client_lock`, `db_path_state`, `get_client_pub`, `get_client`, `open_or_recreate_lmdb`, `resolve_repost_to_original_local`, `resolve_repost_to_original`, `resolve_thread_root_internal`, `resolve_thread_root_local`, `sanitize_lmdb_dir`, `state`, `try_open_lmdb`, `user_relays_state`, `wipe_db_directory`
+// These functions are ignored because they are not marked as `pub`: `build_thread_result_json`, `db_path_state`, `get_client_pub`, `get_client`, `publish_events`, `ranked_relays`, `relay_change_json`, `relay_info_json`, `relay_status_str`, `relay_summary_json`, `resolve_repost_to_original_local`, `resolve_repost_to_original`, `resolve_thread_root_internal`, `resolve_thread_root_local`, `state`, `user_relays_state`, `watch_relay`
 
 Future<void> initClient(
         {required List<String> relayUrls,
@@ -35,9 +35,13 @@ Future<void> updateSigner({required String privateKeyHex}) =>
 Future<bool> isClientInitialized() =>
     RustLib.instance.api.crateApiRelayIsClientInitialized();
 
+/// Add a user relay to the pool and connect. Returns false if the pool
+/// refused it; a relay the relay policy rejects is an error carrying the
+/// reason.
 Future<bool> addRelay({required String url}) =>
     RustLib.instance.api.crateApiRelayAddRelay(url: url);
 
+/// [`add_relay`] with only the given read and write flags.
 Future<bool> addRelayWithFlags(
         {required String url, required bool read, required bool write}) =>
     RustLib.instance.api
@@ -52,38 +56,127 @@ Future<List<String>> getRelayList() =>
 Future<int> getConnectedRelayCount() =>
     RustLib.instance.api.crateApiRelayGetConnectedRelayCount();
 
+/// Persisted quality score of every relay seen so far, best first.
+Future<String> getRelayScores() =>
+    RustLib.instance.api.crateApiRelayGetRelayScores();
+
+Future<void> resetRelayScores() =>
+    RustLib.instance.api.crateApiRelayResetRelayScores();
+
+/// Current relay policy: `blocklist`, `allowlist`, `allowlistOnly`,
+/// `blockDiscoveredOnion`, `blockDiscoveredLocal` and per-URL `overrides`
+/// of `read`/`write`/`discovery`.
+Future<String> getRelayPolicy() =>
+    RustLib.instance.api.crateApiRelayGetRelayPolicy();
+
+/// Replace and persist the relay policy, then apply it to the running pool.
+/// Returns the URLs of relays that were removed because they are no longer
+/// allowed.
+Future<List<String>> setRelayPolicy({required String policyJson}) =>
+    RustLib.instance.api.crateApiRelaySetRelayPolicy(policyJson: policyJson);
+
+/// Whether `url` would be accepted by the relay policy, and why not if it
+/// wouldn't. `discovered` applies the stricter rules used for relay lists.
+Future<String> checkRelayUrl({required String url, required bool discovered}) =>
+    RustLib.instance.api
+        .crateApiRelayCheckRelayUrl(url: url, discovered: discovered);
+
+/// Current proxy config: `enabled`, `address` (`host:port` of a SOCKS5
+/// proxy, e.g. Tor on `127.0.0.1:9050`) and `onionOnly`.
+Future<String> getProxyConfig() =>
+    RustLib.instance.api.crateApiRelayGetProxyConfig();
+
+/// Replace and persist the proxy config, then reconnect every relay whose
+/// route changed. Returns how many relays were reconnected.
+Future<int> setProxyConfig({required String configJson}) =>
+    RustLib.instance.api.crateApiRelaySetProxyConfig(configJson: configJson);
+
 Future<String> getRelayStatus() =>
     RustLib.instance.api.crateApiRelayGetRelayStatus();
 
-Stream<String> streamRelayStatus() =>
-    RustLib.instance.api.crateApiRelayStreamRelayStatus();
+/// Recent NOTICE, CLOSED and rejected OK messages, keyed by relay URL, or
+/// just the list for `relay_url`. Each entry carries a typed `reason` parsed
+/// from the NIP-01 machine-readable prefix.
+Future<String> getRelayMessages({String? relayUrl}) =>
+    RustLib.instance.api.crateApiRelayGetRelayMessages(relayUrl: relayUrl);
+
+Future<void> clearRelayMessages() =>
+    RustLib.instance.api.crateApiRelayClearRelayMessages();
+
+/// Stream relay state as a `snapshot` message followed by `diff` messages.
+/// Diffs are driven by relay notifications (connected, disconnected, auth,
+/// NOTICE and CLOSED); relays added or removed from the pool and latency
+/// changes are picked up by a slower reconcile pass.
+Stream<String> streamRelayStatus({String? operationId, BigInt? timeoutSecs}) =>
+    RustLib.instance.api.crateApiRelayStreamRelayStatus(
+        operationId: operationId, timeoutSecs: timeoutSecs);
 
 Future<String> discoverAndConnectOutboxRelays(
         {required List<String> pubkeysHex,
         required BigInt maxOutboxRelays,
-        required BigInt minRelayFrequency}) =>
+        required BigInt minRelayFrequency,
+        String? operationId,
+        BigInt? timeoutSecs}) =>
     RustLib.instance.api.crateApiRelayDiscoverAndConnectOutboxRelays(
         pubkeysHex: pubkeysHex,
         maxOutboxRelays: maxOutboxRelays,
-        minRelayFrequency: minRelayFrequency);
+        minRelayFrequency: minRelayFrequency,
+        operationId: operationId,
+        timeoutSecs: timeoutSecs);
 
+/// One-shot sync of `filter` with every read relay. Keeps the old
+/// `received`/`sent`/`local`/`remote` totals and adds per-relay results
+/// under `relays`; see [`stream_sync`].
 Future<String> syncEvents({required String filterJson}) =>
     RustLib.instance.api.crateApiRelaySyncEvents(filterJson: filterJson);
 
+/// Sync `filter` with every read relay, emitting a `{"type":"relay",...}`
+/// message whenever a relay starts (`status: "running"`) or finishes. Relays
+/// use negentropy (NIP-77) where supported and paged REQ otherwise (`method`);
+/// a finished relay has `status` `ok`, `timeout` or `error`, or the
+/// operation's outcome if it was cancelled. Each relay resumes from where the
+/// last successful sync of the same filter left off. Ends with
+/// `{"type":"done",...}` carrying the totals.
+Stream<String> streamSync(
+        {required String filterJson,
+        String? operationId,
+        BigInt? timeoutSecs}) =>
+    RustLib.instance.api.crateApiRelayStreamSync(
+        filterJson: filterJson,
+        operationId: operationId,
+        timeoutSecs: timeoutSecs);
+
+/// Per-filter, per-relay sync watermarks: `[{filter, relay, syncedUntil}]`
+/// where `filter` is a hash of the filter without its time window.
+Future<String> getSyncCheckpoints() =>
+    RustLib.instance.api.crateApiRelayGetSyncCheckpoints();
+
+/// Forget every sync watermark so the next syncs start from scratch.
+Future<void> resetSyncCheckpoints() =>
+    RustLib.instance.api.crateApiRelayResetSyncCheckpoints();
+
 Future<String> fetchEvents(
         {required String filterJson, required int timeoutSecs}) =>
     RustLib.instance.api.crateApiRelayFetchEvents(
         filterJson: filterJson, timeoutSecs: timeoutSecs);
 
+/// Interaction counts per note, counted on the main pool's relays (NIP-45
+/// COUNT where supported) and cached briefly.
 Future<String> fetchCountsFromRelays(
         {required List<String> noteIds, String? userPubkeyHex}) =>
     RustLib.instance.api.crateApiRelayFetchCountsFromRelays(
         noteIds: noteIds, userPubkeyHex: userPubkeyHex);
 
 Stream<String> streamInteractionCounts(
-        {required List<String> noteIds, String? userPubkeyHex}) =>
+        {required List<String> noteIds,
+        String? userPubkeyHex,
+        String? operationId,
+        BigInt? timeoutSecs}) =>
     RustLib.instance.api.crateApiRelayStreamInteractionCounts(
-        noteIds: noteIds, userPubkeyHex: userPubkeyHex);
+        noteIds: noteIds,
+        userPubkeyHex: userPubkeyHex,
+        operationId: operationId,
+        timeoutSecs: timeoutSecs);
 
 Future<String?> fetchEventById(
         {required String eventId, required int timeoutSecs}) =>
@@ -103,6 +196,8 @@ Future<String> fetchEventsMultiFilter(
 Future<String> sendEvent({required String eventJson}) =>
     RustLib.instance.api.crateApiRelaySendEvent(eventJson: eventJson);
 
+/// Publish to `relay_urls`, adding them to the pool first. Relays the relay
+/// policy rejects are listed under `failed` with the reason.
 Future<String> sendEventTo(
         {required String eventJson, required List<String> relayUrls}) =>
     RustLib.instance.api
@@ -114,9 +209,15 @@ Future<String> broadcastEvents(
         eventsJson: eventsJson, relayUrls: relayUrls);
 
 Stream<String> streamBroadcastEvents(
-        {required String eventsJson, List<String>? relayUrls}) =>
+        {required String eventsJson,
+        List<String>? relayUrls,
+        String? operationId,
+        BigInt? timeoutSecs}) =>
     RustLib.instance.api.crateApiRelayStreamBroadcastEvents(
-        eventsJson: eventsJson, relayUrls: relayUrls);
+        eventsJson: eventsJson,
+        relayUrls: relayUrls,
+        operationId: operationId,
+        timeoutSecs: timeoutSecs);
 
 Future<String> requestToVanish(
         {required List<String> relayUrls, required String reason}) =>
@@ -128,29 +229,50 @@ Future<String> deleteEvents(
     RustLib.instance.api
         .crateApiRelayDeleteEvents(eventIds: eventIds, reason: reason);
 
-Stream<String> fetchAllEventsForAuthor({required String authorHex}) =>
-    RustLib.instance.api
-        .crateApiRelayFetchAllEventsForAuthor(authorHex: authorHex);
+Stream<String> fetchAllEventsForAuthor(
+        {required String authorHex,
+        String? operationId,
+        BigInt? timeoutSecs}) =>
+    RustLib.instance.api.crateApiRelayFetchAllEventsForAuthor(
+        authorHex: authorHex,
+        operationId: operationId,
+        timeoutSecs: timeoutSecs);
 
 Stream<String> fetchProfileEvents(
         {required String authorHex,
         required String kindsStr,
-        required PlatformInt64 sinceTimestamp}) =>
+        required PlatformInt64 sinceTimestamp,
+        String? operationId,
+        BigInt? timeoutSecs}) =>
     RustLib.instance.api.crateApiRelayFetchProfileEvents(
         authorHex: authorHex,
         kindsStr: kindsStr,
-        sinceTimestamp: sinceTimestamp);
-
-Stream<String> subscribeToEvents({required String filterJson}) =>
-    RustLib.instance.api.crateApiRelaySubscribeToEvents(filterJson: filterJson);
+        sinceTimestamp: sinceTimestamp,
+        operationId: operationId,
+        timeoutSecs: timeoutSecs);
+
+Stream<String> subscribeToEvents(
+        {required String filterJson,
+        String? operationId,
+        BigInt? timeoutSecs}) =>
+    RustLib.instance.api.crateApiRelaySubscribeToEvents(
+        filterJson: filterJson,
+        operationId: operationId,
+        timeoutSecs: timeoutSecs);
 
 Future<String> resolveThreadRoot({required String noteId}) =>
     RustLib.instance.api.crateApiRelayResolveThreadRoot(noteId: noteId);
 
 Future<int> syncRepliesRecursive(
-        {required String noteId, required int maxDepth}) =>
-    RustLib.instance.api
-        .crateApiRelaySyncRepliesRecursive(noteId: noteId, maxDepth: maxDepth);
+        {required String noteId,
+        required int maxDepth,
+        String? operationId,
+        BigInt? timeoutSecs}) =>
+    RustLib.instance.api.crateApiRelaySyncRepliesRecursive(
+        noteId: noteId,
+        maxDepth: maxDepth,
+        operationId: operationId,
+        timeoutSecs: timeoutSecs);
 
 Future<String> buildThreadStructure(
         {required String rootNoteJson, required String repliesJson}) =>
@@ -204,6 +326,48 @@ Future<int> fetchRepostOriginals({required String repostEventIdsJson}) =>
     RustLib.instance.api.crateApiRelayFetchRepostOriginals(
         repostEventIdsJson: repostEventIdsJson);
 
+/// Follower counts as `{pubkeyHex: count}`, from the configured providers
+/// (NIP-45 COUNT on the user's relays, then the local database by default).
+/// Pubkeys no provider could answer are left out.
 Future<String> fetchFollowerCounts({required List<String> pubkeyHexes}) =>
     RustLib.instance.api
         .crateApiRelayFetchFollowerCounts(pubkeyHexes: pubkeyHexes);
+
+/// Follower count providers in the order they are asked: any of `nip45`,
+/// `local` and `primal`.
+Future<List<String>> getFollowerCountProviders() =>
+    RustLib.instance.api.crateApiRelayGetFollowerCountProviders();
+
+/// Replace and persist the follower count providers. `primal` sends the
+/// looked-up pubkeys to Primal's servers and is never enabled by default.
+Future<void> setFollowerCountProviders({required List<String> providers}) =>
+    RustLib.instance.api
+        .crateApiRelaySetFollowerCountProviders(providers: providers);
+
+/// NIP-50 search relays queried by [`search_with_relays`].
+Future<List<String>> getSearchRelays() =>
+    RustLib.instance.api.crateApiRelayGetSearchRelays();
+
+/// Replace and persist the NIP-50 search relays. An empty list keeps
+/// search local.
+Future<void> setSearchRelays({required List<String> relayUrls}) =>
+    RustLib.instance.api.crateApiRelaySetSearchRelays(relayUrls: relayUrls);
+
+/// Search the local indexes and the NIP-50 search relays together.
+/// `target` is `notes` or `profiles`. Relay results are saved locally, and
+/// a hit found in both places appears once. Returns `{results, local,
+/// remote, localError, remoteError}`: hydrated notes or flat profiles,
+/// each with `source` set to `local`, `relay` or `both`, how many hits
+/// each side had, and why a side failed, if it did.
+Future<String> searchWithRelays(
+        {required String query,
+        required String target,
+        required int limit,
+        BigInt? timeoutSecs,
+        String? currentUserPubkeyHex}) =>
+    RustLib.instance.api.crateApiRelaySearchWithRelays(
+        query: query,
+        target: target,
+        limit: limit,
+        timeoutSecs: timeoutSecs,
+        currentUserPubkeyHex: currentUserPubkeyHex);
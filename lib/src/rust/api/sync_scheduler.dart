@@ -0,0 +1,45 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+
+// These functions are ignored because they are not marked as `pub`: `as_str`, `default`, `due_task`, `emit_status`, `fetch`, `follows`, `interval_multiplier`, `interval`, `load`, `next_wakeup`, `parse`, `reconcile`, `reschedule`, `run_loop`, `run_task`, `runs`, `save`, `scheduler`, `since`, `status_json`, `status_sink_state`, `updated`, `wake`
+// These types are ignored because they are neither used by any `pub` functions nor (for structs and enums) marked `#[frb(unignore)]`: `ConfigUpdate`, `SchedulerConfig`, `Scheduler`, `SyncProfile`, `SyncTask`, `TaskState`
+
+/// Start keeping the local database warm for `user_pubkey_hex`. `config_json`
+/// may set `intervals` (seconds per task: `feed`, `notifications`, `dms`,
+/// `lists`, `metadata`; `0` disables one) and `profile`, as in
+/// [`set_sync_config`]. Restarting for the same user keeps the previous run
+/// times.
+Future<void> startSyncScheduler(
+        {required String userPubkeyHex, String? configJson}) =>
+    RustLib.instance.api.crateApiSyncSchedulerStartSyncScheduler(
+        userPubkeyHex: userPubkeyHex, configJson: configJson);
+
+Future<void> stopSyncScheduler() =>
+    RustLib.instance.api.crateApiSyncSchedulerStopSyncScheduler();
+
+/// Switch between `normal`, `batterySaver`, `dataSaver` and `paused`. Kept
+/// next to the database like the rest of the config.
+Future<void> setSyncProfile({required String profile}) =>
+    RustLib.instance.api.crateApiSyncSchedulerSetSyncProfile(profile: profile);
+
+/// Update some task intervals and/or the profile; anything not mentioned
+/// keeps its current value. The config is kept next to the database.
+Future<void> setSyncConfig({required String configJson}) => RustLib.instance.api
+    .crateApiSyncSchedulerSetSyncConfig(configJson: configJson);
+
+/// Run one task (or every enabled task) as soon as the scheduler is free.
+Future<void> triggerSync({String? task}) =>
+    RustLib.instance.api.crateApiSyncSchedulerTriggerSync(task: task);
+
+Future<String> getSyncStatus() =>
+    RustLib.instance.api.crateApiSyncSchedulerGetSyncStatus();
+
+/// Receive the scheduler status on every change. Calling it again replaces
+/// the previous sink.
+Stream<String> streamSyncStatus() =>
+    RustLib.instance.api.crateApiSyncSchedulerStreamSyncStatus();
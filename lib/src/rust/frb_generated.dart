@@ -6,11 +6,15 @@
 import 'api/cashu.dart';
 import 'api/crypto.dart';
 import 'api/database.dart';
+import 'api/event_bus.dart';
 import 'api/events.dart';
 import 'api/nip17.dart';
 import 'api/nip19.dart';
+import 'api/nip21.dart';
 import 'api/nwc.dart';
+import 'api/operations.dart';
 import 'api/relay.dart';
+import 'api/sync_scheduler.dart';
 import 'dart:async';
 import 'dart:convert';
 import 'frb_generated.dart';
@@ -488,7 +492,9 @@ abstract class RustLibApi extends BaseApi {
   Future<String> crateApiRelayDiscoverAndConnectOutboxRelays(
       {required List<String> pubkeysHex,
       required BigInt maxOutboxRelays,
-      required BigInt minRelayFrequency});
+      required BigInt minRelayFrequency,
+      String? operationId,
+      BigInt? timeoutSecs});
 
   String crateApiNip19EncodeBasicBech32(
       {required String hexStr, required String prefix});
@@ -506,7 +512,7 @@ abstract class RustLibApi extends BaseApi {
       {required String eventsJson});
 
   Stream<String> crateApiRelayFetchAllEventsForAuthor(
-      {required String authorHex});
+      {required String authorHex, String? operationId, BigInt? timeoutSecs});
 
   Future<String> crateApiRelayFetchCountsFromRelays(
       {required List<String> noteIds, String? userPubkeyHex});
@@ -547,7 +553,9 @@ abstract class RustLibApi extends BaseApi {
   Stream<String> crateApiRelayFetchProfileEvents(
       {required String authorHex,
       required String kindsStr,
-      required PlatformInt64 sinceTimestamp});
+      required PlatformInt64 sinceTimestamp,
+      String? operationId,
+      BigInt? timeoutSecs});
 
   Future<int> crateApiRelayFetchRepostOriginals(
       {required String repostEventIdsJson});
@@ -654,19 +662,30 @@ abstract class RustLibApi extends BaseApi {
       {required String eventIdHex, required String privateKeyHex});
 
   Stream<String> crateApiRelayStreamBroadcastEvents(
-      {required String eventsJson, List<String>? relayUrls});
+      {required String eventsJson,
+      List<String>? relayUrls,
+      String? operationId,
+      BigInt? timeoutSecs});
 
   Stream<String> crateApiRelayStreamInteractionCounts(
-      {required List<String> noteIds, String? userPubkeyHex});
+      {required List<String> noteIds,
+      String? userPubkeyHex,
+      String? operationId,
+      BigInt? timeoutSecs});
 
-  Stream<String> crateApiRelayStreamRelayStatus();
+  Stream<String> crateApiRelayStreamRelayStatus(
+      {String? operationId, BigInt? timeoutSecs});
 
-  Stream<String> crateApiRelaySubscribeToEvents({required String filterJson});
+  Stream<String> crateApiRelaySubscribeToEvents(
+      {required String filterJson, String? operationId, BigInt? timeoutSecs});
 
   Future<String> crateApiRelaySyncEvents({required String filterJson});
 
   Future<int> crateApiRelaySyncRepliesRecursive(
-      {required String noteId, required int maxDepth});
+      {required String noteId,
+      required int maxDepth,
+      String? operationId,
+      BigInt? timeoutSecs});
 
   String crateApiNip17UnwrapGiftWrap(
       {required String receiverPrivateKeyHex, required String giftWrapJson});
@@ -689,6 +708,138 @@ abstract class RustLibApi extends BaseApi {
   Future<bool> crateApiCryptoVerifyProfileByPubkey({required String pubkeyHex});
 
   Future<int> crateApiRelayWaitForReady({required int timeoutSecs});
+
+  Future<String> crateApiNip21ResolveNostrUri(
+      {required String uri,
+      String? currentUserPubkeyHex,
+      required int timeoutSecs});
+
+  Stream<String> crateApiEventBusStreamEventBus();
+
+  Future<String> crateApiEventBusEventBusSubscribe(
+      {required String filterJson,
+      required String hydration,
+      String? currentUserPubkeyHex});
+
+  Future<void> crateApiEventBusEventBusUnsubscribe({required String handle});
+
+  Future<void> crateApiEventBusEventBusUnsubscribeAll();
+
+  Future<String> crateApiEventBusGetEventBusSubscriptions();
+
+  Stream<String> crateApiOperationsStreamOperationProgress();
+
+  Future<bool> crateApiOperationsCancelOperation({required String operationId});
+
+  Future<int> crateApiOperationsCancelAllOperations();
+
+  Future<String> crateApiOperationsGetActiveOperations();
+
+  Future<String> crateApiRelayGetRelayMessages({String? relayUrl});
+
+  Future<void> crateApiRelayClearRelayMessages();
+
+  Future<String> crateApiRelayGetRelayScores();
+
+  Future<void> crateApiRelayResetRelayScores();
+
+  Future<String> crateApiRelayGetRelayPolicy();
+
+  Future<List<String>> crateApiRelaySetRelayPolicy(
+      {required String policyJson});
+
+  Future<String> crateApiRelayCheckRelayUrl(
+      {required String url, required bool discovered});
+
+  Future<String> crateApiRelayGetProxyConfig();
+
+  Future<int> crateApiRelaySetProxyConfig({required String configJson});
+
+  Future<List<String>> crateApiRelayGetFollowerCountProviders();
+
+  Future<void> crateApiRelaySetFollowerCountProviders(
+      {required List<String> providers});
+
+  Future<void> crateApiSyncSchedulerStartSyncScheduler(
+      {required String userPubkeyHex, String? configJson});
+
+  Future<void> crateApiSyncSchedulerStopSyncScheduler();
+
+  Future<void> crateApiSyncSchedulerSetSyncProfile({required String profile});
+
+  Future<void> crateApiSyncSchedulerSetSyncConfig({required String configJson});
+
+  Future<void> crateApiSyncSchedulerTriggerSync({String? task});
+
+  Future<String> crateApiSyncSchedulerGetSyncStatus();
+
+  Stream<String> crateApiSyncSchedulerStreamSyncStatus();
+
+  Stream<String> crateApiRelayStreamSync(
+      {required String filterJson, String? operationId, BigInt? timeoutSecs});
+
+  Future<String> crateApiRelayGetSyncCheckpoints();
+
+  Future<void> crateApiRelayResetSyncCheckpoints();
+
+  Stream<String> crateApiDatabaseDbExportEvents(
+      {required String path,
+      String? filterJson,
+      String? userPubkeyHex,
+      String? operationId,
+      BigInt? timeoutSecs});
+
+  Stream<String> crateApiDatabaseDbImportEvents(
+      {required String path,
+      String? rebroadcastPubkeyHex,
+      List<String>? relayUrls,
+      String? operationId,
+      BigInt? timeoutSecs});
+
+  Future<String?> crateApiDatabaseDbGetRecoveryReport();
+
+  Future<int> crateApiDatabaseDbDeleteBrokenCopies();
+
+  Future<String> crateApiDatabaseDbGetStorageStatus();
+
+  Future<void> crateApiDatabaseDbSetStorageQuota({BigInt? quotaBytes});
+
+  Future<String?> crateApiDatabaseDbEnforceStorageQuota();
+
+  Future<String> crateApiDatabaseDbGetPersistencePolicy();
+
+  Future<String> crateApiDatabaseDbSetPersistencePolicy(
+      {required String policyJson});
+
+  Future<int> crateApiDatabaseDbApplyRetention();
+
+  Future<String> crateApiDatabaseDbSearchEvents(
+      {required String query,
+      required String filterJson,
+      required int offset,
+      required int limit});
+
+  Future<String> crateApiDatabaseDbGetSearchIndexStatus();
+
+  Future<List<String>> crateApiRelayGetSearchRelays();
+
+  Future<void> crateApiRelaySetSearchRelays({required List<String> relayUrls});
+
+  Future<String> crateApiRelaySearchWithRelays(
+      {required String query,
+      required String target,
+      required int limit,
+      BigInt? timeoutSecs,
+      String? currentUserPubkeyHex});
+
+  Future<String> crateApiDatabaseDbGetWebOfTrust(
+      {required String currentUserHex, required List<String> pubkeysHex});
+
+  Future<String> crateApiDatabaseDbGetFollowGraphStats();
+
+  Future<String> crateApiDatabaseDbGetWotFilter();
+
+  Future<String> crateApiDatabaseDbSetWotFilter({required String filterJson});
 }
 
 class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
@@ -3687,13 +3838,17 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
   Future<String> crateApiRelayDiscoverAndConnectOutboxRelays(
       {required List<String> pubkeysHex,
       required BigInt maxOutboxRelays,
-      required BigInt minRelayFrequency}) {
+      required BigInt minRelayFrequency,
+      String? operationId,
+      BigInt? timeoutSecs}) {
     return handler.executeNormal(NormalTask(
       callFfi: (port_) {
         final serializer = SseSerializer(generalizedFrbRustBinding);
         sse_encode_list_String(pubkeysHex, serializer);
         sse_encode_usize(maxOutboxRelays, serializer);
         sse_encode_usize(minRelayFrequency, serializer);
+        sse_encode_opt_String(operationId, serializer);
+        sse_encode_opt_box_autoadd_u_64(timeoutSecs, serializer);
         pdeCallFfi(generalizedFrbRustBinding, serializer,
             funcId: 100, port: port_);
       },
@@ -3702,7 +3857,13 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         decodeErrorData: sse_decode_AnyhowException,
       ),
       constMeta: kCrateApiRelayDiscoverAndConnectOutboxRelaysConstMeta,
-      argValues: [pubkeysHex, maxOutboxRelays, minRelayFrequency],
+      argValues: [
+        pubkeysHex,
+        maxOutboxRelays,
+        minRelayFrequency,
+        operationId,
+        timeoutSecs
+      ],
       apiImpl: this,
     ));
   }
@@ -3710,7 +3871,13 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
   TaskConstMeta get kCrateApiRelayDiscoverAndConnectOutboxRelaysConstMeta =>
       const TaskConstMeta(
         debugName: "discover_and_connect_outbox_relays",
-        argNames: ["pubkeysHex", "maxOutboxRelays", "minRelayFrequency"],
+        argNames: [
+          "pubkeysHex",
+          "maxOutboxRelays",
+          "minRelayFrequency",
+          "operationId",
+          "timeoutSecs"
+        ],
       );
 
   @override
@@ -3843,12 +4010,14 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
 
   @override
   Stream<String> crateApiRelayFetchAllEventsForAuthor(
-      {required String authorHex}) {
+      {required String authorHex, String? operationId, BigInt? timeoutSecs}) {
     final sink = RustStreamSink<String>();
     unawaited(handler.executeNormal(NormalTask(
       callFfi: (port_) {
         final serializer = SseSerializer(generalizedFrbRustBinding);
         sse_encode_String(authorHex, serializer);
+        sse_encode_opt_String(operationId, serializer);
+        sse_encode_opt_box_autoadd_u_64(timeoutSecs, serializer);
         sse_encode_StreamSink_String_Sse(sink, serializer);
         pdeCallFfi(generalizedFrbRustBinding, serializer,
             funcId: 106, port: port_);
@@ -3858,7 +4027,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         decodeErrorData: sse_decode_AnyhowException,
       ),
       constMeta: kCrateApiRelayFetchAllEventsForAuthorConstMeta,
-      argValues: [authorHex, sink],
+      argValues: [authorHex, operationId, timeoutSecs, sink],
       apiImpl: this,
     )));
     return sink.stream;
@@ -3867,7 +4036,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
   TaskConstMeta get kCrateApiRelayFetchAllEventsForAuthorConstMeta =>
       const TaskConstMeta(
         debugName: "fetch_all_events_for_author",
-        argNames: ["authorHex", "sink"],
+        argNames: ["authorHex", "operationId", "timeoutSecs", "sink"],
       );
 
   @override
@@ -4160,7 +4329,9 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
   Stream<String> crateApiRelayFetchProfileEvents(
       {required String authorHex,
       required String kindsStr,
-      required PlatformInt64 sinceTimestamp}) {
+      required PlatformInt64 sinceTimestamp,
+      String? operationId,
+      BigInt? timeoutSecs}) {
     final sink = RustStreamSink<String>();
     unawaited(handler.executeNormal(NormalTask(
       callFfi: (port_) {
@@ -4168,6 +4339,8 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         sse_encode_String(authorHex, serializer);
         sse_encode_String(kindsStr, serializer);
         sse_encode_i_64(sinceTimestamp, serializer);
+        sse_encode_opt_String(operationId, serializer);
+        sse_encode_opt_box_autoadd_u_64(timeoutSecs, serializer);
         sse_encode_StreamSink_String_Sse(sink, serializer);
         pdeCallFfi(generalizedFrbRustBinding, serializer,
             funcId: 117, port: port_);
@@ -4177,7 +4350,14 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         decodeErrorData: sse_decode_AnyhowException,
       ),
       constMeta: kCrateApiRelayFetchProfileEventsConstMeta,
-      argValues: [authorHex, kindsStr, sinceTimestamp, sink],
+      argValues: [
+        authorHex,
+        kindsStr,
+        sinceTimestamp,
+        operationId,
+        timeoutSecs,
+        sink
+      ],
       apiImpl: this,
     )));
     return sink.stream;
@@ -4186,7 +4366,14 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
   TaskConstMeta get kCrateApiRelayFetchProfileEventsConstMeta =>
       const TaskConstMeta(
         debugName: "fetch_profile_events",
-        argNames: ["authorHex", "kindsStr", "sinceTimestamp", "sink"],
+        argNames: [
+          "authorHex",
+          "kindsStr",
+          "sinceTimestamp",
+          "operationId",
+          "timeoutSecs",
+          "sink"
+        ],
       );
 
   @override
@@ -5188,13 +5375,18 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
 
   @override
   Stream<String> crateApiRelayStreamBroadcastEvents(
-      {required String eventsJson, List<String>? relayUrls}) {
+      {required String eventsJson,
+      List<String>? relayUrls,
+      String? operationId,
+      BigInt? timeoutSecs}) {
     final sink = RustStreamSink<String>();
     unawaited(handler.executeNormal(NormalTask(
       callFfi: (port_) {
         final serializer = SseSerializer(generalizedFrbRustBinding);
         sse_encode_String(eventsJson, serializer);
         sse_encode_opt_list_String(relayUrls, serializer);
+        sse_encode_opt_String(operationId, serializer);
+        sse_encode_opt_box_autoadd_u_64(timeoutSecs, serializer);
         sse_encode_StreamSink_String_Sse(sink, serializer);
         pdeCallFfi(generalizedFrbRustBinding, serializer,
             funcId: 158, port: port_);
@@ -5204,7 +5396,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         decodeErrorData: sse_decode_AnyhowException,
       ),
       constMeta: kCrateApiRelayStreamBroadcastEventsConstMeta,
-      argValues: [eventsJson, relayUrls, sink],
+      argValues: [eventsJson, relayUrls, operationId, timeoutSecs, sink],
       apiImpl: this,
     )));
     return sink.stream;
@@ -5213,18 +5405,29 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
   TaskConstMeta get kCrateApiRelayStreamBroadcastEventsConstMeta =>
       const TaskConstMeta(
         debugName: "stream_broadcast_events",
-        argNames: ["eventsJson", "relayUrls", "sink"],
+        argNames: [
+          "eventsJson",
+          "relayUrls",
+          "operationId",
+          "timeoutSecs",
+          "sink"
+        ],
       );
 
   @override
   Stream<String> crateApiRelayStreamInteractionCounts(
-      {required List<String> noteIds, String? userPubkeyHex}) {
+      {required List<String> noteIds,
+      String? userPubkeyHex,
+      String? operationId,
+      BigInt? timeoutSecs}) {
     final sink = RustStreamSink<String>();
     unawaited(handler.executeNormal(NormalTask(
       callFfi: (port_) {
         final serializer = SseSerializer(generalizedFrbRustBinding);
         sse_encode_list_String(noteIds, serializer);
         sse_encode_opt_String(userPubkeyHex, serializer);
+        sse_encode_opt_String(operationId, serializer);
+        sse_encode_opt_box_autoadd_u_64(timeoutSecs, serializer);
         sse_encode_StreamSink_String_Sse(sink, serializer);
         pdeCallFfi(generalizedFrbRustBinding, serializer,
             funcId: 159, port: port_);
@@ -5234,7 +5437,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         decodeErrorData: sse_decode_AnyhowException,
       ),
       constMeta: kCrateApiRelayStreamInteractionCountsConstMeta,
-      argValues: [noteIds, userPubkeyHex, sink],
+      argValues: [noteIds, userPubkeyHex, operationId, timeoutSecs, sink],
       apiImpl: this,
     )));
     return sink.stream;
@@ -5243,15 +5446,24 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
   TaskConstMeta get kCrateApiRelayStreamInteractionCountsConstMeta =>
       const TaskConstMeta(
         debugName: "stream_interaction_counts",
-        argNames: ["noteIds", "userPubkeyHex", "sink"],
+        argNames: [
+          "noteIds",
+          "userPubkeyHex",
+          "operationId",
+          "timeoutSecs",
+          "sink"
+        ],
       );
 
   @override
-  Stream<String> crateApiRelayStreamRelayStatus() {
+  Stream<String> crateApiRelayStreamRelayStatus(
+      {String? operationId, BigInt? timeoutSecs}) {
     final sink = RustStreamSink<String>();
     unawaited(handler.executeNormal(NormalTask(
       callFfi: (port_) {
         final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_opt_String(operationId, serializer);
+        sse_encode_opt_box_autoadd_u_64(timeoutSecs, serializer);
         sse_encode_StreamSink_String_Sse(sink, serializer);
         pdeCallFfi(generalizedFrbRustBinding, serializer,
             funcId: 160, port: port_);
@@ -5261,7 +5473,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         decodeErrorData: sse_decode_AnyhowException,
       ),
       constMeta: kCrateApiRelayStreamRelayStatusConstMeta,
-      argValues: [sink],
+      argValues: [operationId, timeoutSecs, sink],
       apiImpl: this,
     )));
     return sink.stream;
@@ -5270,16 +5482,19 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
   TaskConstMeta get kCrateApiRelayStreamRelayStatusConstMeta =>
       const TaskConstMeta(
         debugName: "stream_relay_status",
-        argNames: ["sink"],
+        argNames: ["operationId", "timeoutSecs", "sink"],
       );
 
   @override
-  Stream<String> crateApiRelaySubscribeToEvents({required String filterJson}) {
+  Stream<String> crateApiRelaySubscribeToEvents(
+      {required String filterJson, String? operationId, BigInt? timeoutSecs}) {
     final sink = RustStreamSink<String>();
     unawaited(handler.executeNormal(NormalTask(
       callFfi: (port_) {
         final serializer = SseSerializer(generalizedFrbRustBinding);
         sse_encode_String(filterJson, serializer);
+        sse_encode_opt_String(operationId, serializer);
+        sse_encode_opt_box_autoadd_u_64(timeoutSecs, serializer);
         sse_encode_StreamSink_String_Sse(sink, serializer);
         pdeCallFfi(generalizedFrbRustBinding, serializer,
             funcId: 161, port: port_);
@@ -5289,7 +5504,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         decodeErrorData: sse_decode_AnyhowException,
       ),
       constMeta: kCrateApiRelaySubscribeToEventsConstMeta,
-      argValues: [filterJson, sink],
+      argValues: [filterJson, operationId, timeoutSecs, sink],
       apiImpl: this,
     )));
     return sink.stream;
@@ -5298,7 +5513,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
   TaskConstMeta get kCrateApiRelaySubscribeToEventsConstMeta =>
       const TaskConstMeta(
         debugName: "subscribe_to_events",
-        argNames: ["filterJson", "sink"],
+        argNames: ["filterJson", "operationId", "timeoutSecs", "sink"],
       );
 
   @override
@@ -5327,12 +5542,17 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
 
   @override
   Future<int> crateApiRelaySyncRepliesRecursive(
-      {required String noteId, required int maxDepth}) {
+      {required String noteId,
+      required int maxDepth,
+      String? operationId,
+      BigInt? timeoutSecs}) {
     return handler.executeNormal(NormalTask(
       callFfi: (port_) {
         final serializer = SseSerializer(generalizedFrbRustBinding);
         sse_encode_String(noteId, serializer);
         sse_encode_u_32(maxDepth, serializer);
+        sse_encode_opt_String(operationId, serializer);
+        sse_encode_opt_box_autoadd_u_64(timeoutSecs, serializer);
         pdeCallFfi(generalizedFrbRustBinding, serializer,
             funcId: 163, port: port_);
       },
@@ -5341,7 +5561,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         decodeErrorData: sse_decode_AnyhowException,
       ),
       constMeta: kCrateApiRelaySyncRepliesRecursiveConstMeta,
-      argValues: [noteId, maxDepth],
+      argValues: [noteId, maxDepth, operationId, timeoutSecs],
       apiImpl: this,
     ));
   }
@@ -5349,7 +5569,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
   TaskConstMeta get kCrateApiRelaySyncRepliesRecursiveConstMeta =>
       const TaskConstMeta(
         debugName: "sync_replies_recursive",
-        argNames: ["noteId", "maxDepth"],
+        argNames: ["noteId", "maxDepth", "operationId", "timeoutSecs"],
       );
 
   @override
@@ -5580,6 +5800,1337 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         argNames: ["timeoutSecs"],
       );
 
+  @override
+  Future<String> crateApiNip21ResolveNostrUri(
+      {required String uri,
+      String? currentUserPubkeyHex,
+      required int timeoutSecs}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_String(uri, serializer);
+        sse_encode_opt_String(currentUserPubkeyHex, serializer);
+        sse_encode_u_32(timeoutSecs, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 173, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiNip21ResolveNostrUriConstMeta,
+      argValues: [uri, currentUserPubkeyHex, timeoutSecs],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiNip21ResolveNostrUriConstMeta =>
+      const TaskConstMeta(
+        debugName: "resolve_nostr_uri",
+        argNames: ["uri", "currentUserPubkeyHex", "timeoutSecs"],
+      );
+
+  @override
+  Stream<String> crateApiEventBusStreamEventBus() {
+    final sink = RustStreamSink<String>();
+    unawaited(handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_StreamSink_String_Sse(sink, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 174, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiEventBusStreamEventBusConstMeta,
+      argValues: [sink],
+      apiImpl: this,
+    )));
+    return sink.stream;
+  }
+
+  TaskConstMeta get kCrateApiEventBusStreamEventBusConstMeta =>
+      const TaskConstMeta(
+        debugName: "stream_event_bus",
+        argNames: ["sink"],
+      );
+
+  @override
+  Future<String> crateApiEventBusEventBusSubscribe(
+      {required String filterJson,
+      required String hydration,
+      String? currentUserPubkeyHex}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_String(filterJson, serializer);
+        sse_encode_String(hydration, serializer);
+        sse_encode_opt_String(currentUserPubkeyHex, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 175, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiEventBusEventBusSubscribeConstMeta,
+      argValues: [filterJson, hydration, currentUserPubkeyHex],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiEventBusEventBusSubscribeConstMeta =>
+      const TaskConstMeta(
+        debugName: "event_bus_subscribe",
+        argNames: ["filterJson", "hydration", "currentUserPubkeyHex"],
+      );
+
+  @override
+  Future<void> crateApiEventBusEventBusUnsubscribe({required String handle}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_String(handle, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 176, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiEventBusEventBusUnsubscribeConstMeta,
+      argValues: [handle],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiEventBusEventBusUnsubscribeConstMeta =>
+      const TaskConstMeta(
+        debugName: "event_bus_unsubscribe",
+        argNames: ["handle"],
+      );
+
+  @override
+  Future<void> crateApiEventBusEventBusUnsubscribeAll() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 177, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiEventBusEventBusUnsubscribeAllConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiEventBusEventBusUnsubscribeAllConstMeta =>
+      const TaskConstMeta(
+        debugName: "event_bus_unsubscribe_all",
+        argNames: [],
+      );
+
+  @override
+  Future<String> crateApiEventBusGetEventBusSubscriptions() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 178, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiEventBusGetEventBusSubscriptionsConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiEventBusGetEventBusSubscriptionsConstMeta =>
+      const TaskConstMeta(
+        debugName: "get_event_bus_subscriptions",
+        argNames: [],
+      );
+
+  @override
+  Stream<String> crateApiOperationsStreamOperationProgress() {
+    final sink = RustStreamSink<String>();
+    unawaited(handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_StreamSink_String_Sse(sink, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 179, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiOperationsStreamOperationProgressConstMeta,
+      argValues: [sink],
+      apiImpl: this,
+    )));
+    return sink.stream;
+  }
+
+  TaskConstMeta get kCrateApiOperationsStreamOperationProgressConstMeta =>
+      const TaskConstMeta(
+        debugName: "stream_operation_progress",
+        argNames: ["sink"],
+      );
+
+  @override
+  Future<bool> crateApiOperationsCancelOperation(
+      {required String operationId}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_String(operationId, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 180, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_bool,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiOperationsCancelOperationConstMeta,
+      argValues: [operationId],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiOperationsCancelOperationConstMeta =>
+      const TaskConstMeta(
+        debugName: "cancel_operation",
+        argNames: ["operationId"],
+      );
+
+  @override
+  Future<int> crateApiOperationsCancelAllOperations() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 181, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_u_32,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiOperationsCancelAllOperationsConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiOperationsCancelAllOperationsConstMeta =>
+      const TaskConstMeta(
+        debugName: "cancel_all_operations",
+        argNames: [],
+      );
+
+  @override
+  Future<String> crateApiOperationsGetActiveOperations() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 182, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiOperationsGetActiveOperationsConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiOperationsGetActiveOperationsConstMeta =>
+      const TaskConstMeta(
+        debugName: "get_active_operations",
+        argNames: [],
+      );
+
+  @override
+  Future<String> crateApiRelayGetRelayMessages({String? relayUrl}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_opt_String(relayUrl, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 183, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiRelayGetRelayMessagesConstMeta,
+      argValues: [relayUrl],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiRelayGetRelayMessagesConstMeta =>
+      const TaskConstMeta(
+        debugName: "get_relay_messages",
+        argNames: ["relayUrl"],
+      );
+
+  @override
+  Future<void> crateApiRelayClearRelayMessages() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 184, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiRelayClearRelayMessagesConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiRelayClearRelayMessagesConstMeta =>
+      const TaskConstMeta(
+        debugName: "clear_relay_messages",
+        argNames: [],
+      );
+
+  @override
+  Future<String> crateApiRelayGetRelayScores() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 185, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiRelayGetRelayScoresConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiRelayGetRelayScoresConstMeta =>
+      const TaskConstMeta(
+        debugName: "get_relay_scores",
+        argNames: [],
+      );
+
+  @override
+  Future<void> crateApiRelayResetRelayScores() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 186, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiRelayResetRelayScoresConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiRelayResetRelayScoresConstMeta =>
+      const TaskConstMeta(
+        debugName: "reset_relay_scores",
+        argNames: [],
+      );
+
+  @override
+  Future<String> crateApiRelayGetRelayPolicy() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 187, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiRelayGetRelayPolicyConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiRelayGetRelayPolicyConstMeta =>
+      const TaskConstMeta(
+        debugName: "get_relay_policy",
+        argNames: [],
+      );
+
+  @override
+  Future<List<String>> crateApiRelaySetRelayPolicy(
+      {required String policyJson}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_String(policyJson, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 188, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_list_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiRelaySetRelayPolicyConstMeta,
+      argValues: [policyJson],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiRelaySetRelayPolicyConstMeta =>
+      const TaskConstMeta(
+        debugName: "set_relay_policy",
+        argNames: ["policyJson"],
+      );
+
+  @override
+  Future<String> crateApiRelayCheckRelayUrl(
+      {required String url, required bool discovered}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_String(url, serializer);
+        sse_encode_bool(discovered, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 189, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiRelayCheckRelayUrlConstMeta,
+      argValues: [url, discovered],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiRelayCheckRelayUrlConstMeta => const TaskConstMeta(
+        debugName: "check_relay_url",
+        argNames: ["url", "discovered"],
+      );
+
+  @override
+  Future<String> crateApiRelayGetProxyConfig() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 190, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiRelayGetProxyConfigConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiRelayGetProxyConfigConstMeta =>
+      const TaskConstMeta(
+        debugName: "get_proxy_config",
+        argNames: [],
+      );
+
+  @override
+  Future<int> crateApiRelaySetProxyConfig({required String configJson}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_String(configJson, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 191, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_u_32,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiRelaySetProxyConfigConstMeta,
+      argValues: [configJson],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiRelaySetProxyConfigConstMeta =>
+      const TaskConstMeta(
+        debugName: "set_proxy_config",
+        argNames: ["configJson"],
+      );
+
+  @override
+  Future<List<String>> crateApiRelayGetFollowerCountProviders() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 192, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_list_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiRelayGetFollowerCountProvidersConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiRelayGetFollowerCountProvidersConstMeta =>
+      const TaskConstMeta(
+        debugName: "get_follower_count_providers",
+        argNames: [],
+      );
+
+  @override
+  Future<void> crateApiRelaySetFollowerCountProviders(
+      {required List<String> providers}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_list_String(providers, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 193, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiRelaySetFollowerCountProvidersConstMeta,
+      argValues: [providers],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiRelaySetFollowerCountProvidersConstMeta =>
+      const TaskConstMeta(
+        debugName: "set_follower_count_providers",
+        argNames: ["providers"],
+      );
+
+  @override
+  Future<void> crateApiSyncSchedulerStartSyncScheduler(
+      {required String userPubkeyHex, String? configJson}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_String(userPubkeyHex, serializer);
+        sse_encode_opt_String(configJson, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 194, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiSyncSchedulerStartSyncSchedulerConstMeta,
+      argValues: [userPubkeyHex, configJson],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiSyncSchedulerStartSyncSchedulerConstMeta =>
+      const TaskConstMeta(
+        debugName: "start_sync_scheduler",
+        argNames: ["userPubkeyHex", "configJson"],
+      );
+
+  @override
+  Future<void> crateApiSyncSchedulerStopSyncScheduler() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 195, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiSyncSchedulerStopSyncSchedulerConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiSyncSchedulerStopSyncSchedulerConstMeta =>
+      const TaskConstMeta(
+        debugName: "stop_sync_scheduler",
+        argNames: [],
+      );
+
+  @override
+  Future<void> crateApiSyncSchedulerSetSyncProfile({required String profile}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_String(profile, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 196, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiSyncSchedulerSetSyncProfileConstMeta,
+      argValues: [profile],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiSyncSchedulerSetSyncProfileConstMeta =>
+      const TaskConstMeta(
+        debugName: "set_sync_profile",
+        argNames: ["profile"],
+      );
+
+  @override
+  Future<void> crateApiSyncSchedulerSetSyncConfig(
+      {required String configJson}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_String(configJson, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 197, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiSyncSchedulerSetSyncConfigConstMeta,
+      argValues: [configJson],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiSyncSchedulerSetSyncConfigConstMeta =>
+      const TaskConstMeta(
+        debugName: "set_sync_config",
+        argNames: ["configJson"],
+      );
+
+  @override
+  Future<void> crateApiSyncSchedulerTriggerSync({String? task}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_opt_String(task, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 198, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiSyncSchedulerTriggerSyncConstMeta,
+      argValues: [task],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiSyncSchedulerTriggerSyncConstMeta =>
+      const TaskConstMeta(
+        debugName: "trigger_sync",
+        argNames: ["task"],
+      );
+
+  @override
+  Future<String> crateApiSyncSchedulerGetSyncStatus() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 199, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiSyncSchedulerGetSyncStatusConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiSyncSchedulerGetSyncStatusConstMeta =>
+      const TaskConstMeta(
+        debugName: "get_sync_status",
+        argNames: [],
+      );
+
+  @override
+  Stream<String> crateApiSyncSchedulerStreamSyncStatus() {
+    final sink = RustStreamSink<String>();
+    unawaited(handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_StreamSink_String_Sse(sink, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 200, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiSyncSchedulerStreamSyncStatusConstMeta,
+      argValues: [sink],
+      apiImpl: this,
+    )));
+    return sink.stream;
+  }
+
+  TaskConstMeta get kCrateApiSyncSchedulerStreamSyncStatusConstMeta =>
+      const TaskConstMeta(
+        debugName: "stream_sync_status",
+        argNames: ["sink"],
+      );
+
+  @override
+  Stream<String> crateApiRelayStreamSync(
+      {required String filterJson, String? operationId, BigInt? timeoutSecs}) {
+    final sink = RustStreamSink<String>();
+    unawaited(handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_String(filterJson, serializer);
+        sse_encode_opt_String(operationId, serializer);
+        sse_encode_opt_box_autoadd_u_64(timeoutSecs, serializer);
+        sse_encode_StreamSink_String_Sse(sink, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 201, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiRelayStreamSyncConstMeta,
+      argValues: [filterJson, operationId, timeoutSecs, sink],
+      apiImpl: this,
+    )));
+    return sink.stream;
+  }
+
+  TaskConstMeta get kCrateApiRelayStreamSyncConstMeta => const TaskConstMeta(
+        debugName: "stream_sync",
+        argNames: ["filterJson", "operationId", "timeoutSecs", "sink"],
+      );
+
+  @override
+  Future<String> crateApiRelayGetSyncCheckpoints() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 202, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiRelayGetSyncCheckpointsConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiRelayGetSyncCheckpointsConstMeta =>
+      const TaskConstMeta(
+        debugName: "get_sync_checkpoints",
+        argNames: [],
+      );
+
+  @override
+  Future<void> crateApiRelayResetSyncCheckpoints() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 203, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiRelayResetSyncCheckpointsConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiRelayResetSyncCheckpointsConstMeta =>
+      const TaskConstMeta(
+        debugName: "reset_sync_checkpoints",
+        argNames: [],
+      );
+
+  @override
+  Stream<String> crateApiDatabaseDbExportEvents(
+      {required String path,
+      String? filterJson,
+      String? userPubkeyHex,
+      String? operationId,
+      BigInt? timeoutSecs}) {
+    final sink = RustStreamSink<String>();
+    unawaited(handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_String(path, serializer);
+        sse_encode_opt_String(filterJson, serializer);
+        sse_encode_opt_String(userPubkeyHex, serializer);
+        sse_encode_opt_String(operationId, serializer);
+        sse_encode_opt_box_autoadd_u_64(timeoutSecs, serializer);
+        sse_encode_StreamSink_String_Sse(sink, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 204, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiDatabaseDbExportEventsConstMeta,
+      argValues: [
+        path,
+        filterJson,
+        userPubkeyHex,
+        operationId,
+        timeoutSecs,
+        sink
+      ],
+      apiImpl: this,
+    )));
+    return sink.stream;
+  }
+
+  TaskConstMeta get kCrateApiDatabaseDbExportEventsConstMeta =>
+      const TaskConstMeta(
+        debugName: "db_export_events",
+        argNames: [
+          "path",
+          "filterJson",
+          "userPubkeyHex",
+          "operationId",
+          "timeoutSecs",
+          "sink"
+        ],
+      );
+
+  @override
+  Stream<String> crateApiDatabaseDbImportEvents(
+      {required String path,
+      String? rebroadcastPubkeyHex,
+      List<String>? relayUrls,
+      String? operationId,
+      BigInt? timeoutSecs}) {
+    final sink = RustStreamSink<String>();
+    unawaited(handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_String(path, serializer);
+        sse_encode_opt_String(rebroadcastPubkeyHex, serializer);
+        sse_encode_opt_list_String(relayUrls, serializer);
+        sse_encode_opt_String(operationId, serializer);
+        sse_encode_opt_box_autoadd_u_64(timeoutSecs, serializer);
+        sse_encode_StreamSink_String_Sse(sink, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 205, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiDatabaseDbImportEventsConstMeta,
+      argValues: [
+        path,
+        rebroadcastPubkeyHex,
+        relayUrls,
+        operationId,
+        timeoutSecs,
+        sink
+      ],
+      apiImpl: this,
+    )));
+    return sink.stream;
+  }
+
+  TaskConstMeta get kCrateApiDatabaseDbImportEventsConstMeta =>
+      const TaskConstMeta(
+        debugName: "db_import_events",
+        argNames: [
+          "path",
+          "rebroadcastPubkeyHex",
+          "relayUrls",
+          "operationId",
+          "timeoutSecs",
+          "sink"
+        ],
+      );
+
+  @override
+  Future<String?> crateApiDatabaseDbGetRecoveryReport() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 206, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_opt_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiDatabaseDbGetRecoveryReportConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiDatabaseDbGetRecoveryReportConstMeta =>
+      const TaskConstMeta(
+        debugName: "db_get_recovery_report",
+        argNames: [],
+      );
+
+  @override
+  Future<int> crateApiDatabaseDbDeleteBrokenCopies() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 207, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_u_32,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiDatabaseDbDeleteBrokenCopiesConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiDatabaseDbDeleteBrokenCopiesConstMeta =>
+      const TaskConstMeta(
+        debugName: "db_delete_broken_copies",
+        argNames: [],
+      );
+
+  @override
+  Future<String> crateApiDatabaseDbGetStorageStatus() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 208, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiDatabaseDbGetStorageStatusConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiDatabaseDbGetStorageStatusConstMeta =>
+      const TaskConstMeta(
+        debugName: "db_get_storage_status",
+        argNames: [],
+      );
+
+  @override
+  Future<void> crateApiDatabaseDbSetStorageQuota({BigInt? quotaBytes}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_opt_box_autoadd_u_64(quotaBytes, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 209, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiDatabaseDbSetStorageQuotaConstMeta,
+      argValues: [quotaBytes],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiDatabaseDbSetStorageQuotaConstMeta =>
+      const TaskConstMeta(
+        debugName: "db_set_storage_quota",
+        argNames: ["quotaBytes"],
+      );
+
+  @override
+  Future<String?> crateApiDatabaseDbEnforceStorageQuota() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 210, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_opt_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiDatabaseDbEnforceStorageQuotaConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiDatabaseDbEnforceStorageQuotaConstMeta =>
+      const TaskConstMeta(
+        debugName: "db_enforce_storage_quota",
+        argNames: [],
+      );
+
+  @override
+  Future<String> crateApiDatabaseDbGetPersistencePolicy() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 211, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiDatabaseDbGetPersistencePolicyConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiDatabaseDbGetPersistencePolicyConstMeta =>
+      const TaskConstMeta(
+        debugName: "db_get_persistence_policy",
+        argNames: [],
+      );
+
+  @override
+  Future<String> crateApiDatabaseDbSetPersistencePolicy(
+      {required String policyJson}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_String(policyJson, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 212, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiDatabaseDbSetPersistencePolicyConstMeta,
+      argValues: [policyJson],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiDatabaseDbSetPersistencePolicyConstMeta =>
+      const TaskConstMeta(
+        debugName: "db_set_persistence_policy",
+        argNames: ["policyJson"],
+      );
+
+  @override
+  Future<int> crateApiDatabaseDbApplyRetention() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 213, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_u_32,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiDatabaseDbApplyRetentionConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiDatabaseDbApplyRetentionConstMeta =>
+      const TaskConstMeta(
+        debugName: "db_apply_retention",
+        argNames: [],
+      );
+
+  @override
+  Future<String> crateApiDatabaseDbSearchEvents(
+      {required String query,
+      required String filterJson,
+      required int offset,
+      required int limit}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_String(query, serializer);
+        sse_encode_String(filterJson, serializer);
+        sse_encode_u_32(offset, serializer);
+        sse_encode_u_32(limit, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 214, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiDatabaseDbSearchEventsConstMeta,
+      argValues: [query, filterJson, offset, limit],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiDatabaseDbSearchEventsConstMeta =>
+      const TaskConstMeta(
+        debugName: "db_search_events",
+        argNames: ["query", "filterJson", "offset", "limit"],
+      );
+
+  @override
+  Future<String> crateApiDatabaseDbGetSearchIndexStatus() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 215, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiDatabaseDbGetSearchIndexStatusConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiDatabaseDbGetSearchIndexStatusConstMeta =>
+      const TaskConstMeta(
+        debugName: "db_get_search_index_status",
+        argNames: [],
+      );
+
+  @override
+  Future<List<String>> crateApiRelayGetSearchRelays() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 216, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_list_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiRelayGetSearchRelaysConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiRelayGetSearchRelaysConstMeta =>
+      const TaskConstMeta(
+        debugName: "get_search_relays",
+        argNames: [],
+      );
+
+  @override
+  Future<void> crateApiRelaySetSearchRelays({required List<String> relayUrls}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_list_String(relayUrls, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 217, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiRelaySetSearchRelaysConstMeta,
+      argValues: [relayUrls],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiRelaySetSearchRelaysConstMeta =>
+      const TaskConstMeta(
+        debugName: "set_search_relays",
+        argNames: ["relayUrls"],
+      );
+
+  @override
+  Future<String> crateApiRelaySearchWithRelays(
+      {required String query,
+      required String target,
+      required int limit,
+      BigInt? timeoutSecs,
+      String? currentUserPubkeyHex}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_String(query, serializer);
+        sse_encode_String(target, serializer);
+        sse_encode_u_32(limit, serializer);
+        sse_encode_opt_box_autoadd_u_64(timeoutSecs, serializer);
+        sse_encode_opt_String(currentUserPubkeyHex, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 218, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiRelaySearchWithRelaysConstMeta,
+      argValues: [query, target, limit, timeoutSecs, currentUserPubkeyHex],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiRelaySearchWithRelaysConstMeta =>
+      const TaskConstMeta(
+        debugName: "search_with_relays",
+        argNames: [
+          "query",
+          "target",
+          "limit",
+          "timeoutSecs",
+          "currentUserPubkeyHex"
+        ],
+      );
+
+  @override
+  Future<String> crateApiDatabaseDbGetWebOfTrust(
+      {required String currentUserHex, required List<String> pubkeysHex}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_String(currentUserHex, serializer);
+        sse_encode_list_String(pubkeysHex, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 219, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiDatabaseDbGetWebOfTrustConstMeta,
+      argValues: [currentUserHex, pubkeysHex],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiDatabaseDbGetWebOfTrustConstMeta =>
+      const TaskConstMeta(
+        debugName: "db_get_web_of_trust",
+        argNames: ["currentUserHex", "pubkeysHex"],
+      );
+
+  @override
+  Future<String> crateApiDatabaseDbGetFollowGraphStats() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 220, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiDatabaseDbGetFollowGraphStatsConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiDatabaseDbGetFollowGraphStatsConstMeta =>
+      const TaskConstMeta(
+        debugName: "db_get_follow_graph_stats",
+        argNames: [],
+      );
+
+  @override
+  Future<String> crateApiDatabaseDbGetWotFilter() {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 221, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiDatabaseDbGetWotFilterConstMeta,
+      argValues: [],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiDatabaseDbGetWotFilterConstMeta =>
+      const TaskConstMeta(
+        debugName: "db_get_wot_filter",
+        argNames: [],
+      );
+
+  @override
+  Future<String> crateApiDatabaseDbSetWotFilter({required String filterJson}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_String(filterJson, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 222, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kCrateApiDatabaseDbSetWotFilterConstMeta,
+      argValues: [filterJson],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiDatabaseDbSetWotFilterConstMeta =>
+      const TaskConstMeta(
+        debugName: "db_set_wot_filter",
+        argNames: ["filterJson"],
+      );
+
   @protected
   AnyhowException dco_decode_AnyhowException(dynamic raw) {
     // Codec=Dco (DartCObject based), see doc to use other codecs
@@ -6,11 +6,15 @@
 import 'api/cashu.dart';
 import 'api/crypto.dart';
 import 'api/database.dart';
+import 'api/event_bus.dart';
 import 'api/events.dart';
 import 'api/nip17.dart';
 import 'api/nip19.dart';
+import 'api/nip21.dart';
 import 'api/nwc.dart';
+import 'api/operations.dart';
 import 'api/relay.dart';
+import 'api/sync_scheduler.dart';
 import 'dart:async';
 import 'dart:convert';
 import 'dart:ffi' as ffi;
@@ -10,6 +10,7 @@ import 'package:qiqstr/ui/widgets/note/note_widget.dart';
 import 'package:qiqstr/ui/widgets/note/focused_note_widget.dart';
 import '../../widgets/common/common_buttons.dart';
 import '../../widgets/common/list_separator_widget.dart';
+import '../../widgets/common/other_bucket_toggle_widget.dart';
 import '../../widgets/common/top_action_bar_widget.dart';
 import '../../theme/theme_manager.dart';
 import '../../../core/di/app_di.dart';
@@ -40,6 +41,7 @@ class _ThreadPageState extends State<ThreadPage> {
   final Map<String, GlobalKey> _noteKeys = {};
 
   int _visibleRepliesCount = 20;
+  bool _showOtherReplies = false;
   static const int _repliesPerPage = 20;
   static const int _maxInitialReplies = 100;
   static const int _maxNestedReplies = 3;
@@ -483,39 +485,61 @@ class _ThreadPageState extends State<ThreadPage> {
       );
     }
 
+    final mainReplies =
+        directReplies.where((r) => r['bucket'] != 'other').toList();
+    final otherReplies =
+        directReplies.where((r) => r['bucket'] == 'other').toList();
     final maxVisible = math.min(_visibleRepliesCount, _maxInitialReplies);
-    final visibleReplies = directReplies.take(maxVisible).toList();
-    final hasMoreReplies = directReplies.length > maxVisible;
+    final visibleReplies = mainReplies.take(maxVisible).toList();
+    final hasMoreReplies = mainReplies.length > maxVisible;
+    final shownOtherReplies =
+        _showOtherReplies ? otherReplies : const <Map<String, dynamic>>[];
+    final otherToggleIndex = visibleReplies.length + (hasMoreReplies ? 1 : 0);
+
+    Widget buildReply(Map<String, dynamic> reply, bool showSeparator) {
+      return Column(
+        key: ValueKey('reply_${reply['id'] as String? ?? ''}'),
+        mainAxisSize: MainAxisSize.min,
+        children: [
+          _buildThreadReply(
+            context,
+            state,
+            reply,
+            threadStructure,
+            0,
+          ),
+          if (showSeparator) const ListSeparatorWidget(),
+        ],
+      );
+    }
 
     return SliverList(
       delegate: SliverChildBuilderDelegate(
         (context, index) {
           if (index < visibleReplies.length) {
-            final reply = visibleReplies[index];
             final showSeparator = index < visibleReplies.length - 1;
-            return Column(
-              key: ValueKey('reply_${reply['id'] as String? ?? ''}'),
-              mainAxisSize: MainAxisSize.min,
-              children: [
-                _buildThreadReply(
-                  context,
-                  state,
-                  reply,
-                  threadStructure,
-                  0,
-                ),
-                if (showSeparator) const ListSeparatorWidget(),
-              ],
-            );
+            return buildReply(visibleReplies[index], showSeparator);
           } else if (index == visibleReplies.length && hasMoreReplies) {
             return Padding(
               padding: const EdgeInsets.only(top: 8.0, bottom: 4.0),
-              child: _buildLoadMoreButton(context, directReplies.length),
+              child: _buildLoadMoreButton(context, mainReplies.length),
+            );
+          } else if (index == otherToggleIndex) {
+            return OtherBucketToggleWidget(
+              count: otherReplies.length,
+              expanded: _showOtherReplies,
+              onTap: () =>
+                  setState(() => _showOtherReplies = !_showOtherReplies),
             );
+          } else if (index > otherToggleIndex) {
+            final otherIndex = index - otherToggleIndex - 1;
+            final showSeparator = otherIndex < shownOtherReplies.length - 1;
+            return buildReply(shownOtherReplies[otherIndex], showSeparator);
           }
           return const SizedBox.shrink();
         },
-        childCount: visibleReplies.length + (hasMoreReplies ? 1 : 0),
+        childCount: otherToggleIndex +
+            (otherReplies.isNotEmpty ? 1 + shownOtherReplies.length : 0),
         addAutomaticKeepAlives: false,
         addRepaintBoundaries: true,
       ),
@@ -7,6 +7,7 @@ import 'package:flutter_bloc/flutter_bloc.dart';
 
 import '../../theme/theme_manager.dart';
 import '../../widgets/common/list_separator_widget.dart';
+import '../../widgets/common/other_bucket_toggle_widget.dart';
 import '../../widgets/note/quote_widget.dart';
 import '../../../presentation/blocs/notification/notification_bloc.dart';
 import '../../../presentation/blocs/notification/notification_event.dart'
@@ -30,6 +31,7 @@ class NotificationPage extends StatefulWidget {
 class _NotificationPageState extends State<NotificationPage> {
   final ScrollController _scrollController = ScrollController();
   NotificationBloc? _bloc;
+  bool _showOther = false;
 
   @override
   void initState() {
@@ -93,7 +95,13 @@ class _NotificationPageState extends State<NotificationPage> {
         return _buildEmptyContent(context);
       }
 
-      final grouped = _groupNotifications(notifications);
+      final main = notifications.where((n) => n['bucket'] != 'other').toList();
+      final other = notifications.where((n) => n['bucket'] == 'other').toList();
+      final rows = [
+        ..._groupNotifications(main),
+        if (other.isNotEmpty) {'isOtherToggle': true},
+        if (_showOther) ..._groupNotifications(other),
+      ];
       final showLoadMore = !state.hasReachedEnd;
       final showLoadingMore = state.isLoadingMore;
 
@@ -108,16 +116,23 @@ class _NotificationPageState extends State<NotificationPage> {
           controller: _scrollController,
           physics: const AlwaysScrollableScrollPhysics(),
           slivers: [
-            SliverToBoxAdapter(child: _buildHeader(context, notifications)),
+            SliverToBoxAdapter(child: _buildHeader(context, main)),
             SliverPadding(
               padding: const EdgeInsets.only(bottom: 100),
               sliver: SliverList.separated(
-                itemCount: grouped.length +
+                itemCount: rows.length +
                     (showLoadingMore ? 1 : 0) +
                     (!showLoadingMore && !showLoadMore ? 1 : 0),
                 itemBuilder: (context, index) {
-                  if (index < grouped.length) {
-                    final item = grouped[index];
+                  if (index < rows.length) {
+                    final item = rows[index];
+                    if (item['isOtherToggle'] == true) {
+                      return OtherBucketToggleWidget(
+                        count: other.length,
+                        expanded: _showOther,
+                        onTap: () => setState(() => _showOther = !_showOther),
+                      );
+                    }
                     if (item['isGrouped'] == true) {
                       return _GroupedNotificationTile(notification: item);
                     }
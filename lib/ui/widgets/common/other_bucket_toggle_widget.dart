@@ -0,0 +1,48 @@
+import 'package:flutter/material.dart';
+import 'package:phosphor_flutter/phosphor_flutter.dart';
+
+import '../../theme/theme_manager.dart';
+import '../../../l10n/app_localizations.dart';
+
+class OtherBucketToggleWidget extends StatelessWidget {
+  final int count;
+  final bool expanded;
+  final VoidCallback onTap;
+
+  const OtherBucketToggleWidget({
+    super.key,
+    required this.count,
+    required this.expanded,
+    required this.onTap,
+  });
+
+  @override
+  Widget build(BuildContext context) {
+    final l10n = AppLocalizations.of(context)!;
+    return InkWell(
+      onTap: onTap,
+      child: Padding(
+        padding: const EdgeInsets.symmetric(horizontal: 16, vertical: 14),
+        child: Row(
+          children: [
+            Expanded(
+              child: Text(
+                l10n.outsideYourNetwork(count),
+                style: TextStyle(
+                  fontSize: 15,
+                  fontWeight: FontWeight.w600,
+                  color: context.colors.textSecondary,
+                ),
+              ),
+            ),
+            PhosphorIcon(
+              expanded ? PhosphorIcons.caretUp() : PhosphorIcons.caretDown(),
+              size: 18,
+              color: context.colors.textSecondary,
+            ),
+          ],
+        ),
+      ),
+    );
+  }
+}
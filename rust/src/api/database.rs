@@ -6,7 +6,10 @@ use anyhow::Result;
 use nostr_sdk::prelude::*;
 use regex::Regex;
 
-use super::relay::get_client_pub;
+use super::operations::Operation;
+use super::relay::{get_client_pub, publish_events};
+use crate::archive;
+use crate::frb_generated::StreamSink;
 
 // ---------------------------------------------------------------------------
 // Global mute state — set once from Dart, used by all hydrate calls
@@ -371,7 +374,7 @@ fn extract_content_references(content: &str) -> (Vec<String>, Vec<(String, Strin
     (event_ids, naddr_refs)
 }
 
-fn metadata_to_flat_json(event: &Event, m: &Metadata) -> serde_json::Value {
+pub(crate) fn metadata_to_flat_json(event: &Event, m: &Metadata) -> serde_json::Value {
     let location = m.custom.get("location")
         .and_then(|v| v.as_str())
         .unwrap_or("");
@@ -432,32 +435,20 @@ pub async fn db_has_profile(pubkey_hex: String) -> Result<bool> {
     Ok(count > 0)
 }
 
+/// Profiles whose name, display name, NIP-05 or about text match `query`,
+/// allowing prefixes and small typos. Ranked by match quality and by how
+/// close each profile is to the signed-in user in the follow graph.
 pub async fn db_search_profiles(query: String, limit: u32) -> Result<String> {
     let client = get_client_pub().await?;
-    let filter = Filter::new().kind(Kind::Metadata).limit(2000);
-    let events = client.database().query(filter).await?;
-
-    let query_lower = query.to_lowercase();
-    let mut results: Vec<serde_json::Value> = Vec::new();
-
-    for event in events.into_iter() {
-        if let Ok(m) = Metadata::from_json(&event.content) {
-            let name = m.name.as_deref().unwrap_or("").to_lowercase();
-            let display = m.display_name.as_deref().unwrap_or("").to_lowercase();
-            let nip05 = m.nip05.as_deref().unwrap_or("").to_lowercase();
-
-            if name.contains(&query_lower)
-                || display.contains(&query_lower)
-                || nip05.contains(&query_lower)
-            {
-                results.push(metadata_to_flat_json(&event, &m));
-                if results.len() >= limit as usize {
-                    break;
-                }
-            }
-        }
-    }
-
+    let events = crate::profile_index::search(&client, &query, limit as usize).await?;
+    let results: Vec<serde_json::Value> = events
+        .iter()
+        .filter_map(|event| {
+            Metadata::from_json(&event.content)
+                .ok()
+                .map(|m| metadata_to_flat_json(event, &m))
+        })
+        .collect();
     Ok(serde_json::to_string(&results)?)
 }
 
@@ -1268,6 +1259,129 @@ pub async fn db_count_events(filter_json: String) -> Result<u32> {
     Ok(count as u32)
 }
 
+/// Export stored events to a JSONL archive at `path`, one event per line,
+/// with a `<path>.manifest.json` holding the event count, a checksum and,
+/// if `user_pubkey_hex` is given, that user's relay list and lists.
+/// `filter_json` narrows the export (e.g. `{"authors":[<hex>]}` for only the
+/// user's own events); everything is exported without it. Emits
+/// `{"type":"progress","exported","total"}` while writing and a final
+/// `{"type":"done",...}` or `{"type":"error","error"}`.
+#[flutter_rust_bridge::frb]
+pub async fn db_export_events(
+    path: String,
+    filter_json: Option<String>,
+    user_pubkey_hex: Option<String>,
+    operation_id: Option<String>,
+    timeout_secs: Option<u64>,
+    sink: StreamSink<String>,
+) -> Result<()> {
+    let client = get_client_pub().await?;
+    let filter = match filter_json {
+        Some(json) => Filter::from_json(&json)?,
+        None => Filter::new(),
+    };
+    let user = user_pubkey_hex
+        .as_deref()
+        .map(PublicKey::from_hex)
+        .transpose()?;
+    let op = Operation::start("db_export_events", operation_id, timeout_secs);
+
+    let result = archive::export(
+        &client,
+        std::path::Path::new(&path),
+        filter,
+        user,
+        &op,
+        |exported, total| {
+            let _ = sink.add(
+                serde_json::json!({
+                    "type": "progress",
+                    "exported": exported,
+                    "total": total,
+                })
+                .to_string(),
+            );
+        },
+    )
+    .await;
+
+    let message = match result {
+        Ok(mut done) => {
+            done["type"] = "done".into();
+            done
+        }
+        Err(e) => serde_json::json!({ "type": "error", "error": e.to_string() }),
+    };
+    let _ = sink.add(message.to_string());
+    Ok(())
+}
+
+/// Import a JSONL archive such as one written by [`db_export_events`]:
+/// events with a bad signature are skipped and events already stored are
+/// counted as duplicates. Emits `{"type":"progress","stage":"import",...}`
+/// with the import counters after every batch. If `rebroadcast_pubkey_hex`
+/// is given, that user's events from the archive are then republished to
+/// `relay_urls` (or the write relays), with `{"type":"progress",
+/// "stage":"broadcast","sent","failed","total"}` updates. Ends with
+/// `{"type":"done",...}` or `{"type":"error","error"}`.
+#[flutter_rust_bridge::frb]
+pub async fn db_import_events(
+    path: String,
+    rebroadcast_pubkey_hex: Option<String>,
+    relay_urls: Option<Vec<String>>,
+    operation_id: Option<String>,
+    timeout_secs: Option<u64>,
+    sink: StreamSink<String>,
+) -> Result<()> {
+    let client = get_client_pub().await?;
+    let owner = rebroadcast_pubkey_hex
+        .as_deref()
+        .map(PublicKey::from_hex)
+        .transpose()?;
+    let op = Operation::start("db_import_events", operation_id, timeout_secs);
+
+    let imported = archive::import(&client, std::path::Path::new(&path), owner, &op, |stats| {
+        let mut progress = serde_json::json!(stats);
+        progress["type"] = "progress".into();
+        progress["stage"] = "import".into();
+        let _ = sink.add(progress.to_string());
+    })
+    .await;
+    let (stats, own) = match imported {
+        Ok(imported) => imported,
+        Err(e) => {
+            let _ = sink.add(serde_json::json!({ "type": "error", "error": e.to_string() }).to_string());
+            return Ok(());
+        }
+    };
+
+    let mut done = serde_json::json!(stats);
+    if owner.is_some() {
+        let total = own.len();
+        let target_urls: Option<Vec<RelayUrl>> = relay_urls.map(|urls| {
+            urls.iter()
+                .filter_map(|u| RelayUrl::parse(u).ok())
+                .collect()
+        });
+        let (sent, failed) = publish_events(&client, own, target_urls, &op, |sent, failed| {
+            let progress = serde_json::json!({
+                "type": "progress",
+                "stage": "broadcast",
+                "sent": sent,
+                "failed": failed,
+                "total": total,
+            });
+            sink.add(progress.to_string()).is_ok()
+        })
+        .await;
+        done["broadcast"] = serde_json::json!({ "sent": sent, "failed": failed, "total": total });
+    }
+    done["type"] = "done".into();
+    done["status"] = op.outcome().into();
+    let _ = sink.add(done.to_string());
+    Ok(())
+}
+
 pub async fn db_wipe() -> Result<()> {
     let client = get_client_pub().await?;
     client.database().wipe().await?;
@@ -1288,8 +1402,103 @@ pub async fn db_wipe_directory() -> Result<()> {
     Ok(())
 }
 
+/// What happened when the database was last opened, as JSON: `action` is
+/// `opened`, `staleLockRemoved` or `recovered`; a recovery also reports
+/// `openError`, `brokenPath` (where the unreadable store was moved),
+/// `salvaged`, `unreadable` and `salvageError`. `None` before `init_client`
+/// or without a database path.
+pub async fn db_get_recovery_report() -> Result<Option<String>> {
+    Ok(crate::lmdb_recovery::last_report()
+        .map(|report| serde_json::json!(report).to_string()))
+}
+
+/// Delete the unreadable stores kept aside by earlier recoveries once the
+/// user no longer needs them. Returns how many were removed.
+pub async fn db_delete_broken_copies() -> Result<u32> {
+    use super::relay::db_path_state;
+
+    let db_path_lock = db_path_state().read().await;
+    let Some(path) = db_path_lock.as_ref() else {
+        return Ok(0);
+    };
+    let mut removed = 0;
+    for copy in crate::lmdb_recovery::broken_copies(path) {
+        if std::fs::remove_dir_all(&copy).is_ok() {
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// The database's persistence policy as JSON: `persistentKinds` and
+/// `persistentRanges` (inclusive `[from, to]`) are kept in LMDB and
+/// everything else in memory only; `memoryMaxEvents` caps the memory tier;
+/// `retentionDays` maps a kind to how many days its events are kept.
+pub async fn db_get_persistence_policy() -> Result<String> {
+    Ok(serde_json::to_string(&crate::persistence_policy::policy())?)
+}
+
+/// Change the persistence policy. Fields left out of `policy_json` keep
+/// their value, so `{"persistentKinds": [...]}` only replaces that list.
+/// Routing, retention and `memoryMaxEvents` apply from the next save.
+/// Returns the updated policy.
+pub async fn db_set_persistence_policy(policy_json: String) -> Result<String> {
+    let policy = crate::persistence_policy::update(&policy_json)?;
+    Ok(serde_json::to_string(&policy)?)
+}
+
+/// Delete events past their kind's retention now instead of waiting for the
+/// periodic pass. Returns how many were deleted.
+pub async fn db_apply_retention() -> Result<u32> {
+    let client = get_client_pub().await?;
+    Ok(crate::persistence_policy::prune_expired(&client).await? as u32)
+}
+
+/// Disk usage of the event store as JSON: `usedBytes`, `mapSize` (grows
+/// automatically as the store fills), `quotaBytes`, `grows`,
+/// `lastGrowError` and `lastEviction`.
+pub async fn db_get_storage_status() -> Result<String> {
+    Ok(crate::storage_quota::status_json().to_string())
+}
+
+/// Cap the disk space the event store may use; `None` removes the cap.
+/// Once the store nears the quota the least valuable events are evicted
+/// first: other people's reactions, then reposts and zaps, then old notes
+/// from accounts the user doesn't follow. The user's own events, events
+/// mentioning them, DMs and lists are never evicted.
+pub async fn db_set_storage_quota(quota_bytes: Option<u64>) -> Result<()> {
+    crate::storage_quota::set_quota(quota_bytes)
+}
+
+/// Evict events now if the store is over its quota. Returns the eviction
+/// report as JSON, or `None` if nothing needed to go.
+pub async fn db_enforce_storage_quota() -> Result<Option<String>> {
+    let client = get_client_pub().await?;
+    Ok(crate::storage_quota::enforce(&client, true)
+        .await?
+        .map(|report| report.to_string()))
+}
+
 pub async fn db_search_notes(query: String, limit: u32) -> Result<String> {
     let client = get_client_pub().await?;
+    if !crate::search_index::is_open() {
+        return scan_notes(&client, &query, limit).await;
+    }
+    let filter = crate::search_index::SearchFilter {
+        kinds: HashSet::from([Kind::TextNote.as_u16()]),
+        ..Default::default()
+    };
+    let page = crate::search_index::search(&client, &query, &filter, 0, limit as usize).await?;
+    let results: Vec<serde_json::Value> = page
+        .events
+        .iter()
+        .filter_map(|e| serde_json::from_str(&e.as_json()).ok())
+        .collect();
+    Ok(serde_json::to_string(&results)?)
+}
+
+/// Substring search over the newest notes, for when there is no index.
+async fn scan_notes(client: &Client, query: &str, limit: u32) -> Result<String> {
     let filter = Filter::new().kind(Kind::TextNote).limit(500);
     let events = client.database().query(filter).await?;
 
@@ -1309,6 +1518,50 @@ pub async fn db_search_notes(query: String, limit: u32) -> Result<String> {
     Ok(serde_json::to_string(&results)?)
 }
 
+/// Ranked full-text search over stored notes and articles. Every word of
+/// `query` must match, the last one also as the start of a longer word;
+/// `"quoted words"` must appear as a phrase. The
+/// `authors`, `kinds`, `since` and `until` of `filter_json` narrow the
+/// results. Returns `{total, truncated, events}` with one page of events,
+/// best match first; `truncated` means only the best-ranked matches were
+/// considered.
+pub async fn db_search_events(
+    query: String,
+    filter_json: String,
+    offset: u32,
+    limit: u32,
+) -> Result<String> {
+    let client = get_client_pub().await?;
+    let filter = Filter::from_json(&filter_json)?;
+    let filter = crate::search_index::SearchFilter::from(&filter);
+    let page = crate::search_index::search(
+        &client,
+        &query,
+        &filter,
+        offset as usize,
+        limit as usize,
+    )
+    .await?;
+    let events: Vec<serde_json::Value> = page
+        .events
+        .iter()
+        .filter_map(|e| serde_json::from_str(&e.as_json()).ok())
+        .collect();
+    Ok(serde_json::json!({
+        "total": page.total,
+        "truncated": page.truncated,
+        "events": events,
+    })
+    .to_string())
+}
+
+/// Search index status as JSON: `open`, `documents`, `backfilled` (events
+/// stored before the index existed have all been indexed), `mapSize`,
+/// `lastError` and `droppedUpdates` (updates lost to failed writes).
+pub async fn db_get_search_index_status() -> Result<String> {
+    Ok(crate::search_index::status_json().to_string())
+}
+
 pub async fn db_get_oldest_events(limit: u32) -> Result<String> {
     let client = get_client_pub().await?;
     let filter = Filter::new().limit(limit as usize);
@@ -1468,6 +1721,7 @@ pub async fn db_get_database_stats() -> Result<String> {
         "reposts": reposts,
         "zaps": zaps,
         "articles": articles,
+        "tiers": crate::hybrid_database::tier_stats_json(),
     });
     
     Ok(stats.to_string())
@@ -2349,7 +2603,11 @@ pub async fn db_get_hydrated_replies(
         .filter(|e| !is_future_dated(e) && !is_event_muted(e, &muted_pubkeys, &muted_words))
         .collect();
 
-    hydrate_notes(&client, &filtered, false, current_user_pubkey_hex).await
+    let viewer = current_user_pubkey_hex.clone();
+    let hydrated = hydrate_notes(&client, &filtered, false, current_user_pubkey_hex).await?;
+    let mut replies: Vec<serde_json::Value> = serde_json::from_str(&hydrated)?;
+    crate::wot_filter::bucket(viewer.as_deref(), &mut replies, "pubkey");
+    Ok(serde_json::to_string(&replies)?)
 }
 
 pub async fn db_get_hydrated_note(
@@ -2374,7 +2632,7 @@ pub async fn db_get_hydrated_note(
     }
 }
 
-async fn hydrate_notification_events(
+pub(crate) async fn hydrate_notification_events(
     client: &Client,
     events: &[Event],
     user_pubkey_hex: &str,
@@ -2594,6 +2852,7 @@ async fn hydrate_notification_events(
         }
     }
 
+    crate::wot_filter::bucket(Some(user_pubkey_hex), &mut items, "fromPubkey");
     Ok(serde_json::to_string(&items)?)
 }
 
@@ -2668,7 +2927,7 @@ pub async fn db_get_oldest_notification_timestamp(
     Ok(oldest)
 }
 
-async fn hydrate_article_events(
+pub(crate) async fn hydrate_article_events(
     client: &Client,
     events: &[Event],
 ) -> Result<String> {
@@ -3111,6 +3370,8 @@ pub async fn db_get_hydrated_reaction_notes(
     hydrate_notes(&client, &ordered, false, current_user_pubkey_hex).await
 }
 
+/// How many of the current user's follows follow `target_hex`, with their
+/// avatars, from the in-memory follow graph.
 pub async fn db_calculate_follow_score(
     current_user_hex: String,
     target_hex: String,
@@ -3118,32 +3379,8 @@ pub async fn db_calculate_follow_score(
     let client = get_client_pub().await?;
     let current_pk = PublicKey::from_hex(&current_user_hex)?;
     let target_pk = PublicKey::from_hex(&target_hex)?;
-    let target_hex_str = target_pk.to_hex();
-
-    let my_follows: Vec<PublicKey> = client
-        .database()
-        .contacts_public_keys(current_pk)
-        .await?
-        .into_iter()
-        .filter(|pk| *pk != current_pk && *pk != target_pk)
-        .collect();
-
-    if my_follows.is_empty() {
-        return Ok(serde_json::json!({"count": 0, "avatarUrls": []}).to_string());
-    }
-
-    let mut matching_pubkeys: Vec<PublicKey> = Vec::new();
-    for follow_pk in &my_follows {
-        let their_follows = client
-            .database()
-            .contacts_public_keys(*follow_pk)
-            .await
-            .unwrap_or_default();
-        if their_follows.iter().any(|pk| pk.to_hex() == target_hex_str) {
-            matching_pubkeys.push(*follow_pk);
-        }
-    }
 
+    let matching_pubkeys = crate::follow_graph::follows_following(current_pk, &target_pk);
     if matching_pubkeys.is_empty() {
         return Ok(serde_json::json!({"count": 0, "avatarUrls": []}).to_string());
     }
@@ -3169,6 +3406,59 @@ pub async fn db_calculate_follow_score(
     Ok(serde_json::json!({"count": count, "avatarUrls": avatar_urls}).to_string())
 }
 
+/// Web-of-trust answers for each of `pubkeys_hex` relative to the current
+/// user, as JSON keyed by pubkey: `distance` (0 self, 1 follow, 2 follow of
+/// a follow, `null` further), `followedByFollows` and `trust` (0.0-1.0).
+pub async fn db_get_web_of_trust(
+    current_user_hex: String,
+    pubkeys_hex: Vec<String>,
+) -> Result<String> {
+    let current_pk = PublicKey::from_hex(&current_user_hex)?;
+    let pubkeys: Vec<PublicKey> = pubkeys_hex
+        .iter()
+        .filter_map(|h| PublicKey::from_hex(h).ok())
+        .collect();
+    let trust = crate::follow_graph::trust(current_pk, &pubkeys);
+    let result: serde_json::Map<String, serde_json::Value> = pubkeys
+        .iter()
+        .zip(trust)
+        .map(|(pk, t)| {
+            (
+                pk.to_hex(),
+                serde_json::json!({
+                    "distance": t.distance,
+                    "followedByFollows": t.followed_by_follows,
+                    "trust": t.trust,
+                }),
+            )
+        })
+        .collect();
+    Ok(serde_json::to_string(&result)?)
+}
+
+/// Size of the follow graph and whether it has finished loading from the
+/// database, as JSON.
+pub async fn db_get_follow_graph_stats() -> Result<String> {
+    Ok(crate::follow_graph::stats_json().to_string())
+}
+
+/// The web-of-trust filter for replies and notifications as JSON: when
+/// `enabled`, authors further than `maxDistance` follow hops from the user
+/// or with a `trust` below `minTrust` get `"bucket": "other"` instead of
+/// `"main"` in [`db_get_hydrated_replies`], the thread structure and the
+/// notification queries. Off by default.
+/// Until the follow graph has loaded everything is `"main"`.
+pub async fn db_get_wot_filter() -> Result<String> {
+    Ok(serde_json::to_string(&crate::wot_filter::filter())?)
+}
+
+/// Change the web-of-trust filter. Fields left out of `filter_json` keep
+/// their value. Returns the updated filter.
+pub async fn db_set_wot_filter(filter_json: String) -> Result<String> {
+    let filter = crate::wot_filter::update(&filter_json)?;
+    Ok(serde_json::to_string(&filter)?)
+}
+
 pub async fn db_get_follow_sets(
     authors_hex: Vec<String>,
     limit: u32,
@@ -3188,16 +3478,13 @@ pub async fn db_get_follow_sets(
         .kind(Kind::from(30000u16))
         .authors(authors)
         .limit(limit as usize);
+    // The database keeps only the newest version of each set, newest first.
     let events = client.database().query(filter).await?;
 
-    let mut events_vec: Vec<Event> = events.into_iter().collect();
-    events_vec.sort_by(|a, b| b.created_at.cmp(&a.created_at));
-
     let hidden: HashSet<&str> = hidden_d_tags.iter().map(|s| s.as_str()).collect();
-    let mut seen_keys: HashSet<String> = HashSet::new();
     let mut results: Vec<serde_json::Value> = Vec::new();
 
-    for event in &events_vec {
+    for event in events.iter() {
         let tags = tags_from_event(event);
         let mut d_tag = String::new();
         let mut title = String::new();
@@ -3223,11 +3510,6 @@ pub async fn db_get_follow_sets(
             continue;
         }
 
-        let unique_key = format!("{}:{}", event.pubkey.to_hex(), d_tag);
-        if !seen_keys.insert(unique_key) {
-            continue;
-        }
-
         results.push(serde_json::json!({
             "id": event.id.to_hex(),
             "pubkey": event.pubkey.to_hex(),
@@ -3405,9 +3687,11 @@ pub async fn db_get_hydrated_thread_structure(
 
     let all_events: Vec<Event> = collected.into_values().collect();
 
+    let viewer = current_user_pubkey_hex.clone();
     let hydrated_str =
         hydrate_notes(&client, &all_events, false, current_user_pubkey_hex).await?;
-    let hydrated: Vec<serde_json::Value> = serde_json::from_str(&hydrated_str)?;
+    let mut hydrated: Vec<serde_json::Value> = serde_json::from_str(&hydrated_str)?;
+    crate::wot_filter::bucket(viewer.as_deref(), &mut hydrated, "pubkey");
 
     if hydrated.is_empty() {
         return Ok(serde_json::json!({"error": "empty"}).to_string());
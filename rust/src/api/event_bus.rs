@@ -0,0 +1,511 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, Result};
+use flutter_rust_bridge::frb;
+use nostr_sdk::prelude::*;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::sync::{broadcast, Mutex, RwLock};
+use tokio::task::JoinHandle;
+
+use super::relay::get_client_pub;
+use crate::frb_generated::StreamSink;
+
+const SEEN_CAPACITY: usize = 10_000;
+const HYDRATE_BATCH: usize = 100;
+/// Messages kept while no Dart stream is attached.
+const PENDING_CAPACITY: usize = 1_000;
+
+/// What the bus forwards to a subscription's consumer.
+pub(crate) enum BusItem {
+    Event(Box<Event>),
+    Eose(RelayUrl),
+    Closed(RelayUrl, String),
+    /// The dispatcher fell behind the pool and skipped this many
+    /// notifications, so events may be missing.
+    Lagged(u64),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Hydration {
+    Raw,
+    Note,
+    Notification,
+    Article,
+}
+
+impl Hydration {
+    fn parse(s: &str) -> Result<Self> {
+        match s {
+            "raw" | "" => Ok(Self::Raw),
+            "note" => Ok(Self::Note),
+            "notification" => Ok(Self::Notification),
+            "article" => Ok(Self::Article),
+            other => Err(anyhow!("Unknown hydration: {}", other)),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Raw => "raw",
+            Self::Note => "note",
+            Self::Notification => "notification",
+            Self::Article => "article",
+        }
+    }
+}
+
+/// Event ids already delivered on a subscription, bounded so long-lived
+/// subscriptions don't grow without limit.
+#[derive(Default)]
+struct RecentIds {
+    set: HashSet<EventId>,
+    order: VecDeque<EventId>,
+}
+
+impl RecentIds {
+    fn insert(&mut self, id: EventId) -> bool {
+        if !self.set.insert(id) {
+            return false;
+        }
+        self.order.push_back(id);
+        if self.order.len() > SEEN_CAPACITY {
+            if let Some(old) = self.order.pop_front() {
+                self.set.remove(&old);
+            }
+        }
+        true
+    }
+}
+
+struct Route {
+    tx: UnboundedSender<BusItem>,
+    seen: RecentIds,
+    filter_json: String,
+    hydration: Option<Hydration>,
+    delivered: u64,
+}
+
+#[derive(Default)]
+struct Bus {
+    dispatcher: Option<JoinHandle<()>>,
+    routes: HashMap<SubscriptionId, Route>,
+}
+
+static BUS: OnceLock<Mutex<Bus>> = OnceLock::new();
+static BUS_SINK: OnceLock<RwLock<SinkState>> = OnceLock::new();
+
+/// The Dart stream, and what was emitted before one was attached.
+#[derive(Default)]
+struct SinkState {
+    sink: Option<StreamSink<String>>,
+    /// The attached stream was closed; workers stop instead of queueing.
+    closed: bool,
+    pending: VecDeque<String>,
+    /// Messages dropped from a full `pending`.
+    overflowed: u64,
+}
+
+fn bus_state() -> &'static Mutex<Bus> {
+    BUS.get_or_init(|| Mutex::new(Bus::default()))
+}
+
+fn bus_sink_state() -> &'static RwLock<SinkState> {
+    BUS_SINK.get_or_init(|| RwLock::new(SinkState::default()))
+}
+
+/// Single reader of the pool's notification channel. Routes events to the
+/// subscription that requested them, dropping ids it has already delivered,
+/// and hands relay messages to the relay message history. `notifications`
+/// is subscribed to before the task is spawned, so nothing sent in between
+/// is missed.
+async fn dispatch(mut notifications: broadcast::Receiver<RelayPoolNotification>) {
+    loop {
+        let notification = notifications.recv().await;
+        if let Ok(RelayPoolNotification::Message { relay_url, message }) = &notification {
+            crate::relay_messages::observe(relay_url, message);
+        }
+        match notification {
+            Ok(RelayPoolNotification::Event {
+                subscription_id,
+                event,
+                ..
+            }) => {
+                let mut bus = bus_state().lock().await;
+                if let Some(route) = bus.routes.get_mut(&subscription_id) {
+                    if route.seen.insert(event.id) {
+                        route.delivered += 1;
+                        let _ = route.tx.send(BusItem::Event(event));
+                    }
+                }
+            }
+            Ok(RelayPoolNotification::Message { relay_url, message }) => match message {
+                RelayMessage::EndOfStoredEvents(sid) => {
+                    let bus = bus_state().lock().await;
+                    if let Some(route) = bus.routes.get(sid.as_ref()) {
+                        let _ = route.tx.send(BusItem::Eose(relay_url));
+                    }
+                }
+                RelayMessage::Closed {
+                    subscription_id,
+                    message,
+                } => {
+                    let bus = bus_state().lock().await;
+                    if let Some(route) = bus.routes.get(subscription_id.as_ref()) {
+                        let _ = route
+                            .tx
+                            .send(BusItem::Closed(relay_url, message.into_owned()));
+                    }
+                }
+                _ => {}
+            },
+            Ok(RelayPoolNotification::Shutdown) => break,
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                let bus = bus_state().lock().await;
+                for route in bus.routes.values() {
+                    let _ = route.tx.send(BusItem::Lagged(skipped));
+                }
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+        }
+    }
+
+    let mut bus = bus_state().lock().await;
+    bus.routes.clear();
+    bus.dispatcher = None;
+}
+
+async fn register(
+    client: &Client,
+    filter: Filter,
+    hydration: Option<Hydration>,
+    relays: Option<Vec<RelayUrl>>,
+    store: bool,
+) -> Result<(SubscriptionId, UnboundedReceiver<BusItem>)> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let sub_id = SubscriptionId::generate();
+    if !store {
+        crate::hybrid_database::hold_back(&sub_id);
+    }
+
+    {
+        let mut bus = bus_state().lock().await;
+        let running = bus
+            .dispatcher
+            .as_ref()
+            .is_some_and(|handle| !handle.is_finished());
+        if !running {
+            bus.dispatcher = Some(tokio::spawn(dispatch(client.notifications())));
+        }
+        bus.routes.insert(
+            sub_id.clone(),
+            Route {
+                tx,
+                seen: RecentIds::default(),
+                filter_json: filter.as_json(),
+                hydration,
+                delivered: 0,
+            },
+        );
+    }
+
+    let subscribed = match relays {
+        Some(urls) => client.subscribe_with_id_to(urls, sub_id.clone(), filter, None).await,
+        None => client.subscribe_with_id(sub_id.clone(), filter, None).await,
+    };
+    if let Err(e) = subscribed {
+        bus_state().lock().await.routes.remove(&sub_id);
+        crate::hybrid_database::release(&sub_id);
+        return Err(anyhow!("Subscribe failed: {}", e));
+    }
+
+    Ok((sub_id, rx))
+}
+
+/// Open a subscription on the main pool whose deduplicated events are
+/// delivered on the returned channel instead of the shared Dart stream.
+pub(crate) async fn subscribe_channel(
+    client: &Client,
+    filter: Filter,
+) -> Result<(SubscriptionId, UnboundedReceiver<BusItem>)> {
+    register(client, filter, None, None, true).await
+}
+
+/// Like [`subscribe_channel`], but only on the given relays of the pool,
+/// and the events are only delivered, never stored in the database.
+pub(crate) async fn subscribe_transient_to(
+    client: &Client,
+    relays: Vec<RelayUrl>,
+    filter: Filter,
+) -> Result<(SubscriptionId, UnboundedReceiver<BusItem>)> {
+    register(client, filter, None, Some(relays), false).await
+}
+
+/// Close a bus subscription on the relays and stop routing to it.
+pub(crate) async fn unsubscribe_id(client: &Client, sub_id: &SubscriptionId) {
+    let removed = bus_state().lock().await.routes.remove(sub_id).is_some();
+    if removed {
+        client.unsubscribe(sub_id).await;
+    }
+    crate::hybrid_database::release(sub_id);
+}
+
+/// Drop every route and attach the dispatcher to a new client's pool. It
+/// runs for the client's whole life, since relay message history is fed
+/// from it too.
+pub(crate) async fn start(client: &Client) {
+    let mut bus = bus_state().lock().await;
+    if let Some(handle) = bus.dispatcher.take() {
+        handle.abort();
+    }
+    bus.routes.clear();
+    bus.dispatcher = Some(tokio::spawn(dispatch(client.notifications())));
+}
+
+/// Push a message to the Dart stream, or queue it until one is attached.
+/// `false` once the attached stream has been closed.
+async fn emit(json: String) -> bool {
+    let mut state = bus_sink_state().write().await;
+    if state.closed {
+        return false;
+    }
+    match state.sink.as_ref() {
+        Some(sink) => {
+            if sink.add(json).is_err() {
+                state.sink = None;
+                state.closed = true;
+                return false;
+            }
+        }
+        None => {
+            if state.pending.len() >= PENDING_CAPACITY {
+                state.pending.pop_front();
+                state.overflowed += 1;
+            }
+            state.pending.push_back(json);
+        }
+    }
+    true
+}
+
+async fn hydrate_batch(
+    client: &Client,
+    hydration: Hydration,
+    events: &[Event],
+    user_pubkey_hex: &Option<String>,
+) -> Vec<serde_json::Value> {
+    let hydrated = match hydration {
+        Hydration::Raw => {
+            return events
+                .iter()
+                .filter_map(|e| serde_json::from_str(&e.as_json()).ok())
+                .collect();
+        }
+        Hydration::Note => {
+            super::database::hydrate_notes_pub(client, events, false, user_pubkey_hex.clone())
+                .await
+        }
+        Hydration::Notification => {
+            super::database::hydrate_notification_events(
+                client,
+                events,
+                user_pubkey_hex.as_deref().unwrap_or_default(),
+            )
+            .await
+        }
+        Hydration::Article => super::database::hydrate_article_events(client, events).await,
+    };
+
+    hydrated
+        .ok()
+        .and_then(|json| serde_json::from_str::<Vec<serde_json::Value>>(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Per-subscription worker that saves, hydrates and pushes updates into the
+/// shared stream. Events that arrive together are hydrated as one batch.
+/// Closes the subscription once the Dart stream is closed.
+async fn forward(
+    client: Client,
+    handle: String,
+    hydration: Hydration,
+    user_pubkey_hex: Option<String>,
+    mut rx: UnboundedReceiver<BusItem>,
+) {
+    'items: while let Some(first) = rx.recv().await {
+        let mut items = vec![first];
+        while items.len() < HYDRATE_BATCH {
+            match rx.try_recv() {
+                Ok(item) => items.push(item),
+                Err(_) => break,
+            }
+        }
+
+        let mut events: Vec<Event> = Vec::new();
+        let mut signals: Vec<serde_json::Value> = Vec::new();
+        for item in items {
+            match item {
+                BusItem::Event(event) => {
+                    let _ = client.database().save_event(&event).await;
+                    events.push(*event);
+                }
+                BusItem::Eose(relay_url) => signals.push(serde_json::json!({
+                    "type": "eose",
+                    "handle": handle,
+                    "relay": relay_url.to_string(),
+                })),
+                BusItem::Closed(relay_url, message) => signals.push(serde_json::json!({
+                    "type": "closed",
+                    "handle": handle,
+                    "relay": relay_url.to_string(),
+                    "message": message,
+                })),
+                BusItem::Lagged(skipped) => signals.push(serde_json::json!({
+                    "type": "lagged",
+                    "handle": handle,
+                    "skipped": skipped,
+                })),
+            }
+        }
+
+        if !events.is_empty() {
+            for item in hydrate_batch(&client, hydration, &events, &user_pubkey_hex).await {
+                let json = serde_json::json!({
+                    "type": "event",
+                    "handle": handle,
+                    "kind": hydration.as_str(),
+                    "item": item,
+                });
+                if !emit(json.to_string()).await {
+                    break 'items;
+                }
+            }
+        }
+
+        for signal in signals {
+            if !emit(signal.to_string()).await {
+                break 'items;
+            }
+        }
+    }
+    unsubscribe_id(&client, &SubscriptionId::new(handle)).await;
+}
+
+/// Attach the Dart side to the event bus. Every subscription opened with
+/// [`event_bus_subscribe`] pushes its updates into this one stream; calling
+/// it again replaces the previous sink. Updates emitted while no stream was
+/// attached are delivered first. Once the stream is closed, subscriptions
+/// still open end as their next update fails to send.
+///
+/// Each message is a JSON object with `type` `event` (with `handle`, `kind`
+/// and the hydrated `item`), `eose` or `closed` (with `relay`), or `lagged`
+/// with the number of `skipped` updates. A `lagged` message means events
+/// were missed and the consumer should reload from the database.
+#[frb]
+pub async fn stream_event_bus(sink: StreamSink<String>) -> Result<()> {
+    let mut state = bus_sink_state().write().await;
+    if state.overflowed > 0 {
+        let lagged = serde_json::json!({"type": "lagged", "skipped": state.overflowed});
+        let _ = sink.add(lagged.to_string());
+        state.overflowed = 0;
+    }
+    for json in state.pending.drain(..) {
+        let _ = sink.add(json);
+    }
+    state.sink = Some(sink);
+    state.closed = false;
+    Ok(())
+}
+
+/// Open a long-lived subscription and return its handle. `hydration` is
+/// `raw`, `note`, `notification` or `article` and selects how events are
+/// shaped before they reach the stream; `notification` needs the user pubkey.
+pub async fn event_bus_subscribe(
+    filter_json: String,
+    hydration: String,
+    current_user_pubkey_hex: Option<String>,
+) -> Result<String> {
+    let client = get_client_pub().await?;
+    let filter = Filter::from_json(&filter_json)?;
+    let hydration = Hydration::parse(&hydration)?;
+    if hydration == Hydration::Notification && current_user_pubkey_hex.is_none() {
+        return Err(anyhow!("Notification hydration requires a user pubkey"));
+    }
+
+    let (sub_id, rx) = register(&client, filter, Some(hydration), None, true).await?;
+    let handle = sub_id.to_string();
+    tokio::spawn(forward(
+        client,
+        handle.clone(),
+        hydration,
+        current_user_pubkey_hex,
+        rx,
+    ));
+
+    Ok(handle)
+}
+
+pub async fn event_bus_unsubscribe(handle: String) -> Result<()> {
+    let client = get_client_pub().await?;
+    unsubscribe_id(&client, &SubscriptionId::new(handle)).await;
+    Ok(())
+}
+
+pub async fn event_bus_unsubscribe_all() -> Result<()> {
+    let client = get_client_pub().await?;
+    let handles: Vec<SubscriptionId> = {
+        let bus = bus_state().lock().await;
+        bus.routes
+            .iter()
+            .filter(|(_, route)| route.hydration.is_some())
+            .map(|(id, _)| id.clone())
+            .collect()
+    };
+    for sub_id in handles {
+        unsubscribe_id(&client, &sub_id).await;
+    }
+    Ok(())
+}
+
+/// List open bus subscriptions with their filter, hydration mode and how many
+/// unique events each has delivered so far.
+pub async fn get_event_bus_subscriptions() -> Result<String> {
+    let bus = bus_state().lock().await;
+    let list: Vec<serde_json::Value> = bus
+        .routes
+        .iter()
+        .filter_map(|(id, route)| {
+            let hydration = route.hydration?;
+            Some(serde_json::json!({
+                "handle": id.to_string(),
+                "filter": serde_json::from_str::<serde_json::Value>(&route.filter_json)
+                    .unwrap_or_default(),
+                "hydration": hydration.as_str(),
+                "delivered": route.delivered,
+            }))
+        })
+        .collect();
+    Ok(serde_json::to_string(&list)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RecentIds;
+    use nostr_sdk::prelude::*;
+
+    #[test]
+    fn recent_ids_dedups_and_stays_bounded() {
+        let mut seen = RecentIds::default();
+        let first = EventId::all_zeros();
+        assert!(seen.insert(first));
+        assert!(!seen.insert(first));
+
+        for i in 0..super::SEEN_CAPACITY as u64 {
+            let mut bytes = [0u8; 32];
+            bytes[..8].copy_from_slice(&(i + 1).to_be_bytes());
+            seen.insert(EventId::from_byte_array(bytes));
+        }
+        assert_eq!(seen.order.len(), super::SEEN_CAPACITY);
+        assert!(seen.insert(first));
+    }
+}
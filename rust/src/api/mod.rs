@@ -1,8 +1,12 @@
 pub mod cashu;
 pub mod crypto;
 pub mod database;
+pub mod event_bus;
 pub mod events;
 pub mod nip17;
 pub mod nip19;
+pub mod nip21;
 pub mod nwc;
+pub mod operations;
 pub mod relay;
+pub mod sync_scheduler;
@@ -0,0 +1,342 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use anyhow::Result;
+use nostr_sdk::prelude::*;
+
+use super::relay::get_client_pub;
+
+const MAX_HINT_RELAYS: usize = 3;
+
+/// What a `nostr:` URI points at once its bech32 payload is decoded.
+enum Target {
+    Profile(PublicKey),
+    Event {
+        id: EventId,
+        author: Option<PublicKey>,
+    },
+    Address(Coordinate),
+}
+
+fn error_json(reason: &str) -> String {
+    serde_json::json!({
+        "type": "error",
+        "reason": reason,
+    })
+    .to_string()
+}
+
+fn decode(uri: &str) -> std::result::Result<(Target, Vec<RelayUrl>), String> {
+    let trimmed = uri.trim();
+    let bech32 = match trimmed.get(..6) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("nostr:") => &trimmed[6..],
+        _ => trimmed,
+    };
+
+    let nip19 = Nip19::from_bech32(bech32).map_err(|e| format!("invalid_uri: {}", e))?;
+    let nip21 = Nip21::try_from(nip19).map_err(|_| "unsupported_entity".to_string())?;
+
+    Ok(match nip21 {
+        Nip21::Pubkey(pk) => (Target::Profile(pk), Vec::new()),
+        Nip21::Profile(p) => (Target::Profile(p.public_key), p.relays),
+        Nip21::EventId(id) => (Target::Event { id, author: None }, Vec::new()),
+        Nip21::Event(e) => (
+            Target::Event {
+                id: e.event_id,
+                author: e.author,
+            },
+            e.relays,
+        ),
+        Nip21::Coordinate(c) => (Target::Address(c.coordinate), c.relays),
+    })
+}
+
+/// Fetch `filter` from the hinted relays plus the user's read relays. Hint
+/// relays already in the pool are used as they are; others are added
+/// read-only for this lookup and removed afterwards.
+async fn fetch_with_hints(
+    client: &Client,
+    filter: Filter,
+    hints: &[RelayUrl],
+    timeout: Duration,
+) -> Vec<Event> {
+    let readable =
+        RelayServiceFlags::READ | RelayServiceFlags::DISCOVERY | RelayServiceFlags::GOSSIP;
+    let relays = client.pool().all_relays().await;
+    let mut urls: Vec<RelayUrl> = relays
+        .iter()
+        .filter(|(_, r)| r.flags().has(RelayServiceFlags::READ, FlagCheck::All))
+        .map(|(u, _)| u.clone())
+        .collect();
+
+    let mut temporary: Vec<RelayUrl> = Vec::new();
+    for hint in hints.iter().take(MAX_HINT_RELAYS) {
+        if let Some(relay) = relays.get(hint) {
+            if relay.flags().has(readable, FlagCheck::Any) && !urls.contains(hint) {
+                urls.push(hint.clone());
+            }
+            continue;
+        }
+        if let Ok(true) = crate::relay_policy::add_lookup_relay(client, hint.as_str()).await {
+            let _ = client.connect_relay(hint.as_str()).await;
+            temporary.push(hint.clone());
+            urls.push(hint.clone());
+        }
+    }
+    drop(relays);
+
+    let events = if urls.is_empty() {
+        Vec::new()
+    } else {
+        client
+            .fetch_events_from(urls, filter, timeout)
+            .await
+            .map(|events| events.into_iter().collect())
+            .unwrap_or_default()
+    };
+
+    for event in &events {
+        let _ = client.database().save_event(event).await;
+    }
+
+    for url in temporary {
+        let _ = client.remove_relay(url.as_str()).await;
+    }
+
+    events
+}
+
+async fn ensure_author_profile(
+    client: &Client,
+    author: PublicKey,
+    hints: &[RelayUrl],
+    timeout: Duration,
+) {
+    let filter = Filter::new().author(author).kind(Kind::Metadata).limit(1);
+    let has_profile = client
+        .database()
+        .count(filter.clone())
+        .await
+        .map(|c| c > 0)
+        .unwrap_or(false);
+    if !has_profile {
+        fetch_with_hints(client, filter, hints, timeout).await;
+    }
+}
+
+async fn resolve_profile(
+    client: &Client,
+    pk: PublicKey,
+    hints: &[RelayUrl],
+    timeout: Duration,
+) -> Result<String> {
+    let filter = Filter::new().author(pk).kind(Kind::Metadata).limit(1);
+    let mut source = "local";
+    let mut event = client.database().query(filter.clone()).await?.first_owned();
+
+    if event.is_none() {
+        source = "remote";
+        event = fetch_with_hints(client, filter, hints, timeout)
+            .await
+            .into_iter()
+            .max_by_key(|e| e.created_at);
+    }
+
+    let Some(event) = event else {
+        return Ok(error_json("not_found"));
+    };
+    let Ok(metadata) = Metadata::from_json(&event.content) else {
+        return Ok(error_json("invalid_metadata"));
+    };
+
+    Ok(serde_json::json!({
+        "type": "profile",
+        "source": source,
+        "profile": super::database::metadata_to_flat_json(&event, &metadata),
+    })
+    .to_string())
+}
+
+async fn hydrate_event(
+    client: &Client,
+    event: Event,
+    source: &str,
+    current_user_pubkey_hex: Option<String>,
+) -> Result<String> {
+    let (kind, hydrated) = if event.kind == Kind::LongFormTextNote {
+        (
+            "article",
+            super::database::hydrate_article_events(client, &[event]).await?,
+        )
+    } else if event.kind == Kind::TextNote || event.kind == Kind::Repost {
+        (
+            "note",
+            super::database::hydrate_notes_pub(client, &[event], false, current_user_pubkey_hex)
+                .await?,
+        )
+    } else {
+        return Ok(error_json(&format!("unsupported_kind: {}", event.kind.as_u16())));
+    };
+
+    let arr: Vec<serde_json::Value> = serde_json::from_str(&hydrated)?;
+    let Some(item) = arr.into_iter().next() else {
+        return Ok(error_json("not_found"));
+    };
+
+    Ok(serde_json::json!({
+        "type": kind,
+        "source": source,
+        kind: item,
+    })
+    .to_string())
+}
+
+async fn resolve_event(
+    client: &Client,
+    id: EventId,
+    author: Option<PublicKey>,
+    hints: &[RelayUrl],
+    timeout: Duration,
+    current_user_pubkey_hex: Option<String>,
+) -> Result<String> {
+    let mut source = "local";
+    let mut event = client.database().event_by_id(&id).await?;
+
+    if event.is_none() {
+        source = "remote";
+        let filter = Filter::new().id(id).limit(1);
+        event = fetch_with_hints(client, filter, hints, timeout)
+            .await
+            .into_iter()
+            .find(|e| e.id == id);
+    }
+
+    let Some(event) = event else {
+        return Ok(error_json("not_found"));
+    };
+    if author.is_some_and(|a| a != event.pubkey) {
+        return Ok(error_json("author_mismatch"));
+    }
+
+    ensure_author_profile(client, event.pubkey, hints, timeout).await;
+    hydrate_event(client, event, source, current_user_pubkey_hex).await
+}
+
+async fn resolve_address(
+    client: &Client,
+    coordinate: Coordinate,
+    hints: &[RelayUrl],
+    timeout: Duration,
+    current_user_pubkey_hex: Option<String>,
+) -> Result<String> {
+    if coordinate.kind != Kind::LongFormTextNote {
+        return Ok(error_json(&format!(
+            "unsupported_kind: {}",
+            coordinate.kind.as_u16()
+        )));
+    }
+
+    let filter = Filter::new()
+        .author(coordinate.public_key)
+        .kind(coordinate.kind)
+        .identifier(coordinate.identifier.clone())
+        .limit(1);
+
+    let mut source = "local";
+    let mut event = client.database().query(filter.clone()).await?.first_owned();
+
+    if event.is_none() {
+        source = "remote";
+        event = fetch_with_hints(client, filter, hints, timeout)
+            .await
+            .into_iter()
+            .max_by_key(|e| e.created_at);
+    }
+
+    let Some(event) = event else {
+        return Ok(error_json("not_found"));
+    };
+
+    ensure_author_profile(client, event.pubkey, hints, timeout).await;
+    hydrate_event(client, event, source, current_user_pubkey_hex).await
+}
+
+/// Resolve any `nostr:` URI or bare bech32 entity (npub, nprofile, note,
+/// nevent, naddr) into a hydrated profile, note or article.
+///
+/// The local database is checked first; on a miss the entity is fetched from
+/// its relay hints plus the user's read relays and saved. The result is a JSON
+/// object whose `type` is `profile`, `note`, `article` or `error` (with a
+/// `reason`), and whose `source` is `local` or `remote`.
+pub async fn resolve_nostr_uri(
+    uri: String,
+    current_user_pubkey_hex: Option<String>,
+    timeout_secs: u32,
+) -> Result<String> {
+    let (target, hints) = match decode(&uri) {
+        Ok(decoded) => decoded,
+        Err(reason) => return Ok(error_json(&reason)),
+    };
+
+    let client = get_client_pub().await?;
+    let timeout = Duration::from_secs(timeout_secs as u64);
+
+    let mut seen: HashSet<RelayUrl> = HashSet::new();
+    let hints: Vec<RelayUrl> = hints.into_iter().filter(|u| seen.insert(u.clone())).collect();
+
+    match target {
+        Target::Profile(pk) => resolve_profile(&client, pk, &hints, timeout).await,
+        Target::Event { id, author } => {
+            resolve_event(
+                &client,
+                id,
+                author,
+                &hints,
+                timeout,
+                current_user_pubkey_hex,
+            )
+            .await
+        }
+        Target::Address(coordinate) => {
+            resolve_address(
+                &client,
+                coordinate,
+                &hints,
+                timeout,
+                current_user_pubkey_hex,
+            )
+            .await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, Target};
+    use nostr_sdk::prelude::*;
+
+    #[test]
+    fn decode_accepts_uri_and_bare_bech32() {
+        let keys = Keys::generate();
+        let npub = keys.public_key().to_bech32().unwrap();
+
+        for input in [format!("nostr:{}", npub), format!("NOSTR:{}", npub), npub] {
+            match decode(&input) {
+                Ok((Target::Profile(pk), hints)) => {
+                    assert_eq!(pk, keys.public_key());
+                    assert!(hints.is_empty());
+                }
+                _ => panic!("expected profile for {}", input),
+            }
+        }
+    }
+
+    #[test]
+    fn decode_rejects_secret_keys() {
+        let nsec = Keys::generate().secret_key().to_bech32().unwrap();
+        assert_eq!(
+            decode(&format!("nostr:{}", nsec)).err().as_deref(),
+            Some("unsupported_entity")
+        );
+    }
+}
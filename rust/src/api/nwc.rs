@@ -8,6 +8,8 @@ use nostr::nips::nip47::{
 use nostr::prelude::*;
 use nostr_sdk::prelude::*;
 
+use crate::relay_policy::RelaySource;
+
 #[frb(sync)]
 pub fn validate_nwc_uri(uri: String) -> bool {
     NostrWalletConnectURI::parse(&uri).is_ok()
@@ -44,7 +46,8 @@ pub async fn nwc_pay_invoice(nwc_uri: String, invoice: String) -> Result<String>
 
     let client: Client = Client::default();
     for relay_url in uri.relays.iter() {
-        let _ = client.add_relay(relay_url.as_str()).await;
+        let _ =
+            crate::relay_policy::add_relay(&client, relay_url.as_str(), RelaySource::User).await;
     }
     client.connect().await;
 
@@ -124,7 +127,8 @@ pub async fn nwc_get_balance(nwc_uri: String) -> Result<String> {
 
     let client: Client = Client::default();
     for relay_url in uri.relays.iter() {
-        let _ = client.add_relay(relay_url.as_str()).await;
+        let _ =
+            crate::relay_policy::add_relay(&client, relay_url.as_str(), RelaySource::User).await;
     }
     client.connect().await;
 
@@ -210,7 +214,8 @@ pub async fn nwc_make_invoice(nwc_uri: String, amount_msats: u64, description: O
 
     let client: Client = Client::default();
     for relay_url in uri.relays.iter() {
-        let _ = client.add_relay(relay_url.as_str()).await;
+        let _ =
+            crate::relay_policy::add_relay(&client, relay_url.as_str(), RelaySource::User).await;
     }
     client.connect().await;
 
@@ -299,7 +304,8 @@ pub async fn nwc_list_transactions(nwc_uri: String, limit: Option<u64>, offset:
 
     let client: Client = Client::default();
     for relay_url in uri.relays.iter() {
-        let _ = client.add_relay(relay_url.as_str()).await;
+        let _ =
+            crate::relay_policy::add_relay(&client, relay_url.as_str(), RelaySource::User).await;
     }
     client.connect().await;
 
@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, OnceLock, RwLock, Weak};
+use std::time::Duration;
+
+use anyhow::Result;
+use flutter_rust_bridge::frb;
+use nostr_sdk::prelude::*;
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+use crate::frb_generated::StreamSink;
+
+#[derive(Default)]
+struct Progress {
+    done: u64,
+    total: Option<u64>,
+    stage: String,
+}
+
+struct OperationInner {
+    id: String,
+    name: &'static str,
+    started_at: u64,
+    timeout_secs: Option<u64>,
+    deadline: Option<Instant>,
+    cancel: watch::Sender<bool>,
+    progress: Mutex<Progress>,
+}
+
+impl OperationInner {
+    fn status(&self, finished: bool) -> &'static str {
+        if *self.cancel.borrow() {
+            "cancelled"
+        } else if self.deadline.is_some_and(|d| Instant::now() >= d) {
+            "timedOut"
+        } else if finished {
+            "completed"
+        } else {
+            "running"
+        }
+    }
+
+    fn to_json(&self, finished: bool) -> serde_json::Value {
+        let progress = self.progress.lock().unwrap();
+        serde_json::json!({
+            "id": self.id,
+            "name": self.name,
+            "status": self.status(finished),
+            "done": progress.done,
+            "total": progress.total,
+            "stage": progress.stage,
+            "startedAt": self.started_at,
+            "timeoutSecs": self.timeout_secs,
+        })
+    }
+}
+
+impl Drop for OperationInner {
+    fn drop(&mut self) {
+        if let Ok(mut ops) = registry().lock() {
+            if ops.get(&self.id).is_some_and(|w| w.strong_count() == 0) {
+                ops.remove(&self.id);
+            }
+        }
+        emit(self.to_json(true));
+    }
+}
+
+/// Handle threaded through a long-running API call. Cloning is cheap; the
+/// operation is unregistered once the last clone is dropped.
+#[derive(Clone)]
+pub(crate) struct Operation {
+    inner: Arc<OperationInner>,
+}
+
+static OPERATIONS: OnceLock<Mutex<HashMap<String, Weak<OperationInner>>>> = OnceLock::new();
+static PROGRESS_SINK: OnceLock<RwLock<Option<StreamSink<String>>>> = OnceLock::new();
+
+fn registry() -> &'static Mutex<HashMap<String, Weak<OperationInner>>> {
+    OPERATIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn progress_sink_state() -> &'static RwLock<Option<StreamSink<String>>> {
+    PROGRESS_SINK.get_or_init(|| RwLock::new(None))
+}
+
+fn emit(value: serde_json::Value) {
+    if let Ok(sink) = progress_sink_state().read() {
+        if let Some(sink) = sink.as_ref() {
+            let _ = sink.add(value.to_string());
+        }
+    }
+}
+
+impl Operation {
+    /// Register an operation under `operation_id` (or a generated id). A
+    /// `timeout_secs` of `None` or `0` means the operation never times out.
+    pub(crate) fn start(
+        name: &'static str,
+        operation_id: Option<String>,
+        timeout_secs: Option<u64>,
+    ) -> Self {
+        let id = operation_id
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(|| SubscriptionId::generate().to_string());
+        let timeout_secs = timeout_secs.filter(|secs| *secs > 0);
+        let (cancel, _) = watch::channel(false);
+
+        let inner = Arc::new(OperationInner {
+            id: id.clone(),
+            name,
+            started_at: Timestamp::now().as_secs(),
+            timeout_secs,
+            deadline: timeout_secs.map(|secs| Instant::now() + Duration::from_secs(secs)),
+            cancel,
+            progress: Mutex::new(Progress::default()),
+        });
+
+        let previous = registry()
+            .lock()
+            .unwrap()
+            .insert(id, Arc::downgrade(&inner));
+        if let Some(previous) = previous.and_then(|w| w.upgrade()) {
+            previous.cancel.send_replace(true);
+        }
+
+        emit(inner.to_json(false));
+        Self { inner }
+    }
+
+    /// True once the operation was cancelled or ran past its deadline.
+    pub(crate) fn is_stopped(&self) -> bool {
+        *self.inner.cancel.borrow()
+            || self.inner.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
+    /// Resolves when the operation is cancelled or its deadline passes.
+    pub(crate) async fn stopped(&self) {
+        let mut rx = self.inner.cancel.subscribe();
+        let cancelled = async {
+            let _ = rx.wait_for(|cancelled| *cancelled).await;
+        };
+        match self.inner.deadline {
+            Some(deadline) => {
+                tokio::select! {
+                    _ = cancelled => {}
+                    _ = tokio::time::sleep_until(deadline) => {}
+                }
+            }
+            None => cancelled.await,
+        }
+    }
+
+    /// Drive `fut` until it completes or the operation stops, whichever
+    /// comes first. Returns `None` if the operation stopped.
+    pub(crate) async fn run<F: Future>(&self, fut: F) -> Option<F::Output> {
+        tokio::select! {
+            biased;
+            _ = self.stopped() => None,
+            out = fut => Some(out),
+        }
+    }
+
+    pub(crate) fn progress(&self, done: u64, total: Option<u64>, stage: &str) {
+        {
+            let mut progress = self.inner.progress.lock().unwrap();
+            progress.done = done;
+            progress.total = total;
+            if progress.stage != stage {
+                progress.stage = stage.to_string();
+            }
+        }
+        emit(self.inner.to_json(false));
+    }
+
+    /// `completed`, `cancelled` or `timedOut`, for inclusion in final results.
+    pub(crate) fn outcome(&self) -> &'static str {
+        self.inner.status(true)
+    }
+}
+
+/// Receive a progress update for every running operation, plus a final
+/// update with its status when it ends. Operations are the calls that take
+/// an `operation_id` (streamed sync, interaction count streams, broadcasts,
+/// author and profile fetches, reply sync, archive export and import),
+/// `sync_events` and scheduled sync tasks. Other calls, such as
+/// `fetch_events` or `fetch_counts_from_relays`, don't report here. Calling
+/// it again replaces the previous sink.
+#[frb]
+pub async fn stream_operation_progress(sink: StreamSink<String>) -> Result<()> {
+    let mut lock = progress_sink_state().write().unwrap();
+    *lock = Some(sink);
+    Ok(())
+}
+
+/// Cancel a running operation. Returns false if no such operation exists.
+pub async fn cancel_operation(operation_id: String) -> Result<bool> {
+    let op = registry()
+        .lock()
+        .unwrap()
+        .get(&operation_id)
+        .and_then(|w| w.upgrade());
+    match op {
+        Some(op) => {
+            op.cancel.send_replace(true);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+pub async fn cancel_all_operations() -> Result<u32> {
+    let ops: Vec<Arc<OperationInner>> = registry()
+        .lock()
+        .unwrap()
+        .values()
+        .filter_map(|w| w.upgrade())
+        .collect();
+    for op in &ops {
+        op.cancel.send_replace(true);
+    }
+    Ok(ops.len() as u32)
+}
+
+pub async fn get_active_operations() -> Result<String> {
+    let ops: Vec<Arc<OperationInner>> = registry()
+        .lock()
+        .unwrap()
+        .values()
+        .filter_map(|w| w.upgrade())
+        .collect();
+    let list: Vec<serde_json::Value> = ops.iter().map(|op| op.to_json(false)).collect();
+    Ok(serde_json::to_string(&list)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cancel_operation, Operation};
+
+    #[tokio::test]
+    async fn cancel_stops_running_future() {
+        let op = Operation::start("test", Some("op-cancel".to_string()), None);
+        let waiter = op.clone();
+        let task = tokio::spawn(async move {
+            waiter
+                .run(tokio::time::sleep(std::time::Duration::from_secs(60)))
+                .await
+        });
+
+        assert!(cancel_operation("op-cancel".to_string()).await.unwrap());
+        assert!(task.await.unwrap().is_none());
+        assert!(op.is_stopped());
+        assert_eq!(op.outcome(), "cancelled");
+    }
+}
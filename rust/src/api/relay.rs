@@ -5,18 +5,16 @@ use std::time::Duration;
 
 use anyhow::{anyhow, Result};
 use flutter_rust_bridge::frb;
-use nostr_lmdb::NostrLMDB;
 use nostr_sdk::prelude::*;
 use tokio::sync::RwLock;
 
+use super::operations::Operation;
+use crate::interaction_counts::{self, InteractionTally};
+use crate::negentropy_sync;
+use crate::relay_policy::RelaySource;
 use crate::hybrid_database::HybridDatabase;
 use crate::frb_generated::StreamSink;
 
-static COUNTING_CLIENT: OnceLock<RwLock<Option<Client>>> = OnceLock::new();
-
-fn counting_client_lock() -> &'static RwLock<Option<Client>> {
-    COUNTING_CLIENT.get_or_init(|| RwLock::new(None))
-}
 
 static CLIENT: OnceLock<RwLock<Option<Client>>> = OnceLock::new();
 static USER_RELAYS: OnceLock<RwLock<Vec<String>>> = OnceLock::new();
@@ -45,111 +43,58 @@ pub(crate) async fn get_client_pub() -> Result<Client> {
     get_client().await
 }
 
-fn sanitize_lmdb_dir(path: &str) {
-    let db_dir = std::path::Path::new(path);
-    if !db_dir.exists() {
-        return;
-    }
-
-    let lock_file = db_dir.join("lock.mdb");
-    if lock_file.exists() {
-        let _ = fs::remove_file(&lock_file);
-    }
-
-    let data_file = db_dir.join("data.mdb");
-    if data_file.exists() {
-        match fs::metadata(&data_file) {
-            Ok(meta) if meta.len() == 0 => {
-                let _ = fs::remove_dir_all(db_dir);
-            }
-            Err(_) => {
-                let _ = fs::remove_dir_all(db_dir);
-            }
-            _ => {}
-        }
-    }
-}
-
-const LMDB_MAP_SIZES: &[usize] = &[
-    2 * 1024 * 1024 * 1024,  // 2 GB
-    1024 * 1024 * 1024,       // 1 GB
-    512 * 1024 * 1024,        // 512 MB
-    256 * 1024 * 1024,        // 256 MB
-];
-
-fn try_open_lmdb(path: &str) -> Result<NostrLMDB> {
-    let path_owned = path.to_string();
-
-    for &map_size in LMDB_MAP_SIZES {
-        let p = path_owned.clone();
-        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-            NostrLMDB::builder(&p)
-                .map_size(map_size)
-                .build()
-        }));
-
-        match result {
-            Ok(Ok(db)) => return Ok(db),
-            Ok(Err(_)) => continue,
-            Err(_) => continue,
-        }
-    }
-
-    Err(anyhow!("LMDB open failed with all map sizes"))
-}
-
-fn wipe_db_directory(path: &str) {
-    let db_path = std::path::Path::new(path);
-    if db_path.exists() {
-        let _ = fs::remove_dir_all(db_path);
-    }
-}
-
-fn open_or_recreate_lmdb(path: &str) -> Result<NostrLMDB> {
-    sanitize_lmdb_dir(path);
-
-    match try_open_lmdb(path) {
-        Ok(db) => Ok(db),
-        Err(_) => {
-            wipe_db_directory(path);
-            try_open_lmdb(path)
-        }
-    }
-}
-
 pub async fn init_client(
     relay_urls: Vec<String>,
     private_key_hex: Option<String>,
     db_path: Option<String>,
     discovery_relays: Vec<String>,
 ) -> Result<()> {
-    let mut builder = Client::builder();
+    let mut builder = Client::builder().admit_policy(crate::hybrid_database::HoldBackPolicy);
 
     if let Some(ref sk_hex) = private_key_hex {
         let keys = Keys::parse(sk_hex)?;
         builder = builder.signer(keys);
     }
 
+    crate::persistence_policy::load(db_path.as_deref());
     if let Some(ref path) = db_path {
-        let lmdb = open_or_recreate_lmdb(path)?;
-        let database = HybridDatabase::new(lmdb);
+        crate::search_index::open(path);
+        let (lmdb, map_size) = crate::lmdb_recovery::open(path).await?;
+        let database = HybridDatabase::new(lmdb)
+            .resizable(path, map_size)
+            .shared();
         builder = builder.database(database);
 
         let mut db_path_lock = db_path_state().write().await;
         *db_path_lock = Some(path.clone());
+    } else {
+        crate::search_index::close();
     }
 
     let client = builder.build();
+    crate::relay_scores::start(&client, db_path.as_deref());
+    crate::relay_policy::load(db_path.as_deref());
+    crate::proxy::load(db_path.as_deref()).await;
+    crate::follower_counts::load(db_path.as_deref());
+    crate::search_relays::load(db_path.as_deref());
+    crate::wot_filter::load(db_path.as_deref());
+    crate::negentropy_sync::load(db_path.as_deref());
+    super::sync_scheduler::load(db_path.as_deref()).await;
+    crate::storage_quota::start(&client, db_path.as_deref());
+    crate::persistence_policy::start(&client);
+    crate::search_index::start(&client);
+    crate::follow_graph::start(&client);
+    crate::profile_index::start(&client);
 
     let relay_futures: Vec<_> = relay_urls
         .iter()
-        .map(|url| client.add_relay(url.as_str()))
+        .map(|url| crate::relay_policy::add_relay(&client, url, RelaySource::User))
         .collect();
     futures::future::join_all(relay_futures).await;
 
     let discovery_futures: Vec<_> = discovery_relays
         .iter()
-        .map(|url| client.add_discovery_relay(url.as_str()))
+        .map(|url| crate::relay_policy::add_discovery_relay(&client, url))
         .collect();
     futures::future::join_all(discovery_futures).await;
 
@@ -158,6 +103,8 @@ pub async fn init_client(
         *ur = relay_urls;
     }
 
+    super::event_bus::start(&client).await;
+
     let mut lock = state().write().await;
     if let Some(old_client) = lock.take() {
         old_client.disconnect().await;
@@ -210,9 +157,17 @@ pub async fn is_client_initialized() -> bool {
     lock.is_some()
 }
 
+/// Add a user relay to the pool and connect. Returns false if the pool
+/// refused it; a relay the relay policy rejects is an error carrying the
+/// reason.
 pub async fn add_relay(url: String) -> Result<bool> {
     let client = get_client().await?;
-    let added = client.add_relay(&url).await.is_ok();
+    if let Ok(relay_url) = RelayUrl::parse(&url) {
+        crate::relay_policy::ensure_allowed(&relay_url, RelaySource::User)?;
+    }
+    let added = crate::relay_policy::add_relay(&client, &url, RelaySource::User)
+        .await
+        .is_ok();
     if added {
         let mut ur = user_relays_state().write().await;
         if !ur.contains(&url) {
@@ -223,11 +178,15 @@ pub async fn add_relay(url: String) -> Result<bool> {
     Ok(added)
 }
 
+/// [`add_relay`] with only the given read and write flags.
 pub async fn add_relay_with_flags(url: String, read: bool, write: bool) -> Result<bool> {
     let client = get_client().await?;
     let relay_url = RelayUrl::parse(&url)?;
+    crate::relay_policy::ensure_allowed(&relay_url, RelaySource::User)?;
 
-    let added = client.add_relay(relay_url.as_str()).await.is_ok();
+    let added = crate::relay_policy::add_relay(&client, relay_url.as_str(), RelaySource::User)
+        .await
+        .is_ok();
     if added {
         let relays = client.relays().await;
         if let Some(relay) = relays.get(&relay_url) {
@@ -238,6 +197,7 @@ pub async fn add_relay_with_flags(url: String, read: bool, write: bool) -> Resul
             if !write {
                 flags.remove(RelayServiceFlags::WRITE);
             }
+            crate::relay_policy::apply_overrides(&relay_url, relay);
         }
         let mut ur = user_relays_state().write().await;
         if !ur.contains(&url) {
@@ -287,130 +247,390 @@ pub async fn get_connected_relay_count() -> Result<u32> {
     Ok(count)
 }
 
-pub async fn get_relay_status() -> Result<String> {
-    let client = get_client().await?;
-    let relays = client.relays().await;
+fn relay_status_str(status: RelayStatus) -> &'static str {
+    match status {
+        RelayStatus::Initialized => "initialized",
+        RelayStatus::Pending => "pending",
+        RelayStatus::Connecting => "connecting",
+        RelayStatus::Connected => "connected",
+        RelayStatus::Disconnected => "disconnected",
+        RelayStatus::Terminated => "terminated",
+        RelayStatus::Banned => "banned",
+        RelayStatus::Sleeping => "sleeping",
+    }
+}
 
-    let mut relay_list = Vec::new();
-    for (url, relay) in relays.iter() {
-        let status = relay.status();
-        let stats = relay.stats();
-        let flags = relay.flags();
-        let is_discovery = flags.has(RelayServiceFlags::DISCOVERY, FlagCheck::All)
-            && !flags.has(RelayServiceFlags::READ, FlagCheck::All)
-            && !flags.has(RelayServiceFlags::WRITE, FlagCheck::All);
+fn relay_info_json(url: &RelayUrl, relay: &Relay) -> serde_json::Value {
+    let stats = relay.stats();
+    let flags = relay.flags();
+    let is_discovery = flags.has(RelayServiceFlags::DISCOVERY, FlagCheck::All)
+        && !flags.has(RelayServiceFlags::READ, FlagCheck::All)
+        && !flags.has(RelayServiceFlags::WRITE, FlagCheck::All);
+
+    let latency_ms: u64 = stats
+        .latency()
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+
+    serde_json::json!({
+        "url": url.to_string(),
+        "status": relay_status_str(relay.status()),
+        "isDiscovery": is_discovery,
+        "attempts": stats.attempts(),
+        "success": stats.success(),
+        "bytesSent": stats.bytes_sent(),
+        "bytesReceived": stats.bytes_received(),
+        "connectedAt": stats.connected_at().as_secs(),
+        "latencyMs": latency_ms,
+        "score": crate::relay_scores::score(url.as_str()),
+        "demoted": crate::relay_scores::is_demoted(url.as_str()),
+    })
+}
 
-        let status_str = match status {
-            RelayStatus::Initialized => "initialized",
-            RelayStatus::Pending => "pending",
-            RelayStatus::Connecting => "connecting",
-            RelayStatus::Connected => "connected",
-            RelayStatus::Disconnected => "disconnected",
-            RelayStatus::Terminated => "terminated",
-            RelayStatus::Banned => "banned",
-            RelayStatus::Sleeping => "sleeping",
-        };
+/// Every relay carrying `flag`, best scored first, with demoted relays left
+/// out. None are cut on score, so the user's own relays are always used.
+async fn ranked_relays(client: &Client, flag: RelayServiceFlags) -> Vec<RelayUrl> {
+    let urls: Vec<RelayUrl> = client
+        .relays()
+        .await
+        .iter()
+        .filter(|(_, r)| r.flags().has(flag, FlagCheck::All))
+        .map(|(u, _)| u.clone())
+        .collect();
+    let all = urls.len();
+    crate::relay_scores::rank(urls, all)
+}
+
+/// Persisted quality score of every relay seen so far, best first.
+pub async fn get_relay_scores() -> Result<String> {
+    Ok(crate::relay_scores::scores_json().to_string())
+}
+
+pub async fn reset_relay_scores() -> Result<()> {
+    crate::relay_scores::reset();
+    Ok(())
+}
+
+/// Current relay policy: `blocklist`, `allowlist`, `allowlistOnly`,
+/// `blockDiscoveredOnion`, `blockDiscoveredLocal` and per-URL `overrides`
+/// of `read`/`write`/`discovery`.
+pub async fn get_relay_policy() -> Result<String> {
+    Ok(crate::relay_policy::policy_json().to_string())
+}
 
-        let relay_info = serde_json::json!({
-            "url": url.to_string(),
-            "status": status_str,
-            "isDiscovery": is_discovery,
-            "attempts": stats.attempts(),
-            "success": stats.success(),
-            "bytesSent": stats.bytes_sent(),
-            "bytesReceived": stats.bytes_received(),
-            "connectedAt": stats.connected_at().as_secs(),
+/// Replace and persist the relay policy, then apply it to the running pool.
+/// Returns the URLs of relays that were removed because they are no longer
+/// allowed.
+pub async fn set_relay_policy(policy_json: String) -> Result<Vec<String>> {
+    crate::relay_policy::set_policy(&policy_json)?;
+    let Ok(client) = get_client().await else {
+        return Ok(Vec::new());
+    };
+    let removed = crate::relay_policy::enforce(&client).await;
+    if !removed.is_empty() {
+        let mut ur = user_relays_state().write().await;
+        ur.retain(|u| {
+            RelayUrl::parse(u).is_ok_and(|url| !removed.iter().any(|r| r == url.as_str()))
         });
-        relay_list.push(relay_info);
     }
+    Ok(removed)
+}
 
-    let total = relay_list
-        .iter()
-        .filter(|r| r["isDiscovery"] == false)
-        .count();
-    let connected = relay_list
+/// Whether `url` would be accepted by the relay policy, and why not if it
+/// wouldn't. `discovered` applies the stricter rules used for relay lists.
+pub async fn check_relay_url(url: String, discovered: bool) -> Result<String> {
+    let relay_url = RelayUrl::parse(&url)?;
+    let source = if discovered {
+        RelaySource::Discovered
+    } else {
+        RelaySource::User
+    };
+    let result = match crate::relay_policy::check(&relay_url, source) {
+        Ok(()) => serde_json::json!({ "allowed": true }),
+        Err(reason) => serde_json::json!({ "allowed": false, "reason": reason }),
+    };
+    Ok(result.to_string())
+}
+
+/// Current proxy config: `enabled`, `address` (`host:port` of a SOCKS5
+/// proxy, e.g. Tor on `127.0.0.1:9050`) and `onionOnly`.
+pub async fn get_proxy_config() -> Result<String> {
+    Ok(crate::proxy::config_json().to_string())
+}
+
+/// Replace and persist the proxy config, then reconnect every relay whose
+/// route changed. Returns how many relays were reconnected.
+pub async fn set_proxy_config(config_json: String) -> Result<u32> {
+    crate::proxy::set_config(&config_json).await?;
+    let reconnected = match get_client().await {
+        Ok(client) => crate::proxy::apply_to_pool(&client).await,
+        Err(_) => 0,
+    };
+    Ok(reconnected as u32)
+}
+
+fn relay_summary_json<'a>(relays: impl Iterator<Item = &'a serde_json::Value>) -> serde_json::Value {
+    let mut total = 0usize;
+    let mut connected = 0usize;
+    for r in relays.filter(|r| r["isDiscovery"] == false) {
+        total += 1;
+        if r["status"] == "connected" {
+            connected += 1;
+        }
+    }
+    serde_json::json!({
+        "totalRelays": total,
+        "connectedRelays": connected,
+    })
+}
+
+pub async fn get_relay_status() -> Result<String> {
+    let client = get_client().await?;
+    let relays = client.relays().await;
+
+    let relay_list: Vec<serde_json::Value> = relays
         .iter()
-        .filter(|r| r["status"] == "connected" && r["isDiscovery"] == false)
-        .count();
+        .map(|(url, relay)| {
+            let mut info = relay_info_json(url, relay);
+            info["recentMessages"] =
+                serde_json::Value::from(crate::relay_messages::relay_history(url.as_str()));
+            info
+        })
+        .collect();
 
     let result = serde_json::json!({
-        "summary": {
-            "totalRelays": total,
-            "connectedRelays": connected,
-        },
+        "summary": relay_summary_json(relay_list.iter()),
         "relays": relay_list,
     });
 
     Ok(result.to_string())
 }
 
-#[frb]
-pub async fn stream_relay_status(sink: StreamSink<String>) -> Result<()> {
-    let mut interval = tokio::time::interval(Duration::from_millis(800));
-    loop {
-        interval.tick().await;
-        let Ok(client) = get_client().await else {
-            break;
-        };
-        let relays = client.relays().await;
-        let mut relay_list = Vec::new();
-        for (url, relay) in relays.iter() {
-            let status = relay.status();
-            let stats = relay.stats();
-            let flags = relay.flags();
-            let is_discovery = flags.has(RelayServiceFlags::DISCOVERY, FlagCheck::All)
-                && !flags.has(RelayServiceFlags::READ, FlagCheck::All)
-                && !flags.has(RelayServiceFlags::WRITE, FlagCheck::All);
+/// Recent NOTICE, CLOSED and rejected OK messages, keyed by relay URL, or
+/// just the list for `relay_url`. Each entry carries a typed `reason` parsed
+/// from the NIP-01 machine-readable prefix.
+pub async fn get_relay_messages(relay_url: Option<String>) -> Result<String> {
+    let result = match relay_url {
+        Some(url) => {
+            let url = RelayUrl::parse(&url)?;
+            serde_json::Value::from(crate::relay_messages::relay_history(url.as_str()))
+        }
+        None => serde_json::to_value(crate::relay_messages::all_history())?,
+    };
+    Ok(result.to_string())
+}
 
-            let status_str = match status {
-                RelayStatus::Initialized => "initialized",
-                RelayStatus::Pending => "pending",
-                RelayStatus::Connecting => "connecting",
+pub async fn clear_relay_messages() -> Result<()> {
+    crate::relay_messages::clear_history();
+    Ok(())
+}
+
+/// Forward the status, auth and NOTICE/CLOSED/AUTH notifications of one relay
+/// into `tx`. Event traffic is dropped here so it never reaches the stream.
+fn watch_relay(
+    url: RelayUrl,
+    relay: &Relay,
+    tx: tokio::sync::mpsc::UnboundedSender<(RelayUrl, RelayNotification)>,
+) -> tokio::task::JoinHandle<()> {
+    let mut notifications = relay.notifications();
+    tokio::spawn(async move {
+        loop {
+            match notifications.recv().await {
+                Ok(RelayNotification::Event { .. }) => continue,
+                Ok(RelayNotification::Message { ref message })
+                    if !matches!(
+                        message,
+                        RelayMessage::Notice(_)
+                            | RelayMessage::Closed { .. }
+                            | RelayMessage::Auth { .. }
+                    ) =>
+                {
+                    continue
+                }
+                Ok(RelayNotification::Shutdown) => break,
+                Ok(notification) => {
+                    if tx.send((url.clone(), notification)).is_err() {
+                        break;
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    })
+}
+
+/// Turn a relay notification into a diff entry. Status notifications are
+/// compared against the last state sent so repeats produce nothing.
+fn relay_change_json(
+    url: &RelayUrl,
+    notification: RelayNotification,
+    relays: &HashMap<RelayUrl, Relay>,
+    known: &mut HashMap<String, serde_json::Value>,
+) -> Option<serde_json::Value> {
+    let key = url.to_string();
+    match notification {
+        RelayNotification::RelayStatus { status } => {
+            let relay = relays.get(url)?;
+            let info = relay_info_json(url, relay);
+            if known.get(&key).is_some_and(|prev| prev["status"] == info["status"]) {
+                return None;
+            }
+            known.insert(key.clone(), info.clone());
+            let change = match status {
                 RelayStatus::Connected => "connected",
-                RelayStatus::Disconnected => "disconnected",
-                RelayStatus::Terminated => "terminated",
-                RelayStatus::Banned => "banned",
-                RelayStatus::Sleeping => "sleeping",
+                RelayStatus::Disconnected | RelayStatus::Terminated => "disconnected",
+                _ => "status",
             };
+            Some(serde_json::json!({ "url": key, "change": change, "relay": info }))
+        }
+        RelayNotification::Authenticated => {
+            Some(serde_json::json!({ "url": key, "change": "authenticated" }))
+        }
+        RelayNotification::AuthenticationFailed => {
+            Some(serde_json::json!({ "url": key, "change": "authFailed" }))
+        }
+        RelayNotification::Message { message } => match message {
+            RelayMessage::Notice(message) => Some(serde_json::json!({
+                "url": key,
+                "change": "notice",
+                "reason": crate::relay_messages::RejectReason::parse(&message).as_str(),
+                "message": message,
+            })),
+            RelayMessage::Closed {
+                subscription_id,
+                message,
+            } => Some(serde_json::json!({
+                "url": key,
+                "change": "closed",
+                "subscriptionId": subscription_id.to_string(),
+                "reason": crate::relay_messages::RejectReason::parse(&message).as_str(),
+                "message": message,
+            })),
+            RelayMessage::Auth { .. } => {
+                Some(serde_json::json!({ "url": key, "change": "authRequired" }))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
 
-            let latency_ms: u64 = relay
-                .stats()
-                .latency()
-                .map(|d| d.as_millis() as u64)
-                .unwrap_or(0);
-
-            let relay_info = serde_json::json!({
-                "url": url.to_string(),
-                "status": status_str,
-                "isDiscovery": is_discovery,
-                "attempts": stats.attempts(),
-                "success": stats.success(),
-                "bytesSent": stats.bytes_sent(),
-                "bytesReceived": stats.bytes_received(),
-                "connectedAt": stats.connected_at().as_secs(),
-                "latencyMs": latency_ms,
-            });
-            relay_list.push(relay_info);
+/// Stream relay state as a `snapshot` message followed by `diff` messages.
+/// Diffs are driven by relay notifications (connected, disconnected, auth,
+/// NOTICE and CLOSED); relays added or removed from the pool and latency
+/// changes are picked up by a slower reconcile pass.
+#[frb]
+pub async fn stream_relay_status(
+    operation_id: Option<String>,
+    timeout_secs: Option<u64>,
+    sink: StreamSink<String>,
+) -> Result<()> {
+    let op = Operation::start("stream_relay_status", operation_id, timeout_secs);
+    let client = get_client().await?;
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watchers: HashMap<RelayUrl, tokio::task::JoinHandle<()>> = HashMap::new();
+    let mut known: HashMap<String, serde_json::Value> = HashMap::new();
+
+    {
+        let relays = client.relays().await;
+        for (url, relay) in relays.iter() {
+            watchers.insert(url.clone(), watch_relay(url.clone(), relay, tx.clone()));
+            known.insert(url.to_string(), relay_info_json(url, relay));
         }
+    }
 
-        let total = relay_list.iter().filter(|r| r["isDiscovery"] == false).count();
-        let connected = relay_list
-            .iter()
-            .filter(|r| r["status"] == "connected" && r["isDiscovery"] == false)
-            .count();
-
-        let result = serde_json::json!({
-            "summary": {
-                "totalRelays": total,
-                "connectedRelays": connected,
-            },
-            "relays": relay_list,
-        });
+    let snapshot = serde_json::json!({
+        "type": "snapshot",
+        "summary": relay_summary_json(known.values()),
+        "relays": known.values().collect::<Vec<_>>(),
+    });
+    if sink.add(snapshot.to_string()).is_ok() {
+        let mut reconcile = tokio::time::interval(Duration::from_secs(5));
+        reconcile.tick().await;
+
+        loop {
+            let mut changes: Vec<serde_json::Value> = Vec::new();
+
+            tokio::select! {
+                _ = op.stopped() => break,
+                item = rx.recv() => {
+                    let Some(first) = item else {
+                        break;
+                    };
+                    let relays = client.relays().await;
+                    let mut pending = vec![first];
+                    while let Ok(next) = rx.try_recv() {
+                        pending.push(next);
+                    }
+                    for (url, notification) in pending {
+                        if let Some(change) = relay_change_json(&url, notification, &relays, &mut known) {
+                            changes.push(change);
+                        }
+                    }
+                }
+                _ = reconcile.tick() => {
+                    let relays = client.relays().await;
 
-        if sink.add(result.to_string()).is_err() {
-            break;
+                    watchers.retain(|url, handle| {
+                        if relays.contains_key(url) {
+                            return true;
+                        }
+                        handle.abort();
+                        known.remove(url.as_str());
+                        changes.push(serde_json::json!({
+                            "url": url.to_string(),
+                            "change": "removed",
+                        }));
+                        false
+                    });
+
+                    for (url, relay) in relays.iter() {
+                        let info = relay_info_json(url, relay);
+                        let key = url.to_string();
+                        let change = match known.get(&key) {
+                            None => Some("added"),
+                            Some(prev) if prev["status"] != info["status"] => Some("status"),
+                            Some(prev) if prev["latencyMs"] != info["latencyMs"] => Some("latency"),
+                            Some(_) => None,
+                        };
+                        if let Some(change) = change {
+                            changes.push(serde_json::json!({
+                                "url": key,
+                                "change": change,
+                                "relay": info,
+                            }));
+                            known.insert(key, info);
+                        }
+                        // A watcher ends when its relay shuts down, so a
+                        // relay removed and re-added since the last pass
+                        // needs a new one on the new instance.
+                        let watched = watchers.get(url).is_some_and(|handle| !handle.is_finished());
+                        if !watched {
+                            watchers.insert(url.clone(), watch_relay(url.clone(), relay, tx.clone()));
+                        }
+                    }
+                }
+            }
+
+            if changes.is_empty() {
+                continue;
+            }
+            let diff = serde_json::json!({
+                "type": "diff",
+                "summary": relay_summary_json(known.values()),
+                "changes": changes,
+            });
+            if sink.add(diff.to_string()).is_err() {
+                break;
+            }
         }
     }
+
+    for (_, handle) in watchers {
+        handle.abort();
+    }
     Ok(())
 }
 
@@ -418,8 +638,13 @@ pub async fn discover_and_connect_outbox_relays(
     pubkeys_hex: Vec<String>,
     max_outbox_relays: usize,
     min_relay_frequency: usize,
+    operation_id: Option<String>,
+    timeout_secs: Option<u64>,
 ) -> Result<String> {
+    use futures::stream::{FuturesUnordered, StreamExt};
+
     let client = get_client().await?;
+    let op = Operation::start("discover_and_connect_outbox_relays", operation_id, timeout_secs);
 
     let public_keys: Vec<PublicKey> = pubkeys_hex
         .iter()
@@ -431,13 +656,14 @@ pub async fn discover_and_connect_outbox_relays(
             "discoveredRelays": 0,
             "addedRelays": 0,
             "totalConnected": 0,
+            "status": op.outcome(),
         }).to_string());
     }
 
     let mut outbox_freq: HashMap<String, usize> = HashMap::new();
     let mut inbox_freq: HashMap<String, usize> = HashMap::new();
 
-    let chunk_futures: Vec<_> = public_keys
+    let mut chunk_futures: FuturesUnordered<_> = public_keys
         .chunks(50)
         .map(|chunk| {
             let filter = Filter::new()
@@ -448,7 +674,23 @@ pub async fn discover_and_connect_outbox_relays(
         })
         .collect();
 
-    let chunk_results = futures::future::join_all(chunk_futures).await;
+    let total_chunks = chunk_futures.len() as u64;
+    let mut chunk_results = Vec::new();
+    op.progress(0, Some(total_chunks), "relayLists");
+    while let Some(Some(result)) = op.run(chunk_futures.next()).await {
+        chunk_results.push(result);
+        op.progress(chunk_results.len() as u64, Some(total_chunks), "relayLists");
+    }
+    drop(chunk_futures);
+
+    if op.is_stopped() {
+        return Ok(serde_json::json!({
+            "discoveredRelays": 0,
+            "addedRelays": 0,
+            "totalConnected": 0,
+            "status": op.outcome(),
+        }).to_string());
+    }
 
     for result in chunk_results {
         if let Ok(events) = result {
@@ -504,11 +746,22 @@ pub async fn discover_and_connect_outbox_relays(
 
     let mut candidates: Vec<(String, usize, bool, bool)> = all_relays
         .into_iter()
-        .filter(|(_, (count, _, _))| *count >= min_relay_frequency)
+        .filter(|(url, (count, _, _))| {
+            *count >= min_relay_frequency
+                && !crate::relay_scores::is_demoted(url)
+                && RelayUrl::parse(url).is_ok_and(|u| {
+                    crate::relay_policy::check(&u, RelaySource::Discovered).is_ok()
+                })
+        })
         .map(|(url, (count, is_outbox, is_inbox))| (url, count, is_outbox, is_inbox))
         .collect();
 
-    candidates.sort_by(|a, b| b.1.cmp(&a.1));
+    // Popularity among follows, scaled by how well the relay has behaved.
+    // Unknown relays sit at the neutral score, i.e. a factor of 1.
+    let weight = |url: &str, count: usize| {
+        count as f64 * (0.5 + crate::relay_scores::score(url))
+    };
+    candidates.sort_by(|a, b| weight(&b.0, b.1).total_cmp(&weight(&a.0, a.1)));
     candidates.truncate(max_outbox_relays);
 
     let discovered_count = candidates.len() as u32;
@@ -521,9 +774,12 @@ pub async fn discover_and_connect_outbox_relays(
         })
         .collect();
 
+    op.progress(0, Some(parsed_candidates.len() as u64), "addingRelays");
     let add_futures: Vec<_> = parsed_candidates
         .iter()
-        .map(|(relay_url, _, _)| client.add_relay(relay_url.as_str()))
+        .map(|(relay_url, _, _)| {
+            crate::relay_policy::add_relay(&client, relay_url.as_str(), RelaySource::Discovered)
+        })
         .collect();
     let add_results = futures::future::join_all(add_futures).await;
 
@@ -538,12 +794,14 @@ pub async fn discover_and_connect_outbox_relays(
                 } else if is_inbox && !is_outbox {
                     flags.remove(RelayServiceFlags::READ);
                 }
+                crate::relay_policy::apply_overrides(relay_url, relay);
             }
             added_count += 1;
         }
     }
 
     if added_count > 0 {
+        op.progress(added_count as u64, Some(parsed_candidates.len() as u64), "connecting");
         client.connect().await;
     }
 
@@ -559,46 +817,65 @@ pub async fn discover_and_connect_outbox_relays(
         "discoveredRelays": discovered_count,
         "addedRelays": added_count,
         "totalConnected": connected_count,
+        "status": op.outcome(),
     });
 
     Ok(result.to_string())
 }
 
+/// One-shot sync of `filter` with every read relay. Keeps the old
+/// `received`/`sent`/`local`/`remote` totals and adds per-relay results
+/// under `relays`; see [`stream_sync`].
 pub async fn sync_events(filter_json: String) -> Result<String> {
     let client = get_client().await?;
     let filter = Filter::from_json(&filter_json)?;
-    let opts = SyncOptions::default()
-        .initial_timeout(Duration::from_secs(5));
-
-    let sync_future = client.sync(filter, &opts);
-    let output = match tokio::time::timeout(Duration::from_secs(12), sync_future).await {
-        Ok(Ok(output)) => output,
-        Ok(Err(_)) => {
-            return Ok(serde_json::json!({
-                "received": 0,
-                "sent": 0,
-                "local": 0,
-                "remote": 0,
-            }).to_string());
-        }
-        Err(_) => {
-            return Ok(serde_json::json!({
-                "received": 0,
-                "sent": 0,
-                "local": 0,
-                "remote": 0,
-            }).to_string());
-        }
-    };
+    let op = Operation::start("sync_events", None, Some(30));
+    let report = negentropy_sync::sync(&client, filter, &op, |_| {}).await;
+    Ok(report.to_json().to_string())
+}
 
-    let result = serde_json::json!({
-        "received": output.received.len(),
-        "sent": output.sent.len(),
-        "local": output.local.len(),
-        "remote": output.remote.len(),
-    });
+/// Sync `filter` with every read relay, emitting a `{"type":"relay",...}`
+/// message whenever a relay starts (`status: "running"`) or finishes. Relays
+/// use negentropy (NIP-77) where supported and paged REQ otherwise (`method`);
+/// a finished relay has `status` `ok`, `timeout` or `error`, or the
+/// operation's outcome if it was cancelled. Each relay resumes from where the
+/// last successful sync of the same filter left off. Ends with
+/// `{"type":"done",...}` carrying the totals.
+#[frb]
+pub async fn stream_sync(
+    filter_json: String,
+    operation_id: Option<String>,
+    timeout_secs: Option<u64>,
+    sink: StreamSink<String>,
+) -> Result<()> {
+    let client = get_client().await?;
+    let filter = Filter::from_json(&filter_json)?;
+    let op = Operation::start("stream_sync", operation_id, timeout_secs);
+
+    let report = negentropy_sync::sync(&client, filter, &op, |relay| {
+        let mut message = serde_json::json!(relay);
+        message["type"] = "relay".into();
+        let _ = sink.add(message.to_string());
+    })
+    .await;
+
+    let mut done = report.to_json();
+    done["type"] = "done".into();
+    done["status"] = op.outcome().into();
+    let _ = sink.add(done.to_string());
+    Ok(())
+}
 
-    Ok(result.to_string())
+/// Per-filter, per-relay sync watermarks: `[{filter, relay, syncedUntil}]`
+/// where `filter` is a hash of the filter without its time window.
+pub async fn get_sync_checkpoints() -> Result<String> {
+    Ok(negentropy_sync::checkpoints_json().to_string())
+}
+
+/// Forget every sync watermark so the next syncs start from scratch.
+pub async fn reset_sync_checkpoints() -> Result<()> {
+    negentropy_sync::reset_checkpoints();
+    Ok(())
 }
 
 pub async fn fetch_events(filter_json: String, timeout_secs: u32) -> Result<String> {
@@ -606,8 +883,13 @@ pub async fn fetch_events(filter_json: String, timeout_secs: u32) -> Result<Stri
     let filter = Filter::from_json(&filter_json)?;
     let timeout = Duration::from_secs(timeout_secs as u64);
 
-    let events: Events = client.fetch_events(filter, timeout).await
-        .unwrap_or_default();
+    let urls = ranked_relays(&client, RelayServiceFlags::READ).await;
+    let events: Events = if urls.is_empty() {
+        client.fetch_events(filter, timeout).await
+    } else {
+        client.fetch_events_from(urls, filter, timeout).await
+    }
+    .unwrap_or_default();
 
     let events_json: Vec<serde_json::Value> = events
         .into_iter()
@@ -618,373 +900,98 @@ pub async fn fetch_events(filter_json: String, timeout_secs: u32) -> Result<Stri
 }
 
 
+/// Interaction counts per note, counted on the main pool's relays (NIP-45
+/// COUNT where supported) and cached briefly.
 pub async fn fetch_counts_from_relays(
     note_ids: Vec<String>,
     user_pubkey_hex: Option<String>,
 ) -> Result<String> {
-    let main_client = get_client().await?;
-
-    let ids: Vec<EventId> = note_ids
-        .iter()
-        .filter_map(|id| EventId::from_hex(id).ok())
-        .collect();
-
-    if ids.is_empty() {
-        return Ok("{}".to_string());
-    }
-
+    let client = get_client().await?;
     let user_pk = user_pubkey_hex
         .as_ref()
         .and_then(|h| PublicKey::from_hex(h).ok());
 
-    let lock = counting_client_lock();
-    let client = {
-        let reader = lock.read().await;
-        reader.clone()
-    };
-    let client = match client {
-        Some(c) => c,
-        None => {
-            let mut writer = lock.write().await;
-            if let Some(ref c) = *writer {
-                c.clone()
-            } else {
-                let c = Client::builder()
-                    .database(MemoryDatabase::default())
-                    .build();
-                *writer = Some(c.clone());
-                c
-            }
-        }
-    };
-
-    let relay_urls: Vec<String> = main_client
-        .relays()
-        .await
-        .keys()
-        .map(|u| u.to_string())
-        .collect();
-    for url in &relay_urls {
-        let _ = client.add_relay(url.as_str()).await;
-    }
-    client.connect().await;
-
-    let filter = Filter::new()
-        .kinds([Kind::Reaction, Kind::Repost, Kind::ZapReceipt, Kind::TextNote])
-        .events(ids)
-        .limit(500);
-
-    let timeout = Duration::from_secs(5);
-    let events = client.fetch_events(filter, timeout).await
-        .unwrap_or_default();
-
-    let mut counts: HashMap<String, [usize; 4]> = HashMap::new();
-    let mut user_reacted: HashMap<String, bool> = HashMap::new();
-    let mut user_reposted: HashMap<String, bool> = HashMap::new();
-    let mut counted_events: HashMap<String, HashSet<String>> = HashMap::new();
-
-    for nid in &note_ids {
-        counts.insert(nid.clone(), [0; 4]);
-        user_reacted.insert(nid.clone(), false);
-        user_reposted.insert(nid.clone(), false);
-        counted_events.insert(nid.clone(), HashSet::new());
-    }
-
-    for event in events.iter() {
-        let kind = event.kind;
-        let event_id_hex = event.id.to_hex();
-        let is_user = user_pk.as_ref().map_or(false, |pk| event.pubkey == *pk);
-        let zap_sats = if kind == Kind::ZapReceipt {
-            super::database::extract_zap_amount_sats(event) as usize
-        } else {
-            0
-        };
-
-        if kind == Kind::TextNote {
-            if let Some(parent_id) = super::database::extract_reply_parent_id(event) {
-                if let Some(c) = counts.get_mut(&parent_id) {
-                    let seen = counted_events.get_mut(&parent_id).unwrap();
-                    if seen.insert(event_id_hex.clone()) {
-                        c[3] += 1;
-                    }
-                }
-            }
-            continue;
-        }
-
-        let target = super::database::extract_first_e_tag(event);
-        if let Some(target_id) = target {
-            if let Some(c) = counts.get_mut(&target_id) {
-                let seen = counted_events.get_mut(&target_id).unwrap();
-                if seen.insert(event_id_hex) {
-                    match kind {
-                        k if k == Kind::Reaction => {
-                            c[0] += 1;
-                            if is_user {
-                                user_reacted.insert(target_id, true);
-                            }
-                        }
-                        k if k == Kind::Repost => {
-                            c[1] += 1;
-                            if is_user {
-                                user_reposted.insert(target_id, true);
-                            }
-                        }
-                        k if k == Kind::ZapReceipt => c[2] += zap_sats,
-                        _ => {}
-                    }
-                }
-            }
-        }
-    }
-
-    let mut result = serde_json::Map::new();
-    for (nid, c) in &counts {
-        result.insert(
-            nid.clone(),
-            serde_json::json!({
-                "reactions": c[0],
-                "reposts": c[1],
-                "zaps": c[2],
-                "replies": c[3],
-                "hasReacted": user_reacted.get(nid).copied().unwrap_or(false),
-                "hasReposted": user_reposted.get(nid).copied().unwrap_or(false),
-            }),
-        );
-    }
-    Ok(serde_json::to_string(&result)?)
+    let counts = interaction_counts::count(&client, note_ids, user_pk).await;
+    Ok(serde_json::to_string(&counts)?)
 }
 
 #[frb]
 pub async fn stream_interaction_counts(
     note_ids: Vec<String>,
     user_pubkey_hex: Option<String>,
+    operation_id: Option<String>,
+    timeout_secs: Option<u64>,
     sink: StreamSink<String>,
 ) -> Result<()> {
-    let main_client = get_client().await?;
+    let client = get_client().await?;
+    let op = Operation::start("stream_interaction_counts", operation_id, timeout_secs);
+
+    let user_pk = user_pubkey_hex
+        .as_ref()
+        .and_then(|h| PublicKey::from_hex(h).ok());
 
-    let ids: Vec<EventId> = note_ids
+    let (cached, missing) = interaction_counts::cached(&note_ids, user_pk);
+    let ids: Vec<EventId> = missing
         .iter()
         .filter_map(|id| EventId::from_hex(id).ok())
         .collect();
-
+    if !cached.is_empty() && sink.add(serde_json::to_string(&cached)?).is_err() {
+        return Ok(());
+    }
     if ids.is_empty() {
         return Ok(());
     }
 
-    let user_pk = user_pubkey_hex
-        .as_ref()
-        .and_then(|h| PublicKey::from_hex(h).ok());
-
-    let lock = counting_client_lock();
-    let client = {
-        let reader = lock.read().await;
-        reader.clone()
-    };
-    let client = match client {
-        Some(c) => c,
-        None => {
-            let mut writer = lock.write().await;
-            if let Some(ref c) = *writer {
-                c.clone()
-            } else {
-                let c = Client::builder()
-                    .database(MemoryDatabase::default())
-                    .build();
-                *writer = Some(c.clone());
-                c
-            }
-        }
+    let mut tally = InteractionTally::new(missing, user_pk);
+    let emit = |tally: &InteractionTally| {
+        let mut counts = cached.clone();
+        counts.extend(tally.to_map());
+        sink.add(serde_json::to_string(&counts).unwrap_or_else(|_| "{}".to_string()))
+            .is_ok()
     };
 
-    let relay_urls: Vec<String> = main_client
-        .relays()
-        .await
-        .keys()
-        .map(|u| u.to_string())
-        .collect();
-    for url in &relay_urls {
-        let _ = client.add_relay(url.as_str()).await;
-    }
-    client.connect().await;
-
-    let filter = Filter::new()
-        .kinds([Kind::Reaction, Kind::Repost, Kind::ZapReceipt, Kind::TextNote])
-        .events(ids);
-
-    let Output { val: sub_id, .. } = client
-        .subscribe(filter, None)
-        .await
-        .map_err(|e| anyhow!("Subscribe failed: {}", e))?;
-
-    let mut notifications = client.notifications();
-
-    let mut counts: HashMap<String, [usize; 4]> = HashMap::new();
-    let mut user_reacted: HashMap<String, bool> = HashMap::new();
-    let mut user_reposted: HashMap<String, bool> = HashMap::new();
-    let mut seen_events: HashSet<String> = HashSet::new();
-
-    for nid in &note_ids {
-        counts.insert(nid.clone(), [0; 4]);
-        user_reacted.insert(nid.clone(), false);
-        user_reposted.insert(nid.clone(), false);
+    op.progress(0, None, "count");
+    let plan = interaction_counts::plan(&client, &ids, user_pk, &mut tally).await;
+    if tally.has_counts() && !emit(&tally) {
+        return Ok(());
     }
+    op.progress(0, None, "fetch");
 
-    let mut eose_count: usize = 0;
-    let relay_count = relay_urls.len();
+    let mut fetch = interaction_counts::PlanFetch::start(&client, plan).await;
     let mut last_emit = std::time::Instant::now();
     let mut has_data = false;
 
-    loop {
-        let recv = tokio::time::timeout(
-            Duration::from_secs(3),
-            notifications.recv(),
-        )
-        .await;
-
-        match recv {
-            Ok(Ok(notification)) => match notification {
-                RelayPoolNotification::Event {
-                    subscription_id,
-                    event,
-                    ..
-                } => {
-                    if subscription_id != sub_id {
-                        continue;
-                    }
-                    let event_id = event.id.to_hex();
-                    if seen_events.contains(&event_id) {
-                        continue;
-                    }
-                    seen_events.insert(event_id);
-
-                    let kind = event.kind;
-                    let is_user = user_pk
-                        .as_ref()
-                        .map_or(false, |pk| event.pubkey == *pk);
-                    let zap_sats = if kind == Kind::ZapReceipt {
-                        super::database::extract_zap_amount_sats(&event) as usize
-                    } else {
-                        0
-                    };
-
-                    let mut counted_for: HashSet<String> = HashSet::new();
-                    for tag in event.tags.iter() {
-                        let tag_kind = tag.kind();
-                        if matches!(
-                            tag_kind,
-                            TagKind::SingleLetter(SingleLetterTag {
-                                character: Alphabet::E,
-                                ..
-                            })
-                        ) {
-                            if let Some(ref_id) = tag.content() {
-                                let ref_hex = ref_id.to_string();
-                                if counted_for.contains(&ref_hex) {
-                                    continue;
-                                }
-                                if let Some(c) = counts.get_mut(&ref_hex) {
-                                    counted_for.insert(ref_hex.clone());
-                                    has_data = true;
-                                    match kind {
-                                        k if k == Kind::Reaction => {
-                                            c[0] += 1;
-                                            if is_user {
-                                                user_reacted
-                                                    .insert(ref_hex, true);
-                                            }
-                                        }
-                                        k if k == Kind::Repost => {
-                                            c[1] += 1;
-                                            if is_user {
-                                                user_reposted
-                                                    .insert(ref_hex, true);
-                                            }
-                                        }
-                                        k if k == Kind::ZapReceipt => {
-                                            c[2] += zap_sats;
-                                        }
-                                        k if k == Kind::TextNote => {
-                                            c[3] += 1;
-                                        }
-                                        _ => {}
-                                    }
-                                }
-                            }
-                        }
-                    }
-
-                    if has_data
-                        && last_emit.elapsed() >= Duration::from_millis(250)
-                    {
-                        let json = build_counts_json(
-                            &counts,
-                            &note_ids,
-                            &user_reacted,
-                            &user_reposted,
-                        );
-                        if sink.add(json).is_err() {
-                            break;
-                        }
-                        last_emit = std::time::Instant::now();
-                    }
-                }
-                RelayPoolNotification::Message { message, .. } => {
-                    if let RelayMessage::EndOfStoredEvents(sid) = message {
-                        if *sid == sub_id {
-                            eose_count += 1;
-                            if eose_count >= relay_count {
-                                break;
-                            }
-                        }
-                    }
-                }
-                RelayPoolNotification::Shutdown => break,
-            },
-            Ok(Err(_)) => break,
-            Err(_) => break,
+    while let Some(Ok(Some(event))) = op
+        .run(tokio::time::timeout(Duration::from_secs(3), fetch.next_event()))
+        .await
+    {
+        if !tally.add_event(&event) {
+            continue;
+        }
+        has_data = true;
+        if last_emit.elapsed() >= Duration::from_millis(250) {
+            if !emit(&tally) {
+                break;
+            }
+            last_emit = std::time::Instant::now();
         }
     }
 
-    if has_data {
-        let json = build_counts_json(
-            &counts,
-            &note_ids,
-            &user_reacted,
-            &user_reposted,
-        );
-        let _ = sink.add(json);
+    if fetch.is_truncated() {
+        tally.set_truncated();
+    }
+    tally.set_incomplete(&fetch.incomplete_kinds());
+    if has_data || tally.has_counts() {
+        emit(&tally);
+    }
+    if fetch.is_complete() {
+        interaction_counts::store(&tally.to_map(), user_pk);
     }
 
-    let _ = client.unsubscribe(&sub_id).await;
+    fetch.close(&client).await;
     Ok(())
 }
 
-fn build_counts_json(
-    counts: &HashMap<String, [usize; 4]>,
-    note_ids: &[String],
-    user_reacted: &HashMap<String, bool>,
-    user_reposted: &HashMap<String, bool>,
-) -> String {
-    let mut result = serde_json::Map::new();
-    for nid in note_ids {
-        if let Some(c) = counts.get(nid) {
-            result.insert(
-                nid.clone(),
-                serde_json::json!({
-                    "reactions": c[0],
-                    "reposts": c[1],
-                    "zaps": c[2],
-                    "replies": c[3],
-                    "hasReacted": user_reacted.get(nid).copied().unwrap_or(false),
-                    "hasReposted": user_reposted.get(nid).copied().unwrap_or(false),
-                }),
-            );
-        }
-    }
-    serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
-}
-
 pub async fn fetch_event_by_id(event_id: String, timeout_secs: u32) -> Result<Option<String>> {
     let client = get_client().await?;
     let id = EventId::from_hex(&event_id)?;
@@ -1071,12 +1078,17 @@ pub async fn fetch_events_multi_filter(filters_json: String, timeout_secs: u32)
 pub async fn send_event(event_json: String) -> Result<String> {
     let client = get_client().await?;
     let event = Event::from_json(&event_json)?;
-    let output = tokio::time::timeout(
-        Duration::from_secs(12),
-        client.send_event(&event),
-    )
-    .await
-    .map_err(|_| anyhow::anyhow!("event dispatch timed out"))??;
+    let urls = ranked_relays(&client, RelayServiceFlags::WRITE).await;
+    let dispatch = async {
+        if urls.is_empty() {
+            client.send_event(&event).await
+        } else {
+            client.send_event_to(urls, &event).await
+        }
+    };
+    let output = tokio::time::timeout(Duration::from_secs(12), dispatch)
+        .await
+        .map_err(|_| anyhow::anyhow!("event dispatch timed out"))??;
 
     let success: Vec<String> = output.success.iter().map(|u| u.to_string()).collect();
     let failed: HashMap<String, String> = output
@@ -1084,42 +1096,70 @@ pub async fn send_event(event_json: String) -> Result<String> {
         .iter()
         .map(|(u, e)| (u.to_string(), e.to_string()))
         .collect();
+    let rejections: HashMap<&String, serde_json::Value> = failed
+        .iter()
+        .map(|(u, e)| (u, crate::relay_messages::rejection_json(e)))
+        .collect();
 
     let result = serde_json::json!({
         "id": output.id().to_hex(),
         "success": success,
         "failed": failed,
+        "rejections": rejections,
     });
 
     Ok(result.to_string())
 }
 
+/// Publish to `relay_urls`, adding them to the pool first. Relays the relay
+/// policy rejects are listed under `failed` with the reason.
 pub async fn send_event_to(event_json: String, relay_urls: Vec<String>) -> Result<String> {
     let client = get_client().await?;
     let event = Event::from_json(&event_json)?;
 
+    let mut blocked: HashMap<String, String> = HashMap::new();
     let urls: Vec<RelayUrl> = relay_urls
         .iter()
         .filter_map(|u| RelayUrl::parse(u).ok())
+        .filter(|url| match crate::relay_policy::ensure_allowed(url, RelaySource::User) {
+            Ok(()) => true,
+            Err(e) => {
+                blocked.insert(url.to_string(), e.to_string());
+                false
+            }
+        })
         .collect();
+    if urls.is_empty() && !blocked.is_empty() {
+        let reasons: Vec<String> = blocked.into_values().collect();
+        return Err(anyhow::anyhow!(reasons.join("; ")));
+    }
 
-    let add_futures: Vec<_> = urls.iter().map(|url| client.add_relay(url.as_str())).collect();
+    let add_futures: Vec<_> = urls
+            .iter()
+            .map(|url| crate::relay_policy::add_relay(&client, url.as_str(), RelaySource::User))
+            .collect();
     futures::future::join_all(add_futures).await;
     client.connect().await;
 
     let output = client.send_event_to(urls, &event).await?;
 
     let success: Vec<String> = output.success.iter().map(|u| u.to_string()).collect();
-    let failed: HashMap<String, String> = output
+    let mut failed: HashMap<String, String> = output
         .failed
         .iter()
         .map(|(u, e)| (u.to_string(), e.to_string()))
         .collect();
+    failed.extend(blocked);
+    let rejections: HashMap<&String, serde_json::Value> = failed
+        .iter()
+        .map(|(u, e)| (u, crate::relay_messages::rejection_json(e)))
+        .collect();
 
     let result = serde_json::json!({
         "id": output.id().to_hex(),
         "success": success,
         "failed": failed,
+        "rejections": rejections,
     });
 
     Ok(result.to_string())
@@ -1142,7 +1182,10 @@ pub async fn broadcast_events(
     });
 
     if let Some(ref urls) = target_urls {
-        let add_futures: Vec<_> = urls.iter().map(|url| client.add_relay(url.as_str())).collect();
+        let add_futures: Vec<_> = urls
+            .iter()
+            .map(|url| crate::relay_policy::add_relay(&client, url.as_str(), RelaySource::User))
+            .collect();
         futures::future::join_all(add_futures).await;
         client.connect().await;
     }
@@ -1180,11 +1223,19 @@ pub async fn broadcast_events(
 pub async fn stream_broadcast_events(
     events_json: String,
     relay_urls: Option<Vec<String>>,
+    operation_id: Option<String>,
+    timeout_secs: Option<u64>,
     sink: StreamSink<String>,
 ) -> Result<()> {
     let client = get_client().await?;
+    let op = Operation::start("stream_broadcast_events", operation_id, timeout_secs);
     let events: Vec<serde_json::Value> = serde_json::from_str(&events_json)?;
     let total = events.len() as u32;
+    let parsed: Vec<Event> = events
+        .iter()
+        .filter_map(|v| Event::from_json(v.to_string()).ok())
+        .collect();
+    let invalid = total - parsed.len() as u32;
 
     let target_urls: Option<Vec<RelayUrl>> = relay_urls.map(|urls| {
         urls.iter()
@@ -1192,32 +1243,68 @@ pub async fn stream_broadcast_events(
             .collect()
     });
 
+    let (sent, failed) = publish_events(&client, parsed, target_urls, &op, |sent, failed| {
+        let progress = serde_json::json!({
+            "sent": sent,
+            "failed": failed + invalid,
+            "total": total,
+            "done": false,
+        });
+        sink.add(progress.to_string()).is_ok()
+    })
+    .await;
+
+    let done = serde_json::json!({
+        "sent": sent,
+        "failed": failed + invalid,
+        "total": total,
+        "done": true,
+        "status": op.outcome(),
+    });
+    let _ = sink.add(done.to_string());
+
+    Ok(())
+}
+
+/// Publish `events` in batches to `target_urls` (added to the pool first) or
+/// to the write relays. `on_progress(sent, failed)` runs after each batch and
+/// stops the broadcast by returning `false`. Returns the final `(sent, failed)`.
+pub(crate) async fn publish_events(
+    client: &Client,
+    events: Vec<Event>,
+    target_urls: Option<Vec<RelayUrl>>,
+    op: &Operation,
+    on_progress: impl Fn(u32, u32) -> bool,
+) -> (u32, u32) {
     if let Some(ref urls) = target_urls {
-        let add_futures: Vec<_> = urls.iter().map(|url| client.add_relay(url.as_str())).collect();
+        let add_futures: Vec<_> = urls
+            .iter()
+            .map(|url| crate::relay_policy::add_relay(client, url.as_str(), RelaySource::User))
+            .collect();
         futures::future::join_all(add_futures).await;
         client.connect().await;
     }
 
+    let total = events.len() as u64;
     let mut sent = 0u32;
     let mut failed = 0u32;
 
     const BATCH_SIZE: usize = 50;
 
     for chunk in events.chunks(BATCH_SIZE) {
+        if op.is_stopped() {
+            break;
+        }
         let futures: Vec<_> = chunk
             .iter()
-            .map(|event_val| {
+            .map(|event| {
                 let client = client.clone();
                 let target_urls = target_urls.clone();
-                let event_str = event_val.to_string();
                 async move {
-                    let Ok(event) = Event::from_json(&event_str) else {
-                        return false;
-                    };
                     let result = if let Some(ref urls) = target_urls {
-                        client.send_event_to(urls.clone(), &event).await
+                        client.send_event_to(urls.clone(), event).await
                     } else {
-                        client.send_event(&event).await
+                        client.send_event(event).await
                     };
                     match result {
                         Ok(output) => !output.success.is_empty(),
@@ -1227,32 +1314,21 @@ pub async fn stream_broadcast_events(
             })
             .collect();
 
-        let results = futures::future::join_all(futures).await;
+        let Some(results) = op.run(futures::future::join_all(futures)).await else {
+            break;
+        };
 
         for ok in results {
             if ok { sent += 1; } else { failed += 1; }
         }
+        op.progress((sent + failed) as u64, Some(total), "broadcasting");
 
-        let progress = serde_json::json!({
-            "sent": sent,
-            "failed": failed,
-            "total": total,
-            "done": false,
-        });
-        if sink.add(progress.to_string()).is_err() {
-            return Ok(());
+        if !on_progress(sent, failed) {
+            break;
         }
     }
 
-    let done = serde_json::json!({
-        "sent": sent,
-        "failed": failed,
-        "total": total,
-        "done": true,
-    });
-    let _ = sink.add(done.to_string());
-
-    Ok(())
+    (sent, failed)
 }
 
 pub async fn request_to_vanish(relay_urls: Vec<String>, reason: String) -> Result<String> {
@@ -1338,10 +1414,13 @@ pub async fn delete_events(event_ids: Vec<String>, reason: String) -> Result<Str
 #[frb]
 pub async fn fetch_all_events_for_author(
     author_hex: String,
+    operation_id: Option<String>,
+    timeout_secs: Option<u64>,
     sink: StreamSink<String>,
 ) -> Result<()> {
     let client = get_client().await?;
     let pubkey = PublicKey::from_hex(&author_hex)?;
+    let op = Operation::start("fetch_all_events_for_author", operation_id, timeout_secs);
 
     const PAGE_SIZE: usize = 500;
     let mut seen: HashSet<String> = HashSet::new();
@@ -1395,14 +1474,22 @@ pub async fn fetch_all_events_for_author(
             // fetch_events_from hits the relay directly (no LMDB merge).
             // It closes automatically on EOSE, returning exactly what that
             // relay has for this filter page.
-            let events = match client
-                .fetch_events_from(
+            let started = std::time::Instant::now();
+            let fetched = op
+                .run(client.fetch_events_from(
                     vec![relay_url.clone()],
                     filter,
                     Duration::from_secs(15),
-                )
-                .await
-            {
+                ))
+                .await;
+            let Some(fetched) = fetched else {
+                return Ok(());
+            };
+            crate::relay_scores::record_fetch(
+                relay_url,
+                fetched.as_ref().ok().map(|_| started.elapsed()),
+            );
+            let events = match fetched {
                 Ok(e) => e,
                 Err(_) => {
                     exhausted.insert(key);
@@ -1435,6 +1522,7 @@ pub async fn fetch_all_events_for_author(
                     return Ok(());
                 }
             }
+            op.progress(seen.len() as u64, None, &key);
 
             if new_count == 0 {
                 // Relay truly has nothing older — done with this relay.
@@ -1460,10 +1548,13 @@ pub async fn fetch_profile_events(
     author_hex: String,
     kinds_str: String,
     since_timestamp: i64,
+    operation_id: Option<String>,
+    timeout_secs: Option<u64>,
     sink: StreamSink<String>,
 ) -> Result<()> {
     let client = get_client().await?;
     let pubkey = PublicKey::from_hex(&author_hex)?;
+    let op = Operation::start("fetch_profile_events", operation_id, timeout_secs);
 
     let kind_list: Vec<Kind> = kinds_str
         .split(',')
@@ -1516,12 +1607,13 @@ pub async fn fetch_profile_events(
                 .collect()
         };
 
-        if active.is_empty() {
+        if active.is_empty() || op.is_stopped() {
             break;
         }
 
         let mut handles = Vec::new();
         for relay_url in active {
+            let op = op.clone();
             let client = client.clone();
             let kind_list = kind_list.clone();
             let seen = seen.clone();
@@ -1544,14 +1636,22 @@ pub async fn fetch_profile_events(
                     filter = filter.since(since);
                 }
 
-                let events = match client
-                    .fetch_events_from(
-                        vec![relay_url],
+                let started = std::time::Instant::now();
+                let fetched = op
+                    .run(client.fetch_events_from(
+                        vec![relay_url.clone()],
                         filter,
                         Duration::from_secs(15),
-                    )
-                    .await
-                {
+                    ))
+                    .await;
+                let Some(fetched) = fetched else {
+                    return false;
+                };
+                crate::relay_scores::record_fetch(
+                    &relay_url,
+                    fetched.as_ref().ok().map(|_| started.elapsed()),
+                );
+                let events = match fetched {
                     Ok(e) => e,
                     Err(_) => {
                         exhausted.lock().await.insert(key);
@@ -1609,6 +1709,7 @@ pub async fn fetch_profile_events(
         for handle in handles {
             let _ = handle.await;
         }
+        op.progress(seen.lock().await.len() as u64, None, "fetching");
     }
 
     Ok(())
@@ -1617,46 +1718,31 @@ pub async fn fetch_profile_events(
 #[frb]
 pub async fn subscribe_to_events(
     filter_json: String,
+    operation_id: Option<String>,
+    timeout_secs: Option<u64>,
     sink: StreamSink<String>,
 ) -> Result<()> {
     let client = get_client().await?;
     let filter = Filter::from_json(&filter_json)?;
+    let op = Operation::start("subscribe_to_events", operation_id, timeout_secs);
 
-    let Output { val: sub_id, .. } = client.subscribe(filter, None).await
-        .map_err(|e| anyhow!("Subscribe failed: {}", e))?;
-
-    let mut notifications = client.notifications();
+    let (sub_id, mut rx) = super::event_bus::subscribe_channel(&client, filter).await?;
 
-    loop {
-        match notifications.recv().await {
-            Ok(notification) => {
-                if let RelayPoolNotification::Event {
-                    subscription_id,
-                    event,
-                    ..
-                } = notification
-                {
-                    if subscription_id == sub_id {
-                        let _ = client.database().save_event(&event).await;
-                        if let Ok(json) = serde_json::to_string(
-                            &serde_json::from_str::<serde_json::Value>(&event.as_json())
-                                .unwrap_or_default(),
-                        ) {
-                            if sink.add(json).is_err() {
-                                break;
-                            }
-                        }
-                    }
-                } else if let RelayPoolNotification::Shutdown = notification {
+    while let Some(Some(item)) = op.run(rx.recv()).await {
+        if let super::event_bus::BusItem::Event(event) = item {
+            let _ = client.database().save_event(&event).await;
+            if let Ok(json) = serde_json::to_string(
+                &serde_json::from_str::<serde_json::Value>(&event.as_json())
+                    .unwrap_or_default(),
+            ) {
+                if sink.add(json).is_err() {
                     break;
                 }
             }
-            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
-            Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
         }
     }
 
-    let _ = client.unsubscribe(&sub_id).await;
+    super::event_bus::unsubscribe_id(&client, &sub_id).await;
 
     Ok(())
 }
@@ -1726,26 +1812,34 @@ async fn resolve_thread_root_internal(client: &Client, note_id: &str) -> Result<
 pub async fn sync_replies_recursive(
     note_id: String,
     max_depth: u32,
+    operation_id: Option<String>,
+    timeout_secs: Option<u64>,
 ) -> Result<u32> {
     let client = get_client().await?;
+    let op = Operation::start("sync_replies_recursive", operation_id, timeout_secs);
     let mut processed_ids: HashSet<String> = HashSet::new();
     processed_ids.insert(note_id.clone());
     let mut pending_ids: Vec<EventId> = vec![EventId::from_hex(&note_id)?];
     let mut total_fetched: u32 = 0;
 
-    for _ in 0..max_depth {
+    for depth in 0..max_depth {
         if pending_ids.is_empty() {
             break;
         }
+        op.progress(depth as u64, Some(max_depth as u64), "replies");
 
         let filter = Filter::new()
             .kind(Kind::TextNote)
             .events(pending_ids.clone())
             .limit(200);
 
-        let events: Events = client
-            .fetch_events(filter, Duration::from_secs(8))
-            .await?;
+        let Some(fetched) = op
+            .run(client.fetch_events(filter, Duration::from_secs(8)))
+            .await
+        else {
+            return Ok(total_fetched);
+        };
+        let events: Events = fetched?;
 
         let mut new_ids: Vec<EventId> = Vec::new();
         for event in events.iter() {
@@ -1789,12 +1883,13 @@ pub async fn sync_replies_recursive(
             .collect();
 
         if !missing.is_empty() {
+            op.progress(max_depth as u64, Some(max_depth as u64), "profiles");
             let profile_filter = Filter::new()
                 .authors(missing)
                 .kind(Kind::Metadata)
                 .limit(200);
-            let _ = client
-                .fetch_events(profile_filter, Duration::from_secs(5))
+            let _ = op
+                .run(client.fetch_events(profile_filter, Duration::from_secs(5)))
                 .await;
         }
     }
@@ -2732,85 +2827,146 @@ pub async fn fetch_repost_originals(repost_event_ids_json: String) -> Result<u32
     Ok(fetched_count)
 }
 
+/// Follower counts as `{pubkeyHex: count}`, from the configured providers
+/// (NIP-45 COUNT on the user's relays, then the local database by default).
+/// Pubkeys no provider could answer are left out.
 pub async fn fetch_follower_counts(pubkey_hexes: Vec<String>) -> Result<String> {
-    use tokio_tungstenite::{connect_async, tungstenite::Message};
-    use futures::{SinkExt, StreamExt};
-
-    if pubkey_hexes.is_empty() {
+    let pubkeys: Vec<PublicKey> = pubkey_hexes
+        .iter()
+        .filter_map(|h| PublicKey::from_hex(h).ok())
+        .collect();
+    if pubkeys.is_empty() {
         return Ok("{}".to_string());
     }
 
-    let primal_url = "wss://cache2.primal.net/v1";
-    let sub_id = format!("primal_fc_{}", std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .map(|d| d.as_millis())
-        .unwrap_or(0));
+    let client = get_client().await?;
+    let counts = crate::follower_counts::fetch(&client, pubkeys).await;
+    let follower_counts: serde_json::Map<String, serde_json::Value> = counts
+        .into_iter()
+        .map(|(pk, count)| (pk.to_hex(), serde_json::json!(count)))
+        .collect();
+    Ok(serde_json::to_string(&follower_counts)?)
+}
 
-    let request = serde_json::json!([
-        "REQ",
-        sub_id,
-        {
-            "cache": [
-                "user_infos",
-                {"pubkeys": pubkey_hexes}
-            ]
-        }
-    ]);
-
-    let (ws_stream, _) = match tokio::time::timeout(
-        Duration::from_secs(5),
-        connect_async(primal_url),
-    ).await {
-        Ok(Ok(s)) => s,
-        _ => return Ok("{}".to_string()),
-    };
+/// Follower count providers in the order they are asked: any of `nip45`,
+/// `local` and `primal`.
+pub async fn get_follower_count_providers() -> Result<Vec<String>> {
+    Ok(crate::follower_counts::providers()
+        .into_iter()
+        .filter_map(|p| serde_json::to_value(p).ok())
+        .filter_map(|v| v.as_str().map(str::to_string))
+        .collect())
+}
 
-    let (mut write, mut read) = ws_stream.split();
-    let _ = write.send(Message::Text(request.to_string())).await;
+/// Replace and persist the follower count providers. `primal` sends the
+/// looked-up pubkeys to Primal's servers and is never enabled by default.
+pub async fn set_follower_count_providers(providers: Vec<String>) -> Result<()> {
+    crate::follower_counts::set_providers(&providers)
+}
 
-    let mut follower_counts: serde_json::Map<String, serde_json::Value> = serde_json::Map::new();
+/// NIP-50 search relays queried by [`search_with_relays`].
+pub async fn get_search_relays() -> Result<Vec<String>> {
+    Ok(crate::search_relays::relays())
+}
 
-    let timeout = tokio::time::sleep(Duration::from_secs(5));
-    tokio::pin!(timeout);
+/// Replace and persist the NIP-50 search relays. An empty list keeps
+/// search local.
+pub async fn set_search_relays(relay_urls: Vec<String>) -> Result<()> {
+    crate::search_relays::set_relays(relay_urls)
+}
 
-    loop {
-        tokio::select! {
-            _ = &mut timeout => break,
-            msg = read.next() => {
-                match msg {
-                    Some(Ok(Message::Text(text))) => {
-                        if let Ok(decoded) = serde_json::from_str::<serde_json::Value>(&text) {
-                            if let Some(arr) = decoded.as_array() {
-                                if arr.len() >= 1 {
-                                    let msg_type = arr[0].as_str().unwrap_or("");
-                                    if msg_type == "EOSE" {
-                                        break;
-                                    }
-                                    if msg_type == "EVENT" && arr.len() >= 3 {
-                                        let event = &arr[2];
-                                        let kind = event["kind"].as_u64().unwrap_or(0);
-                                        if kind == 10000133 {
-                                            if let Ok(content) = serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(
-                                                event["content"].as_str().unwrap_or("{}")
-                                            ) {
-                                                for (k, v) in content {
-                                                    if v.is_number() {
-                                                        follower_counts.insert(k, v);
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    Some(Ok(Message::Close(_))) | None => break,
-                    _ => {}
-                }
-            }
+/// Search the local indexes and the NIP-50 search relays together.
+/// `target` is `notes` or `profiles`. Relay results are saved locally, and
+/// a hit found in both places appears once. Returns `{results, local,
+/// remote, localError, remoteError}`: hydrated notes or flat profiles,
+/// each with `source` set to `local`, `relay` or `both`, how many hits
+/// each side had, and why a side failed, if it did.
+pub async fn search_with_relays(
+    query: String,
+    target: String,
+    limit: u32,
+    timeout_secs: Option<u64>,
+    current_user_pubkey_hex: Option<String>,
+) -> Result<String> {
+    let client = get_client().await?;
+    let limit = limit as usize;
+    let timeout = Duration::from_secs(timeout_secs.unwrap_or(5));
+    let kind = match target.as_str() {
+        "notes" => Kind::TextNote,
+        "profiles" => Kind::Metadata,
+        _ => return Err(anyhow!("Unknown search target: {}", target)),
+    };
+
+    let local = async {
+        if kind == Kind::Metadata {
+            crate::profile_index::search(&client, &query, limit).await
+        } else {
+            let filter = crate::search_index::SearchFilter {
+                kinds: HashSet::from([kind.as_u16()]),
+                ..Default::default()
+            };
+            crate::search_index::search(&client, &query, &filter, 0, limit)
+                .await
+                .map(|page| page.events)
         }
-    }
+    };
+    let remote = crate::search_relays::fetch(
+        &client,
+        &query,
+        Filter::new().kind(kind).limit(limit),
+        timeout,
+    );
+    let (local, remote) = futures::join!(local, remote);
+    let local_error = local.as_ref().err().map(|e| e.to_string());
+    let remote_error = remote.as_ref().err().map(|e| e.to_string());
+    let local = local.unwrap_or_default();
+    let remote = remote.unwrap_or_default();
+    let (local_count, remote_count) = (local.len(), remote.len());
+
+    let mut merged = if kind == Kind::Metadata {
+        crate::search_relays::merge(local, remote, |e| e.pubkey)
+    } else {
+        crate::search_relays::merge(local, remote, |e| e.id)
+    };
+    merged.truncate(limit);
 
-    Ok(serde_json::to_string(&follower_counts)?)
+    let results: Vec<serde_json::Value> = if kind == Kind::Metadata {
+        merged
+            .iter()
+            .filter_map(|(event, source)| {
+                let metadata = Metadata::from_json(&event.content).ok()?;
+                let mut profile = super::database::metadata_to_flat_json(event, &metadata);
+                profile["source"] = source.as_str().into();
+                Some(profile)
+            })
+            .collect()
+    } else {
+        let sources: HashMap<String, &str> = merged
+            .iter()
+            .map(|(event, source)| (event.id.to_hex(), source.as_str()))
+            .collect();
+        let events: Vec<Event> = merged.into_iter().map(|(event, _)| event).collect();
+        let hydrated = super::database::hydrate_notes_pub(
+            &client,
+            &events,
+            false,
+            current_user_pubkey_hex,
+        )
+        .await?;
+        let mut notes: Vec<serde_json::Value> = serde_json::from_str(&hydrated)?;
+        for note in notes.iter_mut() {
+            let source = note["id"].as_str().and_then(|id| sources.get(id)).copied();
+            note["source"] = source.unwrap_or("local").into();
+        }
+        notes
+    };
+
+    Ok(serde_json::json!({
+        "results": results,
+        "local": local_count,
+        "remote": remote_count,
+        "localError": local_error,
+        "remoteError": remote_error,
+    })
+    .to_string())
 }
@@ -0,0 +1,605 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use flutter_rust_bridge::frb;
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, Notify, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use super::operations::Operation;
+use super::relay::get_client_pub;
+use crate::frb_generated::StreamSink;
+
+const CONFIG_FILE: &str = "sync_scheduler.json";
+const TASK_TIMEOUT_SECS: u64 = 60;
+const FEED_LOOKBACK_SECS: u64 = 2 * 86400;
+const NOTIFICATIONS_LOOKBACK_SECS: u64 = 30 * 86400;
+/// Gift wraps carry a randomized `created_at` up to two days in the past.
+const GIFT_WRAP_SKEW_SECS: u64 = 2 * 86400 + 3600;
+const OVERLAP_SECS: u64 = 60;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "camelCase")]
+enum SyncTask {
+    Feed,
+    Notifications,
+    Dms,
+    Lists,
+    Metadata,
+}
+
+const TASKS: [SyncTask; 5] = [
+    SyncTask::Feed,
+    SyncTask::Notifications,
+    SyncTask::Dms,
+    SyncTask::Lists,
+    SyncTask::Metadata,
+];
+
+impl SyncTask {
+    fn parse(s: &str) -> Result<Self> {
+        serde_json::from_value(serde_json::Value::String(s.to_string()))
+            .map_err(|_| anyhow!("Unknown sync task: {}", s))
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Feed => "feed",
+            Self::Notifications => "notifications",
+            Self::Dms => "dms",
+            Self::Lists => "lists",
+            Self::Metadata => "metadata",
+        }
+    }
+}
+
+/// How aggressively to sync. Dart picks the profile from battery and
+/// network state; Rust only applies it.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+enum SyncProfile {
+    Normal,
+    /// On battery saver: everything runs four times less often and
+    /// follows' metadata is skipped.
+    BatterySaver,
+    /// On a metered connection: twice less often, no follows' metadata.
+    DataSaver,
+    Paused,
+}
+
+impl SyncProfile {
+    fn interval_multiplier(&self) -> u32 {
+        match self {
+            Self::Normal => 1,
+            Self::DataSaver => 2,
+            Self::BatterySaver => 4,
+            Self::Paused => 0,
+        }
+    }
+
+    fn runs(&self, task: SyncTask) -> bool {
+        match self {
+            Self::Normal => true,
+            Self::BatterySaver | Self::DataSaver => task != SyncTask::Metadata,
+            Self::Paused => false,
+        }
+    }
+}
+
+/// Base interval per task in seconds; `0` disables the task.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+struct SchedulerConfig {
+    intervals: HashMap<SyncTask, u64>,
+    profile: SyncProfile,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            intervals: HashMap::from([
+                (SyncTask::Feed, 300),
+                (SyncTask::Notifications, 120),
+                (SyncTask::Dms, 120),
+                (SyncTask::Lists, 1800),
+                (SyncTask::Metadata, 3600),
+            ]),
+            profile: SyncProfile::Normal,
+        }
+    }
+}
+
+/// Partial update of [`SchedulerConfig`] as sent from Dart.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct ConfigUpdate {
+    intervals: HashMap<SyncTask, u64>,
+    profile: Option<SyncProfile>,
+}
+
+impl SchedulerConfig {
+    /// This config with the intervals and profile from `json` laid over it.
+    fn updated(&self, json: &str) -> Result<Self> {
+        let update: ConfigUpdate = serde_json::from_str(json)?;
+        let mut config = self.clone();
+        config.intervals.extend(update.intervals);
+        if let Some(profile) = update.profile {
+            config.profile = profile;
+        }
+        Ok(config)
+    }
+
+    fn interval(&self, task: SyncTask) -> Option<Duration> {
+        let base = self.intervals.get(&task).copied().unwrap_or(0);
+        let multiplier = self.profile.interval_multiplier();
+        if base == 0 || multiplier == 0 || !self.profile.runs(task) {
+            return None;
+        }
+        Some(Duration::from_secs(base * multiplier as u64))
+    }
+}
+
+#[derive(Default)]
+struct TaskState {
+    running: bool,
+    next_run: Option<Instant>,
+    last_run_at: Option<u64>,
+    /// Unix time the last successful run started; the next run fetches from
+    /// a little before it.
+    last_success_at: Option<u64>,
+    last_result: Option<serde_json::Value>,
+    last_error: Option<String>,
+}
+
+#[derive(Default)]
+struct Scheduler {
+    /// Where `config` is persisted, next to the database.
+    path: Option<PathBuf>,
+    user_pubkey: Option<PublicKey>,
+    config: SchedulerConfig,
+    tasks: HashMap<SyncTask, TaskState>,
+    handle: Option<JoinHandle<()>>,
+}
+
+static SCHEDULER: OnceLock<Mutex<Scheduler>> = OnceLock::new();
+static WAKE: OnceLock<Notify> = OnceLock::new();
+static STATUS_SINK: OnceLock<RwLock<Option<StreamSink<String>>>> = OnceLock::new();
+
+fn scheduler() -> &'static Mutex<Scheduler> {
+    SCHEDULER.get_or_init(|| Mutex::new(Scheduler::default()))
+}
+
+fn wake() -> &'static Notify {
+    WAKE.get_or_init(Notify::new)
+}
+
+fn status_sink_state() -> &'static RwLock<Option<StreamSink<String>>> {
+    STATUS_SINK.get_or_init(|| RwLock::new(None))
+}
+
+fn save(s: &Scheduler) -> Result<()> {
+    if let Some(path) = s.path.as_ref() {
+        std::fs::write(path, serde_json::to_vec_pretty(&s.config)?)?;
+    }
+    Ok(())
+}
+
+/// Load the persisted intervals and profile stored next to the database,
+/// if any.
+pub(crate) async fn load(db_path: Option<&str>) {
+    let path = db_path.map(|p| {
+        PathBuf::from(p)
+            .parent()
+            .unwrap_or_else(|| std::path::Path::new("."))
+            .join(CONFIG_FILE)
+    });
+    let config = path
+        .as_ref()
+        .and_then(|p| std::fs::read(p).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+    let mut s = scheduler().lock().await;
+    s.path = path;
+    s.config = config;
+    reschedule(&mut s);
+}
+
+fn status_json(s: &Scheduler) -> serde_json::Value {
+    let now = Instant::now();
+    let unix_now = Timestamp::now().as_secs();
+    let tasks: Vec<serde_json::Value> = TASKS
+        .iter()
+        .map(|task| {
+            let state = s.tasks.get(task);
+            let next_in = state
+                .and_then(|t| t.next_run)
+                .map(|at| at.saturating_duration_since(now).as_secs());
+            serde_json::json!({
+                "task": task.as_str(),
+                "enabled": s.config.interval(*task).is_some(),
+                "intervalSecs": s.config.interval(*task).map(|d| d.as_secs()),
+                "running": state.is_some_and(|t| t.running),
+                "lastRunAt": state.and_then(|t| t.last_run_at),
+                "nextRunAt": next_in.map(|secs| unix_now + secs),
+                "lastResult": state.and_then(|t| t.last_result.clone()),
+                "lastError": state.and_then(|t| t.last_error.clone()),
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "running": s.handle.as_ref().is_some_and(|h| !h.is_finished()),
+        "userPubkey": s.user_pubkey.map(|pk| pk.to_hex()),
+        "profile": s.config.profile,
+        "tasks": tasks,
+    })
+}
+
+async fn emit_status() {
+    let json = status_json(&*scheduler().lock().await).to_string();
+    let sink = status_sink_state().read().await;
+    if let Some(sink) = sink.as_ref() {
+        let _ = sink.add(json);
+    }
+}
+
+/// Recompute every task's next run from its last run and the current config.
+fn reschedule(s: &mut Scheduler) {
+    let now = Instant::now();
+    let unix_now = Timestamp::now().as_secs();
+    for task in TASKS {
+        let interval = s.config.interval(task);
+        let state = s.tasks.entry(task).or_default();
+        state.next_run = interval.map(|interval| {
+            let elapsed = state
+                .last_run_at
+                .map(|at| Duration::from_secs(unix_now.saturating_sub(at)))
+                .unwrap_or(interval);
+            now + interval.saturating_sub(elapsed)
+        });
+    }
+}
+
+fn since(last_success_at: Option<u64>, lookback: u64) -> Timestamp {
+    let now = Timestamp::now().as_secs();
+    let since = match last_success_at {
+        Some(at) => at.saturating_sub(OVERLAP_SECS),
+        None => now.saturating_sub(lookback),
+    };
+    Timestamp::from(since)
+}
+
+/// Negentropy reconciliation of `filter` against the read relays, resuming
+/// from the last checkpoint and falling back to REQ where unsupported.
+async fn reconcile(client: &Client, filter: Filter, op: &Operation) -> Result<serde_json::Value> {
+    let report = crate::negentropy_sync::sync(client, filter, op, |_| {}).await;
+    if op.is_stopped() {
+        return Err(anyhow!("sync {}", op.outcome()));
+    }
+    // Per-relay details would bloat every status update; keep the totals.
+    let mut totals = report.to_json();
+    if let Some(totals) = totals.as_object_mut() {
+        totals.remove("relays");
+    }
+    Ok(totals)
+}
+
+/// Plain REQ for filters negentropy can't help with, e.g. `#p` lookups.
+async fn fetch(client: &Client, filter: Filter, op: &Operation) -> Result<serde_json::Value> {
+    let events = op
+        .run(client.fetch_events(filter, Duration::from_secs(10)))
+        .await
+        .ok_or_else(|| anyhow!("fetch {}", op.outcome()))??;
+    Ok(serde_json::json!({ "received": events.len() }))
+}
+
+async fn follows(client: &Client, user: PublicKey) -> Vec<PublicKey> {
+    client
+        .database()
+        .contacts_public_keys(user)
+        .await
+        .map(|set| set.into_iter().collect())
+        .unwrap_or_default()
+}
+
+async fn run_task(
+    client: &Client,
+    task: SyncTask,
+    user: PublicKey,
+    last_success_at: Option<u64>,
+    op: &Operation,
+) -> Result<serde_json::Value> {
+    match task {
+        SyncTask::Feed => {
+            let authors = follows(client, user).await;
+            if authors.is_empty() {
+                return Ok(serde_json::json!({ "received": 0 }));
+            }
+            let filter = Filter::new()
+                .kinds([
+                    Kind::TextNote,
+                    Kind::EventDeletion,
+                    Kind::Repost,
+                    Kind::LongFormTextNote,
+                ])
+                .authors(authors)
+                .since(since(last_success_at, FEED_LOOKBACK_SECS));
+            let result = reconcile(client, filter, op).await?;
+            let _ = super::database::db_process_deletion_events().await;
+            Ok(result)
+        }
+        SyncTask::Notifications => {
+            let filter = Filter::new()
+                .kinds([
+                    Kind::TextNote,
+                    Kind::Repost,
+                    Kind::Reaction,
+                    Kind::ZapReceipt,
+                ])
+                .pubkey(user)
+                .since(since(last_success_at, NOTIFICATIONS_LOOKBACK_SECS));
+            fetch(client, filter, op).await
+        }
+        SyncTask::Dms => {
+            let since =
+                last_success_at.map(|at| Timestamp::from(at.saturating_sub(GIFT_WRAP_SKEW_SECS)));
+            let mut filter = Filter::new().kind(Kind::GiftWrap).pubkey(user);
+            if let Some(since) = since {
+                filter = filter.since(since);
+            }
+            reconcile(client, filter, op).await
+        }
+        SyncTask::Lists => {
+            let filter = Filter::new()
+                .kinds([
+                    Kind::Metadata,
+                    Kind::ContactList,
+                    Kind::MuteList,
+                    Kind::PinList,
+                    Kind::RelayList,
+                    Kind::Bookmarks,
+                    Kind::from(30001),
+                    Kind::InboxRelays,
+                    Kind::FollowSet,
+                    Kind::BookmarkSet,
+                ])
+                .author(user);
+            fetch(client, filter, op).await
+        }
+        SyncTask::Metadata => {
+            let authors = follows(client, user).await;
+            if authors.is_empty() {
+                return Ok(serde_json::json!({ "received": 0 }));
+            }
+            let filter = Filter::new().kind(Kind::Metadata).authors(authors);
+            reconcile(client, filter, op).await
+        }
+    }
+}
+
+fn due_task(s: &Scheduler) -> Option<SyncTask> {
+    let now = Instant::now();
+    TASKS.into_iter().find(|task| {
+        s.tasks
+            .get(task)
+            .and_then(|t| t.next_run)
+            .is_some_and(|at| at <= now)
+    })
+}
+
+fn next_wakeup(s: &Scheduler) -> Option<Instant> {
+    s.tasks.values().filter_map(|t| t.next_run).min()
+}
+
+async fn run_loop() {
+    loop {
+        let (due, user, last_success_at, wakeup) = {
+            let s = scheduler().lock().await;
+            let due = due_task(&s);
+            let last = due
+                .and_then(|t| s.tasks.get(&t))
+                .and_then(|t| t.last_success_at);
+            (due, s.user_pubkey, last, next_wakeup(&s))
+        };
+
+        let (Some(task), Some(user)) = (due, user) else {
+            match wakeup {
+                Some(at) => {
+                    tokio::select! {
+                        _ = tokio::time::sleep_until(at) => {}
+                        _ = wake().notified() => {}
+                    }
+                }
+                None => wake().notified().await,
+            }
+            continue;
+        };
+
+        let started_at = Timestamp::now().as_secs();
+        {
+            let mut s = scheduler().lock().await;
+            let state = s.tasks.entry(task).or_default();
+            state.running = true;
+            state.next_run = None;
+        }
+        emit_status().await;
+
+        let op = Operation::start(
+            "sync_scheduler",
+            Some(format!("sync-{}", task.as_str())),
+            Some(TASK_TIMEOUT_SECS),
+        );
+        let result = match get_client_pub().await {
+            Ok(client) => run_task(&client, task, user, last_success_at, &op).await,
+            Err(e) => Err(e),
+        };
+        drop(op);
+
+        {
+            let mut s = scheduler().lock().await;
+            let state = s.tasks.entry(task).or_default();
+            state.running = false;
+            state.last_run_at = Some(started_at);
+            match result {
+                Ok(value) => {
+                    state.last_success_at = Some(started_at);
+                    state.last_result = Some(value);
+                    state.last_error = None;
+                }
+                Err(e) => state.last_error = Some(e.to_string()),
+            }
+            reschedule(&mut s);
+        }
+        emit_status().await;
+    }
+}
+
+/// Start keeping the local database warm for `user_pubkey_hex`. `config_json`
+/// may set `intervals` (seconds per task: `feed`, `notifications`, `dms`,
+/// `lists`, `metadata`; `0` disables one) and `profile`, as in
+/// [`set_sync_config`]. Restarting for the same user keeps the previous run
+/// times.
+pub async fn start_sync_scheduler(
+    user_pubkey_hex: String,
+    config_json: Option<String>,
+) -> Result<()> {
+    let user = PublicKey::from_hex(&user_pubkey_hex)?;
+    {
+        let mut s = scheduler().lock().await;
+        if let Some(json) = config_json {
+            s.config = s.config.updated(&json)?;
+            save(&s)?;
+        }
+        if s.user_pubkey != Some(user) {
+            s.tasks.clear();
+        }
+        s.user_pubkey = Some(user);
+        reschedule(&mut s);
+        let running = s.handle.as_ref().is_some_and(|h| !h.is_finished());
+        if !running {
+            s.handle = Some(tokio::spawn(run_loop()));
+        }
+    }
+    wake().notify_one();
+    emit_status().await;
+    Ok(())
+}
+
+pub async fn stop_sync_scheduler() -> Result<()> {
+    {
+        let mut s = scheduler().lock().await;
+        if let Some(handle) = s.handle.take() {
+            handle.abort();
+        }
+        for state in s.tasks.values_mut() {
+            state.running = false;
+            state.next_run = None;
+        }
+    }
+    emit_status().await;
+    Ok(())
+}
+
+/// Switch between `normal`, `batterySaver`, `dataSaver` and `paused`. Kept
+/// next to the database like the rest of the config.
+pub async fn set_sync_profile(profile: String) -> Result<()> {
+    let profile: SyncProfile = serde_json::from_value(serde_json::Value::String(profile.clone()))
+        .map_err(|_| anyhow!("Unknown sync profile: {}", profile))?;
+    {
+        let mut s = scheduler().lock().await;
+        s.config.profile = profile;
+        save(&s)?;
+        reschedule(&mut s);
+    }
+    wake().notify_one();
+    emit_status().await;
+    Ok(())
+}
+
+/// Update some task intervals and/or the profile; anything not mentioned
+/// keeps its current value. The config is kept next to the database.
+pub async fn set_sync_config(config_json: String) -> Result<()> {
+    {
+        let mut s = scheduler().lock().await;
+        s.config = s.config.updated(&config_json)?;
+        save(&s)?;
+        reschedule(&mut s);
+    }
+    wake().notify_one();
+    emit_status().await;
+    Ok(())
+}
+
+/// Run one task (or every enabled task) as soon as the scheduler is free.
+pub async fn trigger_sync(task: Option<String>) -> Result<()> {
+    let tasks = match task {
+        Some(task) => vec![SyncTask::parse(&task)?],
+        None => TASKS.to_vec(),
+    };
+    {
+        let mut s = scheduler().lock().await;
+        let now = Instant::now();
+        for task in tasks {
+            let enabled = s.config.interval(task).is_some();
+            let state = s.tasks.entry(task).or_default();
+            if enabled && !state.running {
+                state.next_run = Some(now);
+            }
+        }
+    }
+    wake().notify_one();
+    emit_status().await;
+    Ok(())
+}
+
+pub async fn get_sync_status() -> Result<String> {
+    Ok(status_json(&*scheduler().lock().await).to_string())
+}
+
+/// Receive the scheduler status on every change. Calling it again replaces
+/// the previous sink.
+#[frb]
+pub async fn stream_sync_status(sink: StreamSink<String>) -> Result<()> {
+    let json = status_json(&*scheduler().lock().await).to_string();
+    let _ = sink.add(json);
+    let mut lock = status_sink_state().write().await;
+    *lock = Some(sink);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SchedulerConfig, SyncProfile, SyncTask};
+    use std::time::Duration;
+
+    #[test]
+    fn profiles_stretch_and_skip_tasks() {
+        let config = SchedulerConfig::default()
+            .updated(r#"{"intervals": {"feed": 60, "dms": 0}}"#)
+            .unwrap();
+        assert_eq!(
+            config.interval(SyncTask::Feed),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            config.interval(SyncTask::Notifications),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(config.interval(SyncTask::Dms), None);
+
+        let mut config = config.updated(r#"{"profile": "batterySaver"}"#).unwrap();
+        assert_eq!(config.profile, SyncProfile::BatterySaver);
+        assert_eq!(
+            config.interval(SyncTask::Feed),
+            Some(Duration::from_secs(240))
+        );
+        assert_eq!(config.interval(SyncTask::Metadata), None);
+
+        config.profile = SyncProfile::Paused;
+        assert_eq!(config.interval(SyncTask::Feed), None);
+    }
+}
@@ -0,0 +1,392 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use nostr_sdk::prelude::*;
+use sha2::{Digest, Sha256};
+
+use crate::api::operations::Operation;
+use crate::newest_first::NewestFirst;
+
+const ARCHIVE_VERSION: u32 = 1;
+const EXPORT_PAGE: usize = 1_000;
+const IMPORT_BATCH: usize = 500;
+
+/// Kinds describing the account rather than its content; summarised in the
+/// manifest so an archive can be inspected without reading every line.
+const LIST_KINDS: [u16; 9] = [0, 3, 10000, 10001, 10002, 10003, 10050, 30000, 30003];
+
+/// `path` with `suffix` appended to its full file name.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+pub(crate) fn manifest_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".manifest.json")
+}
+
+/// Relay list and list events of `user`, from the local database.
+async fn account_manifest(client: &Client, user: PublicKey) -> serde_json::Value {
+    let database = client.database();
+    let kinds: Vec<Kind> = LIST_KINDS.iter().map(|k| Kind::from(*k)).collect();
+    let events = database
+        .query(Filter::new().author(user).kinds(kinds))
+        .await
+        .map(|events| events.to_vec())
+        .unwrap_or_default();
+
+    let mut relays = Vec::new();
+    let mut lists = Vec::new();
+    for event in &events {
+        if event.kind == Kind::RelayList {
+            for (url, metadata) in nip65::extract_relay_list(event) {
+                relays.push(serde_json::json!({
+                    "url": url.to_string(),
+                    "read": metadata.is_none_or(|m| m == RelayMetadata::Read),
+                    "write": metadata.is_none_or(|m| m == RelayMetadata::Write),
+                }));
+            }
+        }
+        lists.push(serde_json::json!({
+            "kind": event.kind.as_u16(),
+            "id": event.id.to_hex(),
+            "identifier": event.tags.identifier(),
+            "createdAt": event.created_at.as_secs(),
+            "entries": event.tags.len(),
+        }));
+    }
+    serde_json::json!({
+        "pubkey": user.to_hex(),
+        "relays": relays,
+        "lists": lists,
+    })
+}
+
+/// Write every stored event matching `filter` to `path`, one JSON event per
+/// line, newest first, plus a manifest next to it. The archive is written to
+/// a temporary file and only moved into place once complete.
+pub(crate) async fn export(
+    client: &Client,
+    path: &Path,
+    filter: Filter,
+    user: Option<PublicKey>,
+    op: &Operation,
+    on_progress: impl Fn(u64, u64),
+) -> Result<serde_json::Value> {
+    let database = client.database();
+    let cap = filter.limit;
+    let total = database.count(filter.clone()).await? as u64;
+    let total = cap.map_or(total, |cap| total.min(cap as u64));
+    op.progress(0, Some(total), "export");
+    on_progress(0, total);
+
+    let tmp_path = with_suffix(path, ".partial");
+    let mut writer = BufWriter::new(File::create(&tmp_path)?);
+    let mut hasher = Sha256::new();
+    let mut exported: u64 = 0;
+    let mut pages = NewestFirst::new(filter.clone(), EXPORT_PAGE, filter.until);
+
+    while !op.is_stopped() && cap.is_none_or(|cap| exported < cap as u64) {
+        let Some(events) = pages.next_page(client).await? else {
+            break;
+        };
+        for event in events {
+            if cap.is_some_and(|cap| exported >= cap as u64) {
+                break;
+            }
+            let mut line = event.as_json();
+            line.push('\n');
+            hasher.update(line.as_bytes());
+            writer.write_all(line.as_bytes())?;
+            exported += 1;
+        }
+        op.progress(exported, Some(total), "export");
+        on_progress(exported, total);
+    }
+    writer.flush()?;
+    drop(writer);
+
+    if op.is_stopped() {
+        let _ = std::fs::remove_file(&tmp_path);
+        anyhow::bail!("export {}", op.outcome());
+    }
+
+    let account = match user {
+        Some(user) => Some(account_manifest(client, user).await),
+        None => None,
+    };
+    let manifest = serde_json::json!({
+        "version": ARCHIVE_VERSION,
+        "exportedAt": Timestamp::now().as_secs(),
+        "filter": filter.as_json(),
+        "events": exported,
+        "sha256": hex::encode(hasher.finalize()),
+        "account": account,
+    });
+    std::fs::rename(&tmp_path, path)?;
+    let manifest_path = manifest_path(path);
+    std::fs::write(&manifest_path, serde_json::to_vec_pretty(&manifest)?)?;
+
+    Ok(serde_json::json!({
+        "path": path.to_string_lossy(),
+        "manifestPath": manifest_path.to_string_lossy(),
+        "exported": exported,
+        "manifest": manifest,
+    }))
+}
+
+/// Counters for an archive import, reported with every progress update.
+#[derive(serde::Serialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ImportStats {
+    /// Non-empty lines read so far.
+    pub(crate) read: u64,
+    pub(crate) imported: u64,
+    /// Already in the database (or previously deleted there).
+    pub(crate) duplicates: u64,
+    /// Not a parseable event.
+    pub(crate) invalid: u64,
+    pub(crate) bad_signatures: u64,
+    /// Valid but refused by the database, e.g. superseded replaceable events.
+    pub(crate) rejected: u64,
+    /// Whether the file matches the manifest checksum; `None` without one.
+    pub(crate) checksum_ok: Option<bool>,
+}
+
+async fn save_batch(
+    database: &dyn NostrDatabase,
+    batch: &mut Vec<Event>,
+    stats: &mut ImportStats,
+    own: &mut Vec<Event>,
+    owner: Option<PublicKey>,
+) -> Result<()> {
+    for event in batch.drain(..) {
+        if owner == Some(event.pubkey) {
+            own.push(event.clone());
+        }
+        if database.check_id(&event.id).await? != DatabaseEventStatus::NotExistent {
+            stats.duplicates += 1;
+            continue;
+        }
+        match database.save_event(&event).await? {
+            SaveEventStatus::Success => stats.imported += 1,
+            SaveEventStatus::Rejected(_) => stats.rejected += 1,
+        }
+    }
+    Ok(())
+}
+
+/// Read a JSONL archive written by [`export`] (or any file with one event
+/// per line), verify each signature and save the events not yet stored, in
+/// batches. Returns the counters plus, when `owner` is given, every valid
+/// event of that user in the archive, stored already or not, for
+/// rebroadcasting.
+pub(crate) async fn import(
+    client: &Client,
+    path: &Path,
+    owner: Option<PublicKey>,
+    op: &Operation,
+    on_progress: impl Fn(&ImportStats),
+) -> Result<(ImportStats, Vec<Event>)> {
+    let expected_sha256: Option<String> = std::fs::read(manifest_path(path))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok())
+        .and_then(|manifest| manifest["sha256"].as_str().map(str::to_string));
+    let total = std::fs::metadata(path)?.len();
+    let mut reader = BufReader::new(File::open(path)?);
+    let database = client.database();
+
+    let mut stats = ImportStats::default();
+    let mut hasher = Sha256::new();
+    let mut bytes_read: u64 = 0;
+    let mut batch: Vec<Event> = Vec::with_capacity(IMPORT_BATCH);
+    let mut own: Vec<Event> = Vec::new();
+    let mut raw: Vec<u8> = Vec::new();
+
+    loop {
+        if op.is_stopped() {
+            anyhow::bail!("import {}", op.outcome());
+        }
+        raw.clear();
+        if reader.read_until(b'\n', &mut raw)? == 0 {
+            break;
+        }
+        // The checksum covers the file exactly as written by `export`, so
+        // line endings aren't normalised before hashing.
+        hasher.update(&raw);
+        bytes_read += raw.len() as u64;
+        let Ok(line) = std::str::from_utf8(&raw) else {
+            stats.read += 1;
+            stats.invalid += 1;
+            continue;
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        stats.read += 1;
+        let Ok(event) = Event::from_json(line) else {
+            stats.invalid += 1;
+            continue;
+        };
+        if event.verify().is_err() {
+            stats.bad_signatures += 1;
+            continue;
+        }
+        batch.push(event);
+        if batch.len() >= IMPORT_BATCH {
+            save_batch(database.as_ref(), &mut batch, &mut stats, &mut own, owner).await?;
+            op.progress(bytes_read, Some(total), "import");
+            on_progress(&stats);
+        }
+    }
+    save_batch(database.as_ref(), &mut batch, &mut stats, &mut own, owner).await?;
+
+    stats.checksum_ok = expected_sha256.map(|expected| expected == hex::encode(hasher.finalize()));
+    op.progress(total, Some(total), "import");
+    on_progress(&stats);
+    Ok((stats, own))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn export_pages_through_events_sharing_a_timestamp() {
+        let database = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            ..Default::default()
+        });
+        let client = Client::builder().database(database).build();
+        let keys = Keys::generate();
+        let mut ids = HashSet::new();
+        for i in 0..(EXPORT_PAGE + 5) {
+            // Half the events share one timestamp to straddle a page boundary.
+            let at = if i % 2 == 0 { 1_000 } else { 1_000 + i as u64 };
+            let event = EventBuilder::text_note(format!("note {}", i))
+                .custom_created_at(Timestamp::from(at))
+                .sign_with_keys(&keys)
+                .unwrap();
+            ids.insert(event.id);
+            client.database().save_event(&event).await.unwrap();
+        }
+
+        let dir = std::env::temp_dir().join(format!("archive-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("export.jsonl");
+        let op = Operation::start("test", None, None);
+        let result = export(
+            &client,
+            &path,
+            Filter::new(),
+            Some(keys.public_key()),
+            &op,
+            |_, _| {},
+        )
+        .await
+        .unwrap();
+
+        let exported: HashSet<EventId> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| Event::from_json(line).unwrap().id)
+            .collect();
+        assert_eq!(exported, ids);
+        assert_eq!(result["exported"], ids.len() as u64);
+        assert!(manifest_path(&path).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn import_skips_forged_and_known_events() {
+        let memory = || {
+            MemoryDatabase::with_opts(MemoryDatabaseOptions {
+                events: true,
+                ..Default::default()
+            })
+        };
+        let client = Client::builder().database(memory()).build();
+        let keys = Keys::generate();
+        let notes: Vec<Event> = (0..3)
+            .map(|i| {
+                EventBuilder::text_note(format!("note {}", i))
+                    .sign_with_keys(&keys)
+                    .unwrap()
+            })
+            .collect();
+        client.database().save_event(&notes[0]).await.unwrap();
+
+        let mut forged: serde_json::Value = serde_json::from_str(&notes[2].as_json()).unwrap();
+        forged["content"] = "forged".into();
+        let lines = [
+            notes[0].as_json(),
+            notes[1].as_json(),
+            forged.to_string(),
+            "{}".to_string(),
+        ];
+
+        let dir = std::env::temp_dir().join(format!("archive-import-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("import.jsonl");
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        let op = Operation::start("test", None, None);
+        let (stats, own) = import(&client, &path, Some(keys.public_key()), &op, |_| {})
+            .await
+            .unwrap();
+
+        assert_eq!(stats.read, 4);
+        assert_eq!(stats.imported, 1);
+        assert_eq!(stats.duplicates, 1);
+        assert_eq!(stats.bad_signatures, 1);
+        assert_eq!(stats.invalid, 1);
+        assert_eq!(stats.checksum_ok, None);
+        assert_eq!(own.len(), 2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn import_checksum_covers_the_file_as_written() {
+        let memory = || {
+            MemoryDatabase::with_opts(MemoryDatabaseOptions {
+                events: true,
+                ..Default::default()
+            })
+        };
+        let client = Client::builder().database(memory()).build();
+        let keys = Keys::generate();
+        for i in 0..2 {
+            let event = EventBuilder::text_note(format!("note {}", i))
+                .sign_with_keys(&keys)
+                .unwrap();
+            client.database().save_event(&event).await.unwrap();
+        }
+
+        let dir = std::env::temp_dir().join(format!("archive-checksum-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("export.jsonl");
+        let op = Operation::start("test", None, None);
+        export(&client, &path, Filter::new(), None, &op, |_, _| {})
+            .await
+            .unwrap();
+
+        let fresh = Client::builder().database(memory()).build();
+        let (stats, _) = import(&fresh, &path, None, &op, |_| {}).await.unwrap();
+        assert_eq!(stats.checksum_ok, Some(true));
+
+        let crlf = std::fs::read_to_string(&path)
+            .unwrap()
+            .replace('\n', "\r\n");
+        std::fs::write(&path, crlf).unwrap();
+        let (stats, _) = import(&fresh, &path, None, &op, |_| {}).await.unwrap();
+        assert_eq!(stats.checksum_ok, Some(false));
+        assert_eq!(stats.read, 2);
+        assert_eq!(stats.duplicates, 2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock, RwLock};
+
+use anyhow::Result;
+use nostr_sdk::prelude::*;
+
+use crate::newest_first::NewestFirst;
+
+const BUILD_PAGE: usize = 500;
+/// Follows of the viewer following someone at which their trust reaches
+/// half of a direct follow's.
+const TRUST_HALF_AT: f64 = 3.0;
+
+/// Answers about one pubkey relative to the viewer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Trust {
+    /// Follow hops from the viewer: 0 for the viewer, 1 for their follows,
+    /// 2 for follows of follows; `None` if further or unknown.
+    pub(crate) distance: Option<u8>,
+    /// How many of the viewer's follows follow this pubkey.
+    pub(crate) followed_by_follows: u32,
+    /// 1.0 for the viewer and their follows, approaching 1.0 with the
+    /// number of follows following a 2-hop pubkey, 0.0 otherwise.
+    pub(crate) trust: f64,
+}
+
+/// The viewer's follows and, for everyone they follow, how many of those
+/// follows follow them. Kept up to date as contact lists change.
+struct Root {
+    node: u32,
+    follows: HashSet<u32>,
+    followed_by_follows: HashMap<u32, u32>,
+}
+
+/// Who follows whom, from the newest stored contact list of each author.
+#[derive(Default)]
+pub(crate) struct FollowGraph {
+    ids: HashMap<PublicKey, u32>,
+    keys: Vec<PublicKey>,
+    /// Sorted out-edges per node.
+    following: Vec<Vec<u32>>,
+    followers: Vec<Vec<u32>>,
+    list_at: Vec<Option<Timestamp>>,
+    root: Option<Root>,
+}
+
+impl FollowGraph {
+    fn node(&mut self, pubkey: PublicKey) -> u32 {
+        if let Some(&node) = self.ids.get(&pubkey) {
+            return node;
+        }
+        let node = self.keys.len() as u32;
+        self.ids.insert(pubkey, node);
+        self.keys.push(pubkey);
+        self.following.push(Vec::new());
+        self.followers.push(Vec::new());
+        self.list_at.push(None);
+        node
+    }
+
+    /// Apply a contact list unless a newer one of its author was applied.
+    /// Returns whether the graph changed.
+    fn apply(&mut self, event: &Event) -> bool {
+        if event.kind != Kind::ContactList {
+            return false;
+        }
+        let author = self.node(event.pubkey);
+        if self.list_at[author as usize].is_some_and(|at| at >= event.created_at) {
+            return false;
+        }
+        self.list_at[author as usize] = Some(event.created_at);
+
+        let mut follows: Vec<u32> = event
+            .tags
+            .public_keys()
+            .map(|pubkey| self.node(*pubkey))
+            .filter(|&node| node != author)
+            .collect();
+        follows.sort_unstable();
+        follows.dedup();
+        let previous = std::mem::replace(&mut self.following[author as usize], follows);
+        let current = &self.following[author as usize];
+        let removed: Vec<u32> = previous
+            .iter()
+            .filter(|n| current.binary_search(n).is_err())
+            .copied()
+            .collect();
+        let added: Vec<u32> = current
+            .iter()
+            .filter(|n| previous.binary_search(n).is_err())
+            .copied()
+            .collect();
+
+        for &target in &removed {
+            let followers = &mut self.followers[target as usize];
+            if let Some(i) = followers.iter().position(|&n| n == author) {
+                followers.swap_remove(i);
+            }
+        }
+        for &target in &added {
+            self.followers[target as usize].push(author);
+        }
+
+        match self.root.as_mut() {
+            Some(root) if root.node == author => {
+                let viewer = self.keys[author as usize];
+                self.set_root(viewer);
+            }
+            Some(root) if root.follows.contains(&author) => {
+                for target in removed {
+                    if let Some(count) = root.followed_by_follows.get_mut(&target) {
+                        *count -= 1;
+                        if *count == 0 {
+                            root.followed_by_follows.remove(&target);
+                        }
+                    }
+                }
+                for target in added {
+                    *root.followed_by_follows.entry(target).or_default() += 1;
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// Answer questions relative to `viewer` from now on.
+    fn set_root(&mut self, viewer: PublicKey) {
+        let node = self.node(viewer);
+        let follows: HashSet<u32> = self.following[node as usize].iter().copied().collect();
+        let mut followed_by_follows: HashMap<u32, u32> = HashMap::new();
+        for follow in &follows {
+            for target in &self.following[*follow as usize] {
+                *followed_by_follows.entry(*target).or_default() += 1;
+            }
+        }
+        self.root = Some(Root {
+            node,
+            follows,
+            followed_by_follows,
+        });
+    }
+
+    fn ensure_root(&mut self, viewer: PublicKey) {
+        if self.root_key() != Some(viewer) {
+            self.set_root(viewer);
+        }
+    }
+
+    fn root_key(&self) -> Option<PublicKey> {
+        self.root.as_ref().map(|root| self.keys[root.node as usize])
+    }
+
+    fn trust(&self, pubkey: &PublicKey) -> Trust {
+        let unknown = Trust {
+            distance: None,
+            followed_by_follows: 0,
+            trust: 0.0,
+        };
+        let (Some(root), Some(&node)) = (self.root.as_ref(), self.ids.get(pubkey)) else {
+            return unknown;
+        };
+        let followed_by_follows = root.followed_by_follows.get(&node).copied().unwrap_or(0);
+        let distance = if node == root.node {
+            Some(0)
+        } else if root.follows.contains(&node) {
+            Some(1)
+        } else if followed_by_follows > 0 {
+            Some(2)
+        } else {
+            None
+        };
+        let trust = match distance {
+            Some(0 | 1) => 1.0,
+            Some(_) => {
+                let n = followed_by_follows as f64;
+                n / (n + TRUST_HALF_AT)
+            }
+            None => 0.0,
+        };
+        Trust {
+            distance,
+            followed_by_follows,
+            trust,
+        }
+    }
+
+    /// The viewer's follows who follow `pubkey`.
+    fn follows_following(&self, pubkey: &PublicKey) -> Vec<PublicKey> {
+        let (Some(root), Some(&node)) = (self.root.as_ref(), self.ids.get(pubkey)) else {
+            return Vec::new();
+        };
+        self.followers[node as usize]
+            .iter()
+            .filter(|n| root.follows.contains(n))
+            .map(|n| self.keys[*n as usize])
+            .collect()
+    }
+}
+
+static GRAPH: OnceLock<RwLock<FollowGraph>> = OnceLock::new();
+static BUILT: AtomicBool = AtomicBool::new(false);
+static BUILD_TASK: OnceLock<Mutex<Option<tokio::task::JoinHandle<()>>>> = OnceLock::new();
+
+fn graph() -> &'static RwLock<FollowGraph> {
+    GRAPH.get_or_init(|| RwLock::new(FollowGraph::default()))
+}
+
+/// Apply a newly saved contact list.
+pub(crate) fn index_event(event: &Event) {
+    if event.kind == Kind::ContactList {
+        graph().write().unwrap().apply(event);
+    }
+}
+
+/// Run `f` on the graph with `viewer` as its root.
+fn with_root<T>(viewer: PublicKey, f: impl FnOnce(&FollowGraph) -> T) -> T {
+    {
+        let graph = graph().read().unwrap();
+        if graph.root_key() == Some(viewer) {
+            return f(&graph);
+        }
+    }
+    let mut graph = graph().write().unwrap();
+    graph.ensure_root(viewer);
+    f(&graph)
+}
+
+/// Trust of each of `pubkeys` as seen from `viewer`.
+pub(crate) fn trust(viewer: PublicKey, pubkeys: &[PublicKey]) -> Vec<Trust> {
+    with_root(viewer, |graph| {
+        pubkeys.iter().map(|pk| graph.trust(pk)).collect()
+    })
+}
+
+/// Whether every stored contact list has been read into the graph. Until
+/// then follow distances may be missing.
+pub(crate) fn is_built() -> bool {
+    BUILT.load(Ordering::Relaxed)
+}
+
+/// How many pubkeys `viewer` follows according to their stored contact list.
+pub(crate) fn follow_count(viewer: PublicKey) -> usize {
+    with_root(viewer, |graph| {
+        graph.root.as_ref().map_or(0, |root| root.follows.len())
+    })
+}
+
+/// The follows of `viewer` who follow `pubkey`.
+pub(crate) fn follows_following(viewer: PublicKey, pubkey: &PublicKey) -> Vec<PublicKey> {
+    with_root(viewer, |graph| graph.follows_following(pubkey))
+}
+
+pub(crate) fn stats_json() -> serde_json::Value {
+    let graph = graph().read().unwrap();
+    serde_json::json!({
+        "pubkeys": graph.keys.len(),
+        "contactLists": graph.list_at.iter().filter(|at| at.is_some()).count(),
+        "follows": graph.following.iter().map(Vec::len).sum::<usize>(),
+        "built": BUILT.load(Ordering::Relaxed),
+    })
+}
+
+async fn build(client: &Client) -> Result<()> {
+    let filter = Filter::new().kind(Kind::ContactList);
+    let mut pages = NewestFirst::new(filter, BUILD_PAGE, None);
+    while let Some(events) = pages.next_page(client).await? {
+        let mut graph = graph().write().unwrap();
+        for event in &events {
+            graph.apply(event);
+        }
+    }
+    Ok(())
+}
+
+/// Build the graph from stored contact lists in the background.
+pub(crate) fn start(client: &Client) {
+    *graph().write().unwrap() = FollowGraph::default();
+    BUILT.store(false, Ordering::Relaxed);
+    let client = client.clone();
+    let handle = tokio::spawn(async move {
+        if build(&client).await.is_ok() {
+            BUILT.store(true, Ordering::Relaxed);
+        }
+    });
+    let task = BUILD_TASK.get_or_init(|| Mutex::new(None));
+    if let Some(previous) = task.lock().unwrap().replace(handle) {
+        previous.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contacts(keys: &Keys, follows: &[PublicKey], at: u64) -> Event {
+        EventBuilder::new(Kind::ContactList, "")
+            .tags(follows.iter().map(|pk| Tag::public_key(*pk)))
+            .custom_created_at(Timestamp::from(at))
+            .sign_with_keys(keys)
+            .unwrap()
+    }
+
+    #[test]
+    fn two_hop_counts_follow_contact_list_updates() {
+        let [me, alice, bob, carol, dave] = std::array::from_fn::<Keys, 5, _>(|_| Keys::generate());
+        let mut graph = FollowGraph::default();
+        graph.apply(&contacts(&me, &[alice.public_key(), bob.public_key()], 10));
+        graph.apply(&contacts(&alice, &[carol.public_key()], 10));
+        graph.set_root(me.public_key());
+
+        assert_eq!(graph.trust(&me.public_key()).distance, Some(0));
+        assert_eq!(graph.trust(&alice.public_key()).distance, Some(1));
+        assert_eq!(graph.trust(&carol.public_key()).followed_by_follows, 1);
+        assert_eq!(graph.trust(&dave.public_key()).distance, None);
+
+        // Updates to a follow's list adjust the counts incrementally.
+        graph.apply(&contacts(
+            &bob,
+            &[carol.public_key(), dave.public_key()],
+            10,
+        ));
+        let carol_trust = graph.trust(&carol.public_key());
+        assert_eq!(
+            (carol_trust.distance, carol_trust.followed_by_follows),
+            (Some(2), 2)
+        );
+        assert!(carol_trust.trust > graph.trust(&dave.public_key()).trust);
+        assert_eq!(graph.follows_following(&carol.public_key()).len(), 2);
+
+        // Older lists are ignored; a newer one replaces the old edges.
+        assert!(!graph.apply(&contacts(&alice, &[dave.public_key()], 5)));
+        graph.apply(&contacts(&alice, &[], 20));
+        assert_eq!(graph.trust(&carol.public_key()).followed_by_follows, 1);
+
+        // The viewer unfollowing someone moves them out of the first hop.
+        graph.apply(&contacts(&me, &[bob.public_key()], 20));
+        assert_eq!(graph.trust(&alice.public_key()).distance, None);
+        assert_eq!(graph.trust(&dave.public_key()).distance, Some(2));
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use futures::{SinkExt, StreamExt};
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+use tokio_tungstenite::tungstenite::Message;
+
+const PROVIDERS_FILE: &str = "follower_counts.json";
+const COUNT_TIMEOUT: Duration = Duration::from_secs(5);
+const COUNT_RELAYS: usize = 5;
+const COUNT_CONCURRENCY: usize = 16;
+const PRIMAL_URL: &str = "wss://cache2.primal.net/v1";
+const PRIMAL_FOLLOWER_COUNTS_KIND: u64 = 10000133;
+
+/// Where follower counts come from. Providers are tried in the configured
+/// order; a pubkey is answered by the first provider that has a count for it.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum FollowerCountProvider {
+    /// NIP-45 COUNT of kind-3 events tagging the pubkey, on the user's relays.
+    Nip45,
+    /// Kind-3 events tagging the pubkey in the local database.
+    Local,
+    /// Primal's caching service. Sends the looked-up pubkeys to a third
+    /// party, so it is only used when explicitly enabled.
+    Primal,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+struct ProviderConfig {
+    providers: Vec<FollowerCountProvider>,
+}
+
+impl Default for ProviderConfig {
+    fn default() -> Self {
+        Self {
+            providers: vec![FollowerCountProvider::Nip45, FollowerCountProvider::Local],
+        }
+    }
+}
+
+#[derive(Default)]
+struct ProviderState {
+    path: Option<PathBuf>,
+    config: ProviderConfig,
+}
+
+static PROVIDERS: OnceLock<RwLock<ProviderState>> = OnceLock::new();
+
+fn provider_state() -> &'static RwLock<ProviderState> {
+    PROVIDERS.get_or_init(|| RwLock::new(ProviderState::default()))
+}
+
+/// Load the persisted provider order stored next to the database, if any.
+pub(crate) fn load(db_path: Option<&str>) {
+    let path = db_path.map(|p| {
+        PathBuf::from(p)
+            .parent()
+            .unwrap_or_else(|| std::path::Path::new("."))
+            .join(PROVIDERS_FILE)
+    });
+    let config = path
+        .as_ref()
+        .and_then(|p| std::fs::read(p).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+    let mut state = provider_state().write().unwrap();
+    state.path = path;
+    state.config = config;
+}
+
+pub(crate) fn providers() -> Vec<FollowerCountProvider> {
+    provider_state().read().unwrap().config.providers.clone()
+}
+
+/// Replace and persist the provider order. Names are `nip45`, `local` and
+/// `primal`; duplicates are dropped.
+pub(crate) fn set_providers(names: &[String]) -> Result<()> {
+    let mut providers: Vec<FollowerCountProvider> = Vec::new();
+    for name in names {
+        let provider: FollowerCountProvider =
+            serde_json::from_value(serde_json::Value::String(name.trim().to_lowercase()))
+                .map_err(|_| anyhow!("Unknown follower count provider: {}", name))?;
+        if !providers.contains(&provider) {
+            providers.push(provider);
+        }
+    }
+    let config = ProviderConfig { providers };
+    let mut state = provider_state().write().unwrap();
+    if let Some(path) = state.path.as_ref() {
+        std::fs::write(path, serde_json::to_vec_pretty(&config)?)?;
+    }
+    state.config = config;
+    Ok(())
+}
+
+fn followers_filter(pk: PublicKey) -> Filter {
+    Filter::new().kind(Kind::ContactList).pubkey(pk)
+}
+
+/// Highest COUNT any of the best connected read relays reports. Relays
+/// overlap, so the maximum is the closest lower bound; `None` if no relay
+/// answered (most likely because none support NIP-45).
+async fn nip45_count(relays: &[Relay], pk: PublicKey) -> Option<u64> {
+    let counts = futures::future::join_all(
+        relays
+            .iter()
+            .map(|relay| relay.count_events(followers_filter(pk), COUNT_TIMEOUT)),
+    )
+    .await;
+    counts
+        .into_iter()
+        .filter_map(|c| c.ok())
+        .max()
+        .map(|c| c as u64)
+}
+
+async fn nip45_counts(client: &Client, pubkeys: &[PublicKey]) -> HashMap<PublicKey, u64> {
+    let all = client.relays().await;
+    let ranked = crate::relay_scores::rank(
+        all.iter()
+            .filter(|(_, r)| {
+                r.flags().has(RelayServiceFlags::READ, FlagCheck::All)
+                    && r.status() == RelayStatus::Connected
+            })
+            .map(|(u, _)| u.clone())
+            .collect(),
+        COUNT_RELAYS,
+    );
+    let relays: Vec<Relay> = ranked.iter().filter_map(|u| all.get(u).cloned()).collect();
+    drop(all);
+    if relays.is_empty() {
+        return HashMap::new();
+    }
+
+    let relays = &relays;
+    futures::stream::iter(pubkeys.iter().copied())
+        .map(|pk| async move { nip45_count(relays, pk).await.map(|c| (pk, c)) })
+        .buffer_unordered(COUNT_CONCURRENCY)
+        .filter_map(|r| async move { r })
+        .collect()
+        .await
+}
+
+/// Counts from stored contact lists. Pubkeys no stored list follows are
+/// left out, so the next provider is asked about them.
+pub(crate) async fn local_counts(client: &Client, pubkeys: &[PublicKey]) -> HashMap<PublicKey, u64> {
+    let database = client.database();
+    let mut counts = HashMap::new();
+    for pk in pubkeys {
+        match database.count(followers_filter(*pk)).await {
+            Ok(0) | Err(_) => {}
+            Ok(count) => {
+                counts.insert(*pk, count as u64);
+            }
+        }
+    }
+    counts
+}
+
+async fn primal_counts(pubkeys: &[PublicKey]) -> HashMap<PublicKey, u64> {
+    let mut counts = HashMap::new();
+    let hexes: Vec<String> = pubkeys.iter().map(|pk| pk.to_hex()).collect();
+    let request = serde_json::json!([
+        "REQ",
+        SubscriptionId::generate().to_string(),
+        { "cache": ["user_infos", { "pubkeys": hexes }] }
+    ]);
+
+    let Ok(Ok(ws)) =
+        tokio::time::timeout(COUNT_TIMEOUT, crate::proxy::connect_websocket(PRIMAL_URL)).await
+    else {
+        return counts;
+    };
+    let (mut write, mut read) = ws.split();
+    if write.send(Message::Text(request.to_string())).await.is_err() {
+        return counts;
+    }
+
+    let _ = tokio::time::timeout(COUNT_TIMEOUT, async {
+        while let Some(Ok(message)) = read.next().await {
+            let Message::Text(text) = message else {
+                if matches!(message, Message::Close(_)) {
+                    break;
+                }
+                continue;
+            };
+            let Ok(serde_json::Value::Array(arr)) = serde_json::from_str(&text) else {
+                continue;
+            };
+            match arr.first().and_then(|t| t.as_str()) {
+                Some("EOSE") => break,
+                Some("EVENT") if arr.len() >= 3 => {
+                    let event = &arr[2];
+                    if event["kind"].as_u64() != Some(PRIMAL_FOLLOWER_COUNTS_KIND) {
+                        continue;
+                    }
+                    let content: HashMap<String, serde_json::Value> =
+                        serde_json::from_str(event["content"].as_str().unwrap_or("{}"))
+                            .unwrap_or_default();
+                    for (hex, value) in content {
+                        if let (Ok(pk), Some(count)) = (PublicKey::from_hex(&hex), value.as_u64()) {
+                            counts.insert(pk, count);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    })
+    .await;
+    counts
+}
+
+/// Follower count for each pubkey, asking the configured providers in order
+/// and only for the pubkeys still unanswered.
+pub(crate) async fn fetch(client: &Client, pubkeys: Vec<PublicKey>) -> HashMap<PublicKey, u64> {
+    let mut counts: HashMap<PublicKey, u64> = HashMap::new();
+    for provider in providers() {
+        let missing: Vec<PublicKey> = pubkeys
+            .iter()
+            .filter(|pk| !counts.contains_key(*pk))
+            .copied()
+            .collect();
+        if missing.is_empty() {
+            break;
+        }
+        let found = match provider {
+            FollowerCountProvider::Nip45 => nip45_counts(client, &missing).await,
+            FollowerCountProvider::Local => local_counts(client, &missing).await,
+            FollowerCountProvider::Primal => primal_counts(&missing).await,
+        };
+        counts.extend(found);
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn local_provider_counts_stored_contact_lists() {
+        let database = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            ..Default::default()
+        });
+        let client = Client::builder().database(database).build();
+        let target = Keys::generate().public_key();
+        let other = Keys::generate().public_key();
+
+        for follows in [vec![target], vec![target, other], vec![other]] {
+            let event = EventBuilder::new(Kind::ContactList, "")
+                .tags(follows.into_iter().map(Tag::public_key))
+                .sign_with_keys(&Keys::generate())
+                .unwrap();
+            client.database().save_event(&event).await.unwrap();
+        }
+
+        let unknown = Keys::generate().public_key();
+        let counts = local_counts(&client, &[target, other, unknown]).await;
+        assert_eq!(counts[&target], 2);
+        assert_eq!(counts[&other], 2);
+        assert!(!counts.contains_key(&unknown));
+        assert_eq!(providers(), ProviderConfig::default().providers);
+    }
+}
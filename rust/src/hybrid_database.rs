@@ -1,33 +1,36 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::time::Duration;
 
+use heed::{EnvClosingEvent, EnvFlags, EnvOpenOptions};
 use nostr_database::{
     Backend, DatabaseError, DatabaseEventStatus, Events, MemoryDatabase, MemoryDatabaseOptions,
-    NostrDatabase, SaveEventStatus,
+    NostrDatabase, RejectedReason, SaveEventStatus,
 };
 pub use nostr_lmdb::NostrLMDB;
+use nostr_lmdb::NostrLmdbBuilder;
 use nostr_sdk::prelude::*;
+use tokio::sync::{mpsc, RwLock};
+
+/// Persistent writes between checks of how full the LMDB map is.
+const CAPACITY_CHECK_INTERVAL: u32 = 256;
+/// Grow the map once the data file uses this share of it.
+const GROW_THRESHOLD: f64 = 0.8;
+const REOPEN_ATTEMPTS: u32 = 20;
+const ENV_CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Databases nostr-lmdb creates itself; its env has room for these plus
+/// the builder's `additional_dbs`.
+const LMDB_BASE_DBS: u32 = 11;
+const LMDB_ADDITIONAL_DBS: u32 = 0;
+const LMDB_MAX_READERS: u32 = 126;
+/// Reads after which a memory-tier event is copied into LMDB, so it
+/// survives both eviction and restarts.
+const PROMOTE_AFTER_HITS: u32 = 4;
 
 fn is_persistent_kind(kind: Kind) -> bool {
-    matches!(
-        kind,
-        Kind::Metadata
-            | Kind::ContactList
-            | Kind::EventDeletion
-            | Kind::MuteList
-            | Kind::RelayList
-            | Kind::TextNote
-            | Kind::Repost
-    ) || matches!(
-        kind.as_u16(),
-        7        // Reaction
-            | 1059   // Gift wrap (NIP-17 DM)
-            | 9735   // Zap receipt
-            | 10001  // Pinned notes
-            | 10003  // Bookmarks
-            | 30000  // Follow sets / categorized people
-            | 30001  // Categorized lists (legacy bookmarks)
-            | 30023 // Long-form article
-    )
+    crate::persistence_policy::is_persistent(kind)
 }
 
 fn filter_kinds_all_persistent(filter: &Filter) -> bool {
@@ -37,16 +40,269 @@ fn filter_kinds_all_persistent(filter: &Filter) -> bool {
     }
 }
 
-fn filter_kinds_all_ephemeral(filter: &Filter) -> bool {
-    match &filter.kinds {
-        Some(kinds) if !kinds.is_empty() => kinds.iter().all(|k| !is_persistent_kind(*k)),
-        _ => false,
+/// Filter for every version of a replaceable or addressable event.
+fn coordinate_filter(event: &Event) -> Option<Filter> {
+    let filter = Filter::new().kind(event.kind).author(event.pubkey);
+    if event.kind.is_replaceable() {
+        Some(filter)
+    } else if event.kind.is_addressable() {
+        Some(filter.identifier(event.tags.identifier().unwrap_or_default()))
+    } else {
+        None
+    }
+}
+
+/// NIP-01: the newer version wins, and on equal timestamps the lowest id.
+fn supersedes(a: &Event, b: &Event) -> bool {
+    (a.created_at, std::cmp::Reverse(a.id)) > (b.created_at, std::cmp::Reverse(b.id))
+}
+
+/// Merge tier results keeping only the newest version of each replaceable
+/// or addressable event. Each tier enforces this on its own; versions can
+/// only end up split across tiers.
+fn merge_latest(filter: &Filter, tiers: [Events; 2]) -> Events {
+    let mut latest: HashMap<(Kind, PublicKey, String), Event> = HashMap::new();
+    let mut merged = Events::new(filter);
+    for event in tiers.into_iter().flatten() {
+        let key = if event.kind.is_replaceable() {
+            (event.kind, event.pubkey, String::new())
+        } else if event.kind.is_addressable() {
+            let d = event.tags.identifier().unwrap_or_default().to_string();
+            (event.kind, event.pubkey, d)
+        } else {
+            merged.insert(event);
+            continue;
+        };
+        match latest.get(&key) {
+            Some(current) if !supersedes(&event, current) => {}
+            _ => {
+                latest.insert(key, event);
+            }
+        }
+    }
+    merged.extend(latest.into_values());
+    merged
+}
+
+struct RecencyEntry {
+    tick: u64,
+    hits: u32,
+    bytes: usize,
+}
+
+/// Least-recently-used order of the memory tier. `MemoryDatabase` has no
+/// notion of reads, so eviction is driven from here.
+#[derive(Default)]
+struct Recency {
+    tick: u64,
+    entries: HashMap<EventId, RecencyEntry>,
+    order: BTreeMap<u64, EventId>,
+    bytes: usize,
+}
+
+impl Recency {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn insert(&mut self, id: EventId, bytes: usize) {
+        let tick = self.next_tick();
+        if let Some(old) = self.entries.insert(
+            id,
+            RecencyEntry {
+                tick,
+                hits: 0,
+                bytes,
+            },
+        ) {
+            self.order.remove(&old.tick);
+            self.bytes -= old.bytes;
+        }
+        self.order.insert(tick, id);
+        self.bytes += bytes;
+    }
+
+    /// Mark `id` as just read. Returns how often it has been read.
+    fn touch(&mut self, id: &EventId) -> Option<u32> {
+        let tick = self.next_tick();
+        let entry = self.entries.get_mut(id)?;
+        self.order.remove(&entry.tick);
+        self.order.insert(tick, *id);
+        entry.tick = tick;
+        entry.hits += 1;
+        Some(entry.hits)
+    }
+
+    fn remove(&mut self, id: &EventId) {
+        if let Some(entry) = self.entries.remove(id) {
+            self.order.remove(&entry.tick);
+            self.bytes -= entry.bytes;
+        }
+    }
+
+    /// Drop the least recently used entries beyond `max`.
+    fn evict_over(&mut self, max: usize) -> Vec<EventId> {
+        let excess = self.entries.len().saturating_sub(max);
+        let mut evicted = Vec::with_capacity(excess);
+        for _ in 0..excess {
+            let Some((_, id)) = self.order.pop_first() else {
+                break;
+            };
+            if let Some(entry) = self.entries.remove(&id) {
+                self.bytes -= entry.bytes;
+            }
+            evicted.push(id);
+        }
+        evicted
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+        self.bytes = 0;
+    }
+}
+
+#[derive(Default)]
+struct TierCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl TierCounters {
+    fn record(&self, found: bool) {
+        let counter = if found { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = hits + misses;
+        serde_json::json!({
+            "hits": hits,
+            "misses": misses,
+            "hitRate": if lookups == 0 { 0.0 } else { hits as f64 / lookups as f64 },
+        })
+    }
+}
+
+/// Lookup counters per tier. A lookup is an id lookup or a query sent to
+/// that tier; it is a hit when the tier returned something.
+#[derive(Default)]
+struct TierStats {
+    memory: TierCounters,
+    lmdb: TierCounters,
+    evictions: AtomicU64,
+    promotions: AtomicU64,
+    memory_events: AtomicU64,
+    memory_bytes: AtomicU64,
+    lmdb_path: OnceLock<String>,
+}
+
+/// Stats of the most recently opened database.
+static ACTIVE_STATS: OnceLock<Mutex<Weak<TierStats>>> = OnceLock::new();
+
+/// Hit rates, evictions, promotions and size of each tier as JSON, or
+/// `None` if no database is open.
+pub(crate) fn tier_stats_json() -> Option<serde_json::Value> {
+    let stats = ACTIVE_STATS.get()?.lock().unwrap().upgrade()?;
+    let mut memory = stats.memory.to_json();
+    memory["events"] = stats.memory_events.load(Ordering::Relaxed).into();
+    memory["bytes"] = stats.memory_bytes.load(Ordering::Relaxed).into();
+    memory["maxEvents"] = crate::persistence_policy::memory_max_events().into();
+    memory["evictions"] = stats.evictions.load(Ordering::Relaxed).into();
+    memory["promotions"] = stats.promotions.load(Ordering::Relaxed).into();
+    let mut lmdb = stats.lmdb.to_json();
+    lmdb["bytes"] = stats
+        .lmdb_path
+        .get()
+        .map_or(0, |path| crate::storage_quota::data_file_size(path))
+        .into();
+    Some(serde_json::json!({ "memory": memory, "lmdb": lmdb }))
+}
+
+/// Events that arrived on subscriptions opened with [`hold_back`], by
+/// subscription. [`HybridDatabase`] refuses to store them, which the pool
+/// still passes on to the subscriber.
+static HELD_BACK: OnceLock<Mutex<HashMap<SubscriptionId, HashSet<EventId>>>> = OnceLock::new();
+
+fn held_back() -> &'static Mutex<HashMap<SubscriptionId, HashSet<EventId>>> {
+    HELD_BACK.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Keep events received on `sub_id` out of the database until [`release`].
+/// Must be called before the subscription is sent.
+pub(crate) fn hold_back(sub_id: &SubscriptionId) {
+    held_back()
+        .lock()
+        .unwrap()
+        .insert(sub_id.clone(), HashSet::new());
+}
+
+pub(crate) fn release(sub_id: &SubscriptionId) {
+    held_back().lock().unwrap().remove(sub_id);
+}
+
+fn take_held_back(id: &EventId) -> bool {
+    held_back()
+        .lock()
+        .unwrap()
+        .values_mut()
+        .any(|ids| ids.remove(id))
+}
+
+/// Admission policy that marks events arriving on held-back subscriptions,
+/// since [`NostrDatabase::save_event`] doesn't see the subscription.
+#[derive(Debug, Default)]
+pub(crate) struct HoldBackPolicy;
+
+impl AdmitPolicy for HoldBackPolicy {
+    fn admit_event<'a>(
+        &'a self,
+        _relay_url: &'a RelayUrl,
+        subscription_id: &'a SubscriptionId,
+        event: &'a Event,
+    ) -> BoxedFuture<'a, Result<AdmitStatus, PolicyError>> {
+        Box::pin(async move {
+            if let Some(ids) = held_back().lock().unwrap().get_mut(subscription_id) {
+                ids.insert(event.id);
+            }
+            Ok(AdmitStatus::Success)
+        })
+    }
+}
+
+/// Persistent tier. `db` is `None` only while the map is being resized, or
+/// if reopening after a resize failed.
+struct LmdbStore {
+    db: Option<NostrLMDB>,
+    map_size: usize,
+}
+
+impl LmdbStore {
+    fn db(&self) -> Result<&NostrLMDB, DatabaseError> {
+        self.db
+            .as_ref()
+            .ok_or_else(|| DatabaseError::backend(std::io::Error::other("LMDB store is closed")))
     }
 }
 
 pub struct HybridDatabase {
-    lmdb: NostrLMDB,
+    lmdb: RwLock<LmdbStore>,
     memory: MemoryDatabase,
+    /// Directory of the LMDB store, needed to reopen it with a larger map.
+    path: Option<String>,
+    saves_since_check: AtomicU32,
+    recency: Mutex<Recency>,
+    stats: Arc<TierStats>,
+    /// Frequently read memory-tier events waiting to be copied into LMDB;
+    /// see [`HybridDatabase::shared`].
+    promotions: OnceLock<mpsc::UnboundedSender<Event>>,
+    /// Memory-tier cap overriding the policy's; see
+    /// [`HybridDatabase::memory_cap`].
+    memory_cap: Option<usize>,
 }
 
 impl fmt::Debug for HybridDatabase {
@@ -60,11 +316,355 @@ impl fmt::Debug for HybridDatabase {
 
 impl HybridDatabase {
     pub(crate) fn new(lmdb: NostrLMDB) -> Self {
+        // Unbounded here: `Recency` enforces the policy's cap, so a changed
+        // cap applies from the next save.
         let memory = MemoryDatabase::with_opts(MemoryDatabaseOptions {
             events: true,
-            max_events: Some(50000),
+            max_events: None,
+        });
+        let stats = Arc::new(TierStats::default());
+        *ACTIVE_STATS
+            .get_or_init(|| Mutex::new(Weak::new()))
+            .lock()
+            .unwrap() = Arc::downgrade(&stats);
+        Self {
+            lmdb: RwLock::new(LmdbStore {
+                db: Some(lmdb),
+                map_size: 0,
+            }),
+            memory,
+            path: None,
+            saves_since_check: AtomicU32::new(0),
+            recency: Mutex::new(Recency::default()),
+            stats,
+            promotions: OnceLock::new(),
+            memory_cap: None,
+        }
+    }
+
+    /// Cap the memory tier at `max_events` regardless of the policy, so tests
+    /// don't change the cap for everything else running.
+    #[cfg(test)]
+    fn memory_cap(mut self, max_events: usize) -> Self {
+        self.memory_cap = Some(max_events);
+        self
+    }
+
+    fn memory_max_events(&self) -> usize {
+        self.memory_cap
+            .unwrap_or_else(crate::persistence_policy::memory_max_events)
+    }
+
+    /// Share the database and start a task promoting frequently read
+    /// memory-tier events into LMDB, so reads never wait on a write. The
+    /// task stops once the database is dropped.
+    pub(crate) fn shared(self) -> Arc<Self> {
+        let database = Arc::new(self);
+        let (tx, mut rx) = mpsc::unbounded_channel::<Event>();
+        let _ = database.promotions.set(tx);
+        let weak = Arc::downgrade(&database);
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                let Some(database) = weak.upgrade() else {
+                    break;
+                };
+                let _ = database.promote(&event).await;
+            }
         });
-        Self { lmdb, memory }
+        database
+    }
+
+    /// Let the LMDB map grow: `lmdb` was opened at `path` with `map_size`
+    /// through [`lmdb_builder`]. Growing stays off if heed doesn't recognise
+    /// [`env_options`] as the options of the open env, since the env then
+    /// couldn't be closed before reopening.
+    pub(crate) fn resizable(mut self, path: &str, map_size: usize) -> Self {
+        self.lmdb.get_mut().map_size = map_size;
+        let _ = self.stats.lmdb_path.set(path.to_string());
+        crate::storage_quota::record_map_size(map_size);
+        // SAFETY: identical options return the registered env instead of
+        // opening a second one.
+        match unsafe { env_options(map_size).open(path) } {
+            Ok(_) => self.path = Some(path.to_string()),
+            Err(e) => crate::storage_quota::record_growth(map_size, Some(e.to_string())),
+        }
+        self
+    }
+
+    fn publish_memory_size(&self, recency: &Recency) {
+        let stats = &self.stats;
+        stats
+            .memory_events
+            .store(recency.entries.len() as u64, Ordering::Relaxed);
+        stats
+            .memory_bytes
+            .store(recency.bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Save to the memory tier, evicting the least recently used events
+    /// beyond the policy's cap.
+    async fn save_to_memory(&self, event: &Event) -> Result<SaveEventStatus, DatabaseError> {
+        let status = self.memory.save_event(event).await?;
+        if status != SaveEventStatus::Success {
+            return Ok(status);
+        }
+        let evicted = {
+            let mut recency = self.recency.lock().unwrap();
+            recency.insert(event.id, event.as_json().len());
+            let evicted = recency.evict_over(self.memory_max_events());
+            self.publish_memory_size(&recency);
+            evicted
+        };
+        if !evicted.is_empty() {
+            self.stats
+                .evictions
+                .fetch_add(evicted.len() as u64, Ordering::Relaxed);
+            crate::search_index::remove_events(evicted.iter().copied());
+            self.memory.delete(Filter::new().ids(evicted)).await?;
+        }
+        Ok(status)
+    }
+
+    /// Record reads of memory-tier events and queue the ones that just
+    /// became frequently read for promotion into LMDB.
+    fn touch_memory(&self, events: &[&Event]) {
+        let mut recency = self.recency.lock().unwrap();
+        for event in events {
+            if recency.touch(&event.id) == Some(PROMOTE_AFTER_HITS) {
+                if let Some(promotions) = self.promotions.get() {
+                    let _ = promotions.send((*event).clone());
+                }
+            }
+        }
+    }
+
+    /// Move `event` from the memory tier into LMDB, through the same
+    /// retention check and map growth as any other persistent save.
+    async fn promote(&self, event: &Event) -> Result<(), DatabaseError> {
+        // Evicted or deleted while queued.
+        if self.memory.check_id(&event.id).await? != DatabaseEventStatus::Saved {
+            return Ok(());
+        }
+        if crate::persistence_policy::retention_cutoff(event.kind)
+            .is_some_and(|cutoff| event.created_at < cutoff)
+        {
+            return Ok(());
+        }
+        match self.save_to_lmdb(event).await? {
+            SaveEventStatus::Success | SaveEventStatus::Rejected(RejectedReason::Duplicate) => {}
+            // Kinds LMDB won't take stay in memory.
+            SaveEventStatus::Rejected(_) => return Ok(()),
+        }
+        self.memory.delete(Filter::new().id(event.id)).await?;
+        let mut recency = self.recency.lock().unwrap();
+        recency.remove(&event.id);
+        self.publish_memory_size(&recency);
+        self.stats.promotions.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    async fn query_memory(&self, filter: Filter) -> Result<Events, DatabaseError> {
+        let events = self.memory.query(filter).await?;
+        self.stats.memory.record(!events.is_empty());
+        self.touch_memory(&events.iter().collect::<Vec<_>>());
+        Ok(events)
+    }
+
+    async fn query_lmdb(&self, filter: Filter) -> Result<Events, DatabaseError> {
+        let events = self.lmdb.read().await.db()?.query(filter).await?;
+        self.stats.lmdb.record(!events.is_empty());
+        Ok(events)
+    }
+
+    /// Versions of `event`'s coordinate in the tier it is *not* routed to,
+    /// or `None` if one of them supersedes `event`. The target tier already
+    /// replaces its own older versions.
+    async fn older_versions(&self, event: &Event) -> Result<Option<Vec<EventId>>, DatabaseError> {
+        let Some(filter) = coordinate_filter(event) else {
+            return Ok(Some(Vec::new()));
+        };
+        let stored = if is_persistent_kind(event.kind) {
+            self.memory.query(filter).await?
+        } else {
+            self.lmdb.read().await.db()?.query(filter).await?
+        };
+        if stored.iter().any(|other| supersedes(other, event)) {
+            return Ok(None);
+        }
+        Ok(Some(
+            stored
+                .into_iter()
+                .filter(|other| other.id != event.id)
+                .map(|other| other.id)
+                .collect(),
+        ))
+    }
+
+    async fn delete_ids(&self, ids: Vec<EventId>) -> Result<(), DatabaseError> {
+        {
+            let mut recency = self.recency.lock().unwrap();
+            for id in &ids {
+                recency.remove(id);
+            }
+            self.publish_memory_size(&recency);
+        }
+        crate::search_index::remove_events(ids.iter().copied());
+        let filter = Filter::new().ids(ids);
+        self.memory.delete(filter.clone()).await?;
+        self.lmdb.read().await.db()?.delete(filter).await
+    }
+
+    async fn save_routed(&self, event: &Event) -> Result<SaveEventStatus, DatabaseError> {
+        if !is_persistent_kind(event.kind) {
+            return self.save_to_memory(event).await;
+        }
+        self.save_to_lmdb(event).await
+    }
+
+    /// Save to LMDB, growing the map when it is full or close to it.
+    async fn save_to_lmdb(&self, event: &Event) -> Result<SaveEventStatus, DatabaseError> {
+        let (result, map_size) = {
+            let store = self.lmdb.read().await;
+            (store.db()?.save_event(event).await, store.map_size)
+        };
+        match result {
+            Err(e) if is_map_full(&e) || self.near_capacity(map_size) => {
+                self.grow(map_size).await?;
+                self.lmdb.read().await.db()?.save_event(event).await
+            }
+            result => {
+                self.check_capacity().await;
+                result
+            }
+        }
+    }
+
+    /// Whether the data file uses most of a map of `map_size` bytes. The
+    /// writer batches saves and reports a full map as a generic batch
+    /// failure, so failed writes are checked against this too.
+    fn near_capacity(&self, map_size: usize) -> bool {
+        self.path.as_deref().is_some_and(|path| {
+            crate::storage_quota::data_file_size(path) as f64 >= map_size as f64 * GROW_THRESHOLD
+        })
+    }
+
+    /// Grow the map if the data file is close to filling it. Checked every
+    /// few hundred writes, since it costs a `stat`.
+    async fn check_capacity(&self) {
+        if self.saves_since_check.fetch_add(1, Ordering::Relaxed) < CAPACITY_CHECK_INTERVAL {
+            return;
+        }
+        self.saves_since_check.store(0, Ordering::Relaxed);
+        let map_size = self.lmdb.read().await.map_size;
+        if self.near_capacity(map_size) {
+            let _ = self.grow(map_size).await;
+        }
+    }
+
+    /// Reopen the LMDB store with a larger map, unless another caller
+    /// already grew it past `seen_map_size`. The new size is capped by the
+    /// platform limit and the storage quota; when it can't grow the quota
+    /// task is woken to evict events instead.
+    async fn grow(&self, seen_map_size: usize) -> Result<(), DatabaseError> {
+        let Some(path) = self.path.as_deref() else {
+            return Err(DatabaseError::NotSupported);
+        };
+        let mut store = self.lmdb.write().await;
+        if store.map_size > seen_map_size {
+            return Ok(());
+        }
+        let new_size = crate::storage_quota::next_map_size(store.map_size);
+        if new_size <= store.map_size {
+            crate::storage_quota::wake();
+            return Err(DatabaseError::backend(std::io::Error::other(
+                "LMDB map is at its maximum size",
+            )));
+        }
+
+        let Some(closing) = release_env(path, store.map_size) else {
+            return Err(DatabaseError::backend(std::io::Error::other(
+                "LMDB env is not registered with the expected options",
+            )));
+        };
+        drop(store.db.take());
+        let waiting = closing.clone();
+        let closed = tokio::task::spawn_blocking(move || waiting.wait_timeout(ENV_CLOSE_TIMEOUT))
+            .await
+            .unwrap_or(false);
+        if !closed {
+            // Something still holds the old env, which heed no longer knows
+            // about, so opening the store now would map the same file twice.
+            // Give up on growing and reopen as it was once the env is gone.
+            let _ = tokio::task::spawn_blocking(move || closing.wait()).await;
+            store.db = reopen(path, store.map_size).await.ok();
+            let error = "LMDB env did not close in time, map not grown";
+            crate::storage_quota::record_growth(store.map_size, Some(error.to_string()));
+            return Err(DatabaseError::backend(std::io::Error::other(error)));
+        }
+        match reopen(path, new_size).await {
+            Ok(db) => {
+                store.db = Some(db);
+                store.map_size = new_size;
+                crate::storage_quota::record_growth(new_size, None);
+                Ok(())
+            }
+            Err(e) => {
+                crate::storage_quota::record_growth(store.map_size, Some(e.to_string()));
+                store.db = reopen(path, store.map_size).await.ok();
+                Err(e)
+            }
+        }
+    }
+}
+
+fn is_map_full(error: &DatabaseError) -> bool {
+    error.to_string().contains("MDB_MAP_FULL")
+}
+
+/// Builder for the LMDB store at `path`. Every open goes through this, so
+/// that [`env_options`] describes the env nostr-lmdb registers with heed.
+pub(crate) fn lmdb_builder(path: &str, map_size: usize) -> NostrLmdbBuilder {
+    NostrLMDB::builder(path)
+        .map_size(map_size)
+        .max_readers(LMDB_MAX_READERS)
+        .additional_dbs(LMDB_ADDITIONAL_DBS)
+}
+
+/// The heed options nostr-lmdb opens a store from [`lmdb_builder`] with.
+fn env_options(map_size: usize) -> EnvOpenOptions {
+    let mut options = EnvOpenOptions::new();
+    // SAFETY: only sets the flag nostr-lmdb itself opens the env with.
+    unsafe { options.flags(EnvFlags::NO_TLS) };
+    options
+        .max_dbs(LMDB_BASE_DBS + LMDB_ADDITIONAL_DBS)
+        .max_readers(LMDB_MAX_READERS)
+        .map_size(map_size);
+    options
+}
+
+/// heed keeps every env it opened in a global registry, so dropping the
+/// store alone never closes it. Get the registered handle back by opening
+/// with exactly the options nostr-lmdb uses, and take it out of the
+/// registry; the env closes once the store is dropped too.
+fn release_env(path: &str, map_size: usize) -> Option<EnvClosingEvent> {
+    // SAFETY: same path and options as the open store, so this returns the
+    // existing env rather than opening a second one.
+    let env = unsafe { env_options(map_size).open(path) };
+    env.ok().map(|env| env.prepare_for_closing())
+}
+
+/// Open the store at `path`, waiting for a previous instance's env to close.
+async fn reopen(path: &str, map_size: usize) -> Result<NostrLMDB, DatabaseError> {
+    let mut attempts = 0;
+    loop {
+        match lmdb_builder(path, map_size).build() {
+            Ok(db) => return Ok(db),
+            Err(_) if attempts < REOPEN_ATTEMPTS => {
+                attempts += 1;
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            Err(e) => return Err(e),
+        }
     }
 }
 
@@ -78,11 +678,30 @@ impl NostrDatabase for HybridDatabase {
         event: &'a Event,
     ) -> BoxedFuture<'a, Result<SaveEventStatus, DatabaseError>> {
         Box::pin(async move {
-            if is_persistent_kind(event.kind) {
-                self.lmdb.save_event(event).await
-            } else {
-                self.memory.save_event(event).await
+            // `Other` still lets the pool deliver the event.
+            if take_held_back(&event.id) {
+                return Ok(SaveEventStatus::Rejected(RejectedReason::Other));
+            }
+            if crate::persistence_policy::retention_cutoff(event.kind)
+                .is_some_and(|cutoff| event.created_at < cutoff)
+            {
+                return Ok(SaveEventStatus::Rejected(RejectedReason::Other));
             }
+            // Each tier keeps only the newest version of a replaceable or
+            // addressable event; this extends that across both of them.
+            let Some(older) = self.older_versions(event).await? else {
+                return Ok(SaveEventStatus::Rejected(RejectedReason::Replaced));
+            };
+            let status = self.save_routed(event).await?;
+            if status == SaveEventStatus::Success {
+                crate::search_index::index_event(event);
+                crate::profile_index::index_event(event);
+                crate::follow_graph::index_event(event);
+                if !older.is_empty() {
+                    self.delete_ids(older).await?;
+                }
+            }
+            Ok(status)
         })
     }
 
@@ -95,7 +714,7 @@ impl NostrDatabase for HybridDatabase {
             if mem_status != DatabaseEventStatus::NotExistent {
                 return Ok(mem_status);
             }
-            self.lmdb.check_id(event_id).await
+            self.lmdb.read().await.db()?.check_id(event_id).await
         })
     }
 
@@ -105,22 +724,24 @@ impl NostrDatabase for HybridDatabase {
     ) -> BoxedFuture<'a, Result<Option<Event>, DatabaseError>> {
         Box::pin(async move {
             if let Some(event) = self.memory.event_by_id(event_id).await? {
+                self.stats.memory.record(true);
+                self.touch_memory(&[&event]);
                 return Ok(Some(event));
             }
-            self.lmdb.event_by_id(event_id).await
+            self.stats.memory.record(false);
+            let event = self.lmdb.read().await.db()?.event_by_id(event_id).await?;
+            self.stats.lmdb.record(event.is_some());
+            Ok(event)
         })
     }
 
     fn count(&self, filter: Filter) -> BoxedFuture<'_, Result<usize, DatabaseError>> {
         Box::pin(async move {
             if filter_kinds_all_persistent(&filter) {
-                return self.lmdb.count(filter).await;
-            }
-            if filter_kinds_all_ephemeral(&filter) {
-                return self.memory.count(filter).await;
+                return self.lmdb.read().await.db()?.count(filter).await;
             }
             let mem = self.memory.count(filter.clone()).await?;
-            let lmdb = self.lmdb.count(filter).await?;
+            let lmdb = self.lmdb.read().await.db()?.count(filter).await?;
             Ok(mem + lmdb)
         })
     }
@@ -128,17 +749,13 @@ impl NostrDatabase for HybridDatabase {
     fn query(&self, filter: Filter) -> BoxedFuture<'_, Result<Events, DatabaseError>> {
         Box::pin(async move {
             if filter_kinds_all_persistent(&filter) {
-                return self.lmdb.query(filter).await;
+                return self.query_lmdb(filter).await;
             }
-            if filter_kinds_all_ephemeral(&filter) {
-                return self.memory.query(filter).await;
-            }
-            let mut events = self.memory.query(filter.clone()).await?;
-            let lmdb_events = self.lmdb.query(filter).await?;
-            for event in lmdb_events {
-                events.insert(event);
-            }
-            Ok(events)
+            // Memory-only kinds are looked up in LMDB too: frequently read
+            // events are promoted there.
+            let memory_events = self.query_memory(filter.clone()).await?;
+            let lmdb_events = self.query_lmdb(filter.clone()).await?;
+            Ok(merge_latest(&filter, [memory_events, lmdb_events]))
         })
     }
 
@@ -148,13 +765,16 @@ impl NostrDatabase for HybridDatabase {
     ) -> BoxedFuture<'_, Result<Vec<(EventId, Timestamp)>, DatabaseError>> {
         Box::pin(async move {
             if filter_kinds_all_persistent(&filter) {
-                return self.lmdb.negentropy_items(filter).await;
-            }
-            if filter_kinds_all_ephemeral(&filter) {
-                return self.memory.negentropy_items(filter).await;
+                return self.lmdb.read().await.db()?.negentropy_items(filter).await;
             }
             let mut items = self.memory.negentropy_items(filter.clone()).await?;
-            let lmdb_items = self.lmdb.negentropy_items(filter).await?;
+            let lmdb_items = self
+                .lmdb
+                .read()
+                .await
+                .db()?
+                .negentropy_items(filter)
+                .await?;
             items.extend(lmdb_items);
             Ok(items)
         })
@@ -162,8 +782,27 @@ impl NostrDatabase for HybridDatabase {
 
     fn delete(&self, filter: Filter) -> BoxedFuture<'_, Result<(), DatabaseError>> {
         Box::pin(async move {
+            let deleted = self.memory.query(filter.clone()).await?;
             self.memory.delete(filter.clone()).await?;
-            self.lmdb.delete(filter).await?;
+            {
+                let mut recency = self.recency.lock().unwrap();
+                for event in deleted.iter() {
+                    recency.remove(&event.id);
+                }
+                self.publish_memory_size(&recency);
+            }
+            let store = self.lmdb.read().await;
+            let lmdb = store.db()?;
+            if let Some(indexed) = crate::search_index::indexed_part(&filter) {
+                let items = lmdb.negentropy_items(indexed).await?;
+                crate::search_index::remove_events(
+                    deleted
+                        .iter()
+                        .map(|e| e.id)
+                        .chain(items.into_iter().map(|(id, _)| id)),
+                );
+            }
+            lmdb.delete(filter).await?;
             Ok(())
         })
     }
@@ -171,8 +810,179 @@ impl NostrDatabase for HybridDatabase {
     fn wipe(&self) -> BoxedFuture<'_, Result<(), DatabaseError>> {
         Box::pin(async move {
             self.memory.wipe().await?;
-            self.lmdb.wipe().await?;
+            crate::search_index::clear();
+            {
+                let mut recency = self.recency.lock().unwrap();
+                recency.clear();
+                self.publish_memory_size(&recency);
+            }
+            self.lmdb.read().await.db()?.wipe().await?;
             Ok(())
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn full_map_grows_instead_of_failing_writes() {
+        let dir = std::env::temp_dir().join(format!("hybrid-grow-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.to_str().unwrap();
+        let map_size = 1024 * 1024;
+        let lmdb = lmdb_builder(path, map_size).build().unwrap();
+        let database = HybridDatabase::new(lmdb).resizable(path, map_size);
+
+        let keys = Keys::generate();
+        let padding = "x".repeat(1024);
+        for i in 0..2_000 {
+            let event = EventBuilder::text_note(format!("{} {}", i, padding))
+                .sign_with_keys(&keys)
+                .unwrap();
+            database.save_event(&event).await.unwrap();
+        }
+
+        assert!(database.lmdb.read().await.map_size > map_size);
+        assert_eq!(
+            database
+                .count(Filter::new().kind(Kind::TextNote))
+                .await
+                .unwrap(),
+            2_000
+        );
+        drop(database);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn held_back_subscriptions_are_not_stored() {
+        let dir = std::env::temp_dir().join(format!("hybrid-held-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.to_str().unwrap();
+        let lmdb = NostrLMDB::builder(path).build().unwrap();
+        let database = HybridDatabase::new(lmdb);
+
+        let keys = Keys::generate();
+        let relay_url = RelayUrl::parse("wss://relay.example.com").unwrap();
+        let held = SubscriptionId::generate();
+        let other = SubscriptionId::generate();
+        hold_back(&held);
+
+        let counted = EventBuilder::text_note("counted")
+            .sign_with_keys(&keys)
+            .unwrap();
+        let kept = EventBuilder::text_note("kept")
+            .sign_with_keys(&keys)
+            .unwrap();
+        for (sub_id, event) in [(&held, &counted), (&other, &kept)] {
+            HoldBackPolicy
+                .admit_event(&relay_url, sub_id, event)
+                .await
+                .unwrap();
+            database.save_event(event).await.unwrap();
+        }
+        release(&held);
+
+        assert!(database.event_by_id(&counted.id).await.unwrap().is_none());
+        assert!(database.event_by_id(&kept.id).await.unwrap().is_some());
+        drop(database);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn memory_tier_evicts_least_recent_and_promotes_hot_events() {
+        let dir = std::env::temp_dir().join(format!("hybrid-lru-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.to_str().unwrap();
+        let lmdb = NostrLMDB::builder(path).build().unwrap();
+        let database = HybridDatabase::new(lmdb).memory_cap(2).shared();
+
+        let keys = Keys::generate();
+        let kind = Kind::from(4_242);
+        let events: Vec<Event> = (0..3)
+            .map(|i| {
+                EventBuilder::new(kind, format!("{}", i))
+                    .sign_with_keys(&keys)
+                    .unwrap()
+            })
+            .collect();
+        database.save_event(&events[0]).await.unwrap();
+        database.save_event(&events[1]).await.unwrap();
+        database.event_by_id(&events[0].id).await.unwrap();
+        database.save_event(&events[2]).await.unwrap();
+        assert_eq!(database.stats.evictions.load(Ordering::Relaxed), 1);
+        assert!(database
+            .memory
+            .event_by_id(&events[1].id)
+            .await
+            .unwrap()
+            .is_none());
+
+        for _ in 0..PROMOTE_AFTER_HITS {
+            database.event_by_id(&events[2].id).await.unwrap();
+        }
+        for _ in 0..100 {
+            if database.stats.promotions.load(Ordering::Relaxed) > 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(database.stats.promotions.load(Ordering::Relaxed), 1);
+        assert!(database
+            .memory
+            .event_by_id(&events[2].id)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(database.count(Filter::new().kind(kind)).await.unwrap(), 2);
+        assert!(database.stats.memory.hits.load(Ordering::Relaxed) >= PROMOTE_AFTER_HITS as u64);
+        drop(database);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn only_the_newest_replaceable_version_survives_across_tiers() {
+        let dir = std::env::temp_dir().join(format!("hybrid-replace-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let lmdb = NostrLMDB::builder(dir.to_str().unwrap()).build().unwrap();
+        let database = HybridDatabase::new(lmdb);
+
+        let keys = Keys::generate();
+        let profile = |name: &str, at: u64| {
+            EventBuilder::metadata(&Metadata::new().name(name))
+                .custom_created_at(Timestamp::from(at))
+                .sign_with_keys(&keys)
+                .unwrap()
+        };
+        // A version left in the memory tier, e.g. by an earlier policy.
+        let stale = profile("stale", 1_000);
+        database.memory.save_event(&stale).await.unwrap();
+
+        let current = profile("current", 2_000);
+        assert_eq!(
+            database.save_event(&current).await.unwrap(),
+            SaveEventStatus::Success
+        );
+        assert!(database
+            .memory
+            .event_by_id(&stale.id)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            database.save_event(&profile("older", 1_500)).await.unwrap(),
+            SaveEventStatus::Rejected(RejectedReason::Replaced)
+        );
+
+        database.memory.save_event(&stale).await.unwrap();
+        let events = database
+            .query(Filter::new().kind(Kind::Metadata).author(keys.public_key()))
+            .await
+            .unwrap();
+        assert_eq!(events.to_vec(), vec![current]);
+        drop(database);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,731 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use nostr_sdk::prelude::*;
+
+use crate::api::database::{extract_first_e_tag, extract_reply_parent_id, extract_zap_amount_sats};
+use crate::api::event_bus::{self, BusItem};
+use crate::relay_messages::RejectReason;
+
+const NIP45: u16 = 45;
+const COUNT_TIMEOUT: Duration = Duration::from_secs(4);
+const COUNT_CONCURRENCY: usize = 16;
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+const FETCH_LIMIT: usize = 500;
+const CACHE_CAPACITY: usize = 2_000;
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+const REACTIONS: usize = 0;
+const REPOSTS: usize = 1;
+const ZAPS: usize = 2;
+const REPLIES: usize = 3;
+
+/// Kinds counted with NIP-45 COUNT, and the slot each one fills. Zaps are
+/// summed in sats, which COUNT can't do, and only direct replies count as
+/// replies, which a `#e` filter can't tell apart from mentions and quotes,
+/// so both are always fetched.
+const COUNTED_KINDS: [(Kind, usize); 2] = [(Kind::Reaction, REACTIONS), (Kind::Repost, REPOSTS)];
+
+/// The tally slot events of `kind` are counted in.
+fn slot_of(kind: Kind) -> Option<usize> {
+    match kind {
+        Kind::Reaction => Some(REACTIONS),
+        Kind::Repost => Some(REPOSTS),
+        Kind::ZapReceipt => Some(ZAPS),
+        Kind::TextNote => Some(REPLIES),
+        _ => None,
+    }
+}
+
+/// Reaction, repost, zap and reply counts for a set of notes, built from
+/// fetched events and NIP-45 COUNT answers. A count is exact when it comes
+/// only from fetched events and the fetch of its kind finished on every
+/// relay without being cut off by its limit.
+pub(crate) struct InteractionTally {
+    note_ids: Vec<String>,
+    user_pk: Option<PublicKey>,
+    fetched: HashMap<String, [usize; 4]>,
+    counted: HashMap<String, [Option<usize>; 4]>,
+    reacted: HashSet<String>,
+    reposted: HashSet<String>,
+    seen: HashSet<EventId>,
+    estimated: [bool; 4],
+}
+
+impl InteractionTally {
+    pub(crate) fn new(note_ids: Vec<String>, user_pk: Option<PublicKey>) -> Self {
+        Self {
+            fetched: note_ids.iter().map(|id| (id.clone(), [0; 4])).collect(),
+            counted: HashMap::new(),
+            note_ids,
+            user_pk,
+            reacted: HashSet::new(),
+            reposted: HashSet::new(),
+            seen: HashSet::new(),
+            estimated: [false; 4],
+        }
+    }
+
+    /// Count one fetched event. Returns false for duplicates and events
+    /// that don't touch any tracked note.
+    pub(crate) fn add_event(&mut self, event: &Event) -> bool {
+        if !self.seen.insert(event.id) {
+            return false;
+        }
+        let Some(slot) = slot_of(event.kind) else {
+            return false;
+        };
+        let target = match slot {
+            REPLIES => extract_reply_parent_id(event),
+            _ => extract_first_e_tag(event),
+        };
+        let Some(target) = target else {
+            return false;
+        };
+        let Some(c) = self.fetched.get_mut(&target) else {
+            return false;
+        };
+
+        if slot == ZAPS {
+            c[ZAPS] += extract_zap_amount_sats(event) as usize;
+        } else {
+            c[slot] += 1;
+        }
+        if self.user_pk == Some(event.pubkey) {
+            match slot {
+                REACTIONS => {
+                    self.reacted.insert(target);
+                }
+                REPOSTS => {
+                    self.reposted.insert(target);
+                }
+                _ => {}
+            }
+        }
+        true
+    }
+
+    /// Mark the fetched counts as incomplete, e.g. after hitting a limit.
+    pub(crate) fn set_truncated(&mut self) {
+        self.estimated = [true; 4];
+    }
+
+    /// Mark the counts of `kinds` as incomplete, e.g. because a relay never
+    /// finished sending them.
+    pub(crate) fn set_incomplete(&mut self, kinds: &[Kind]) {
+        for slot in kinds.iter().filter_map(|k| slot_of(*k)) {
+            self.estimated[slot] = true;
+        }
+    }
+
+    fn set_count(&mut self, note_id: &str, slot: usize, count: usize) {
+        let entry = self.counted.entry(note_id.to_string()).or_default();
+        entry[slot] = Some(entry[slot].map_or(count, |c| c.max(count)));
+    }
+
+    pub(crate) fn has_counts(&self) -> bool {
+        !self.counted.is_empty()
+    }
+
+    /// `{noteId: {reactions, reposts, zaps, replies, hasReacted, hasReposted,
+    /// exact: {reactions, reposts, zaps, replies}}}`.
+    pub(crate) fn to_map(&self) -> serde_json::Map<String, serde_json::Value> {
+        let mut result = serde_json::Map::new();
+        for nid in &self.note_ids {
+            let Some(fetched) = self.fetched.get(nid) else {
+                continue;
+            };
+            let counted = self.counted.get(nid).copied().unwrap_or_default();
+            let value = |slot: usize| fetched[slot].max(counted[slot].unwrap_or(0));
+            let exact = |slot: usize| !self.estimated[slot] && counted[slot].is_none();
+            result.insert(
+                nid.clone(),
+                serde_json::json!({
+                    "reactions": value(REACTIONS),
+                    "reposts": value(REPOSTS),
+                    "zaps": value(ZAPS),
+                    "replies": value(REPLIES),
+                    "hasReacted": self.reacted.contains(nid),
+                    "hasReposted": self.reposted.contains(nid),
+                    "exact": {
+                        "reactions": exact(REACTIONS),
+                        "reposts": exact(REPOSTS),
+                        "zaps": exact(ZAPS),
+                        "replies": exact(REPLIES),
+                    },
+                }),
+            );
+        }
+        result
+    }
+}
+
+/// Recently computed per-note aggregates, so scrolling back over a timeline
+/// doesn't recount the same notes. Bounded; the oldest entries go first.
+#[derive(Default)]
+struct AggregateCache {
+    entries: HashMap<String, (Instant, serde_json::Value)>,
+    order: VecDeque<String>,
+}
+
+static AGGREGATES: OnceLock<Mutex<AggregateCache>> = OnceLock::new();
+
+fn aggregate_cache() -> &'static Mutex<AggregateCache> {
+    AGGREGATES.get_or_init(|| Mutex::new(AggregateCache::default()))
+}
+
+/// `hasReacted`/`hasReposted` depend on the viewer, so they are part of the key.
+fn cache_key(note_id: &str, user_pk: Option<PublicKey>) -> String {
+    match user_pk {
+        Some(pk) => format!("{}:{}", note_id, pk.to_hex()),
+        None => note_id.to_string(),
+    }
+}
+
+/// Split `note_ids` into fresh cached aggregates and the ids still to count.
+pub(crate) fn cached(
+    note_ids: &[String],
+    user_pk: Option<PublicKey>,
+) -> (serde_json::Map<String, serde_json::Value>, Vec<String>) {
+    let cache = aggregate_cache().lock().unwrap();
+    let mut hits = serde_json::Map::new();
+    let mut missing = Vec::new();
+    for nid in note_ids {
+        match cache.entries.get(&cache_key(nid, user_pk)) {
+            Some((at, value)) if at.elapsed() < CACHE_TTL => {
+                hits.insert(nid.clone(), value.clone());
+            }
+            _ => missing.push(nid.clone()),
+        }
+    }
+    (hits, missing)
+}
+
+pub(crate) fn store(
+    aggregates: &serde_json::Map<String, serde_json::Value>,
+    user_pk: Option<PublicKey>,
+) {
+    let mut cache = aggregate_cache().lock().unwrap();
+    let now = Instant::now();
+    for (nid, value) in aggregates {
+        let key = cache_key(nid, user_pk);
+        if cache
+            .entries
+            .insert(key.clone(), (now, value.clone()))
+            .is_none()
+        {
+            cache.order.push_back(key);
+        }
+    }
+    while cache.entries.len() > CACHE_CAPACITY {
+        let Some(oldest) = cache.order.pop_front() else {
+            break;
+        };
+        cache.entries.remove(&oldest);
+    }
+}
+
+/// Split read relays into those advertising NIP-45 and the rest.
+async fn split_by_count_support(client: &Client) -> (Vec<RelayUrl>, Vec<RelayUrl>) {
+    let urls: Vec<RelayUrl> = client
+        .relays()
+        .await
+        .iter()
+        .filter(|(_, r)| r.flags().has(RelayServiceFlags::READ, FlagCheck::All))
+        .map(|(u, _)| u.clone())
+        .collect();
+    let support = futures::future::join_all(
+        urls.iter()
+            .map(|u| crate::relay_info::supports_nip(u, NIP45)),
+    )
+    .await;
+    urls.into_iter().zip(support).fold(
+        (Vec::new(), Vec::new()),
+        |(mut count, mut fetch), (url, ok)| {
+            if ok {
+                count.push(url);
+            } else {
+                fetch.push(url);
+            }
+            (count, fetch)
+        },
+    )
+}
+
+/// Filter for everything the fetch-and-count path needs on relays without
+/// NIP-45 support.
+fn full_filter(ids: &[EventId]) -> Filter {
+    Filter::new()
+        .kinds([
+            Kind::Reaction,
+            Kind::Repost,
+            Kind::ZapReceipt,
+            Kind::TextNote,
+        ])
+        .events(ids.iter().copied())
+}
+
+/// What still has to be fetched from NIP-45 relays after COUNT: zap
+/// receipts, replies, the counted kinds in `fetch_kinds`, and the user's own
+/// reactions and reposts for the `hasReacted`/`hasReposted` flags.
+fn count_relay_filters(
+    ids: &[EventId],
+    user_pk: Option<PublicKey>,
+    fetch_kinds: &[Kind],
+) -> Vec<Filter> {
+    let mut kinds = vec![Kind::ZapReceipt, Kind::TextNote];
+    kinds.extend_from_slice(fetch_kinds);
+    let mut filters = vec![Filter::new().kinds(kinds).events(ids.iter().copied())];
+    if let Some(pk) = user_pk {
+        filters.push(
+            Filter::new()
+                .kinds([Kind::Reaction, Kind::Repost])
+                .author(pk)
+                .events(ids.iter().copied()),
+        );
+    }
+    filters
+}
+
+enum CountAnswer {
+    Count(usize),
+    /// The relay closed the COUNT saying it doesn't support it.
+    Unsupported,
+    /// No answer in time, or the relay isn't connected.
+    NoAnswer,
+}
+
+fn rejects_count(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    RejectReason::parse(message) == RejectReason::Unsupported
+        || lower.contains("unsupported")
+        || lower.contains("not supported")
+        || lower.contains("unknown")
+}
+
+/// Send one COUNT and wait for its answer, watching for a CLOSED that says
+/// the relay doesn't support it. A NOTICE can't be tied to this request, so
+/// one that sounds like a refusal only ends the wait.
+async fn count_once(relay: &Relay, filter: Filter) -> CountAnswer {
+    if !relay.is_connected() {
+        return CountAnswer::NoAnswer;
+    }
+    let id = SubscriptionId::generate();
+    let mut notifications = relay.notifications();
+    let request = ClientMessage::Count {
+        subscription_id: Cow::Borrowed(&id),
+        filter: Cow::Owned(filter),
+    };
+    if relay.send_msg(request).is_err() {
+        return CountAnswer::NoAnswer;
+    }
+    let answer = tokio::time::timeout(COUNT_TIMEOUT, async {
+        loop {
+            let message = match notifications.recv().await {
+                Ok(RelayNotification::Message { message }) => message,
+                Ok(RelayNotification::Shutdown)
+                | Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                    return CountAnswer::NoAnswer;
+                }
+                _ => continue,
+            };
+            match message {
+                RelayMessage::Count {
+                    subscription_id,
+                    count,
+                } if subscription_id.as_ref() == &id => return CountAnswer::Count(count),
+                RelayMessage::Closed {
+                    subscription_id,
+                    message,
+                } if subscription_id.as_ref() == &id => {
+                    return if rejects_count(&message) {
+                        CountAnswer::Unsupported
+                    } else {
+                        CountAnswer::NoAnswer
+                    };
+                }
+                RelayMessage::Notice(message) if rejects_count(&message) => {
+                    return CountAnswer::NoAnswer;
+                }
+                _ => {}
+            }
+        }
+    })
+    .await
+    .unwrap_or(CountAnswer::NoAnswer);
+    let _ = relay.send_msg(ClientMessage::close(id));
+    answer
+}
+
+/// What COUNT left to do on the NIP-45 relays.
+struct CountOutcome {
+    /// Relays that answered; zaps, replies and `fetch_kinds` are fetched
+    /// from them.
+    answered: Vec<RelayUrl>,
+    /// Relays that didn't answer; everything is fetched from them.
+    failed: Vec<RelayUrl>,
+    /// Counted kinds with few enough events across the notes that fetching
+    /// them gives exact per-note counts.
+    fetch_kinds: Vec<Kind>,
+}
+
+/// One COUNT per relay and counted kind over all notes at once. Kinds whose
+/// total fits in a fetch are fetched instead, which gives exact per-note
+/// counts; only kinds with more events than that are counted note by note.
+/// A relay is marked as not supporting NIP-45 only when it closes a COUNT
+/// saying so.
+async fn count_on_relays(
+    client: &Client,
+    relays: &[RelayUrl],
+    ids: &[EventId],
+    tally: &mut InteractionTally,
+) -> CountOutcome {
+    let mut handles: Vec<Relay> = Vec::new();
+    for url in relays {
+        if let Ok(relay) = client.relay(url).await {
+            handles.push(relay);
+        }
+    }
+    let wait = handles.iter().map(|r| r.wait_for_connection(COUNT_TIMEOUT));
+    futures::future::join_all(wait).await;
+
+    let handles = &handles;
+    let requests: Vec<(usize, Kind, usize)> = (0..handles.len())
+        .flat_map(|r| COUNTED_KINDS.map(|(kind, slot)| (r, kind, slot)))
+        .collect();
+    let totals: Vec<(usize, usize, CountAnswer)> = futures::stream::iter(requests)
+        .map(|(r, kind, slot)| async move {
+            let filter = Filter::new().kind(kind).events(ids.iter().copied());
+            (r, slot, count_once(&handles[r], filter).await)
+        })
+        .buffer_unordered(COUNT_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut answered = vec![false; handles.len()];
+    let mut unsupported = vec![false; handles.len()];
+    let mut largest = [0usize; 4];
+    for (r, slot, answer) in totals {
+        match answer {
+            CountAnswer::Count(count) => {
+                answered[r] = true;
+                largest[slot] = largest[slot].max(count);
+            }
+            CountAnswer::Unsupported => unsupported[r] = true,
+            CountAnswer::NoAnswer => {}
+        }
+    }
+
+    let mut outcome = CountOutcome {
+        answered: Vec::new(),
+        failed: Vec::new(),
+        fetch_kinds: Vec::new(),
+    };
+    for (r, relay) in handles.iter().enumerate() {
+        if answered[r] {
+            outcome.answered.push(relay.url().clone());
+        } else {
+            if unsupported[r] {
+                crate::relay_info::mark_broken(relay.url(), NIP45);
+            }
+            outcome.failed.push(relay.url().clone());
+        }
+    }
+    if outcome.answered.is_empty() {
+        return outcome;
+    }
+
+    let mut per_note: Vec<(Kind, usize)> = Vec::new();
+    for (kind, slot) in COUNTED_KINDS {
+        if let [id] = ids {
+            // A total over one note is that note's count.
+            tally.set_count(&id.to_hex(), slot, largest[slot]);
+        } else if largest[slot] < FETCH_LIMIT {
+            outcome.fetch_kinds.push(kind);
+        } else {
+            per_note.push((kind, slot));
+        }
+    }
+
+    let answered_handles: Vec<&Relay> = handles
+        .iter()
+        .filter(|r| outcome.answered.contains(r.url()))
+        .collect();
+    let answered_handles = &answered_handles;
+    let mut requests: Vec<(EventId, Kind, usize, usize)> = Vec::new();
+    for (kind, slot) in per_note {
+        for id in ids {
+            requests.extend((0..answered_handles.len()).map(|r| (*id, kind, slot, r)));
+        }
+    }
+    let counts: Vec<(EventId, usize, CountAnswer)> = futures::stream::iter(requests)
+        .map(|(id, kind, slot, r)| async move {
+            let filter = Filter::new().kind(kind).event(id);
+            (id, slot, count_once(answered_handles[r], filter).await)
+        })
+        .buffer_unordered(COUNT_CONCURRENCY)
+        .collect()
+        .await;
+    for (id, slot, answer) in counts {
+        if let CountAnswer::Count(count) = answer {
+            tally.set_count(&id.to_hex(), slot, count);
+        }
+    }
+    outcome
+}
+
+/// COUNT on the NIP-45 relays, then work out which filter still has to be
+/// fetched from which relays: zaps and the user's own interactions from the
+/// relays that answered COUNT, everything from the rest.
+pub(crate) async fn plan(
+    client: &Client,
+    ids: &[EventId],
+    user_pk: Option<PublicKey>,
+    tally: &mut InteractionTally,
+) -> Vec<(Vec<RelayUrl>, Filter)> {
+    let (count_relays, mut fetch_relays) = split_by_count_support(client).await;
+    let mut plan: Vec<(Vec<RelayUrl>, Filter)> = Vec::new();
+    if !count_relays.is_empty() {
+        let outcome = count_on_relays(client, &count_relays, ids, tally).await;
+        fetch_relays.extend(outcome.failed);
+        if !outcome.answered.is_empty() {
+            for filter in count_relay_filters(ids, user_pk, &outcome.fetch_kinds) {
+                plan.push((outcome.answered.clone(), filter));
+            }
+        }
+    }
+    if !fetch_relays.is_empty() {
+        plan.push((fetch_relays, full_filter(ids)));
+    }
+    plan
+}
+
+/// The fetches of a [`plan`], run as bus subscriptions whose events are
+/// counted but never stored: a count shouldn't fill the database with
+/// every reaction to every note on screen.
+#[derive(Default)]
+pub(crate) struct PlanFetch {
+    sub_ids: Vec<SubscriptionId>,
+    items: futures::stream::SelectAll<futures::stream::BoxStream<'static, (usize, BusItem)>>,
+    received: Vec<usize>,
+    kinds: Vec<Vec<Kind>>,
+    expected_eose: Vec<usize>,
+    eose_count: Vec<usize>,
+}
+
+impl PlanFetch {
+    /// Subscribe to every filter of `plan`, each capped at the fetch limit.
+    /// Filters that can't be subscribed are left out.
+    pub(crate) async fn start(client: &Client, plan: Vec<(Vec<RelayUrl>, Filter)>) -> Self {
+        let mut fetch = Self::default();
+        for (urls, filter) in plan {
+            let relays = urls.len();
+            let kinds: Vec<Kind> = filter.kinds.iter().flatten().copied().collect();
+            let Ok((sub_id, rx)) =
+                event_bus::subscribe_transient_to(client, urls, filter.limit(FETCH_LIMIT)).await
+            else {
+                continue;
+            };
+            fetch.add(sub_id, kinds, relays, rx);
+        }
+        fetch
+    }
+
+    fn add(
+        &mut self,
+        sub_id: SubscriptionId,
+        kinds: Vec<Kind>,
+        relays: usize,
+        rx: tokio::sync::mpsc::UnboundedReceiver<BusItem>,
+    ) {
+        let index = self.sub_ids.len();
+        self.sub_ids.push(sub_id);
+        self.received.push(0);
+        self.kinds.push(kinds);
+        self.expected_eose.push(relays);
+        self.eose_count.push(0);
+        self.items.push(
+            futures::stream::unfold(rx, move |mut rx| async move {
+                rx.recv().await.map(|item| ((index, item), rx))
+            })
+            .boxed(),
+        );
+    }
+
+    /// The next fetched event, or `None` once every relay sent EOSE or
+    /// closed its subscription.
+    pub(crate) async fn next_event(&mut self) -> Option<Box<Event>> {
+        while !self.is_complete() {
+            match self.items.next().await? {
+                (index, BusItem::Event(event)) => {
+                    self.received[index] += 1;
+                    return Some(event);
+                }
+                (index, BusItem::Eose(_) | BusItem::Closed(..)) => self.eose_count[index] += 1,
+                (_, BusItem::Lagged(_)) => {}
+            }
+        }
+        None
+    }
+
+    /// Whether every relay finished sending.
+    pub(crate) fn is_complete(&self) -> bool {
+        (0..self.sub_ids.len()).all(|i| self.is_finished(i))
+    }
+
+    fn is_finished(&self, index: usize) -> bool {
+        self.eose_count[index] >= self.expected_eose[index]
+    }
+
+    /// Kinds of the fetches some relay didn't finish sending.
+    pub(crate) fn incomplete_kinds(&self) -> Vec<Kind> {
+        (0..self.sub_ids.len())
+            .filter(|&i| !self.is_finished(i))
+            .flat_map(|i| self.kinds[i].iter().copied())
+            .collect()
+    }
+
+    /// Whether a fetch was cut off by its limit.
+    pub(crate) fn is_truncated(&self) -> bool {
+        self.received.iter().any(|n| *n >= FETCH_LIMIT)
+    }
+
+    pub(crate) async fn close(self, client: &Client) {
+        for sub_id in &self.sub_ids {
+            event_bus::unsubscribe_id(client, sub_id).await;
+        }
+    }
+}
+
+/// Aggregates for `note_ids` on the main pool: cached ones as they are, the
+/// rest counted with [`plan`] and short-lived fetches, then cached once every
+/// fetch finished.
+pub(crate) async fn count(
+    client: &Client,
+    note_ids: Vec<String>,
+    user_pk: Option<PublicKey>,
+) -> serde_json::Map<String, serde_json::Value> {
+    let (mut result, missing) = cached(&note_ids, user_pk);
+    let ids: Vec<EventId> = missing
+        .iter()
+        .filter_map(|id| EventId::from_hex(id).ok())
+        .collect();
+    if ids.is_empty() {
+        return result;
+    }
+
+    let mut tally = InteractionTally::new(missing, user_pk);
+    let plan = plan(client, &ids, user_pk, &mut tally).await;
+    let mut fetch = PlanFetch::start(client, plan).await;
+    let _ = tokio::time::timeout(FETCH_TIMEOUT, async {
+        while let Some(event) = fetch.next_event().await {
+            tally.add_event(&event);
+        }
+    })
+    .await;
+    if fetch.is_truncated() {
+        tally.set_truncated();
+    }
+    tally.set_incomplete(&fetch.incomplete_kinds());
+    let complete = fetch.is_complete();
+    fetch.close(client).await;
+
+    let fresh = tally.to_map();
+    if complete {
+        store(&fresh, user_pk);
+    }
+    result.extend(fresh);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_answers_make_tally_estimated() {
+        let keys = Keys::generate();
+        let note = EventBuilder::text_note("hello")
+            .sign_with_keys(&keys)
+            .unwrap();
+        let reaction = EventBuilder::reaction(&note, "+")
+            .sign_with_keys(&keys)
+            .unwrap();
+
+        let mut tally = InteractionTally::new(vec![note.id.to_hex()], Some(keys.public_key()));
+        assert!(tally.add_event(&reaction));
+        assert!(!tally.add_event(&reaction));
+
+        let json = tally.to_map();
+        let entry = &json[&note.id.to_hex()];
+        assert_eq!(entry["reactions"], 1);
+        assert_eq!(entry["hasReacted"], true);
+        assert_eq!(entry["exact"]["reactions"], true);
+
+        tally.set_count(&note.id.to_hex(), REACTIONS, 12);
+        let json = tally.to_map();
+        let entry = &json[&note.id.to_hex()];
+        assert_eq!(entry["reactions"], 12);
+        assert_eq!(entry["exact"]["reactions"], false);
+        assert_eq!(entry["exact"]["zaps"], true);
+    }
+
+    #[tokio::test]
+    async fn missing_eose_makes_its_kinds_estimated() {
+        let keys = Keys::generate();
+        let note = EventBuilder::text_note("hello")
+            .sign_with_keys(&keys)
+            .unwrap();
+        let reaction = EventBuilder::reaction(&note, "+")
+            .sign_with_keys(&keys)
+            .unwrap();
+        let relay = RelayUrl::parse("wss://relay.example.com").unwrap();
+
+        let mut fetch = PlanFetch::default();
+        let (counted_tx, counted_rx) = tokio::sync::mpsc::unbounded_channel();
+        fetch.add(
+            SubscriptionId::generate(),
+            vec![Kind::Reaction, Kind::Repost],
+            2,
+            counted_rx,
+        );
+        let (fetched_tx, fetched_rx) = tokio::sync::mpsc::unbounded_channel();
+        fetch.add(
+            SubscriptionId::generate(),
+            vec![Kind::ZapReceipt, Kind::TextNote],
+            1,
+            fetched_rx,
+        );
+        counted_tx.send(BusItem::Event(Box::new(reaction))).unwrap();
+        counted_tx.send(BusItem::Eose(relay.clone())).unwrap();
+        fetched_tx.send(BusItem::Eose(relay)).unwrap();
+        drop((counted_tx, fetched_tx));
+
+        let mut tally = InteractionTally::new(vec![note.id.to_hex()], None);
+        while let Some(event) = fetch.next_event().await {
+            tally.add_event(&event);
+        }
+        assert!(!fetch.is_complete());
+        assert_eq!(fetch.incomplete_kinds(), vec![Kind::Reaction, Kind::Repost]);
+        tally.set_incomplete(&fetch.incomplete_kinds());
+
+        let json = tally.to_map();
+        let entry = &json[&note.id.to_hex()];
+        assert_eq!(entry["reactions"], 1);
+        assert_eq!(entry["exact"]["reactions"], false);
+        assert_eq!(entry["exact"]["reposts"], false);
+        assert_eq!(entry["exact"]["zaps"], true);
+        assert_eq!(entry["exact"]["replies"], true);
+    }
+
+    #[test]
+    fn only_explicit_refusals_count_as_unsupported() {
+        assert!(rejects_count("unsupported: COUNT is not implemented"));
+        assert!(rejects_count("ERROR: unknown message type COUNT"));
+        assert!(!rejects_count("rate-limited: slow down"));
+        assert!(!rejects_count("error: shutting down"));
+    }
+}
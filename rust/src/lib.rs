@@ -1,3 +1,21 @@
 mod frb_generated; /* AUTO INJECTED BY flutter_rust_bridge. This line may not be accurate, and you can change it according to your needs. */
+pub(crate) mod archive;
+pub(crate) mod follow_graph;
+pub(crate) mod follower_counts;
 pub(crate) mod hybrid_database;
+pub(crate) mod interaction_counts;
+pub(crate) mod lmdb_recovery;
+pub(crate) mod negentropy_sync;
+pub(crate) mod newest_first;
+pub(crate) mod persistence_policy;
+pub(crate) mod profile_index;
+pub(crate) mod proxy;
+pub(crate) mod relay_info;
+pub(crate) mod relay_messages;
+pub(crate) mod relay_policy;
+pub(crate) mod relay_scores;
+pub(crate) mod search_index;
+pub(crate) mod search_relays;
+pub(crate) mod storage_quota;
+pub(crate) mod wot_filter;
 mod api;
@@ -0,0 +1,297 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use anyhow::{anyhow, Result};
+use heed::types::Bytes;
+use heed::{EnvFlags, EnvOpenOptions};
+use nostr_database::flatbuffers::FlatBufferDecode;
+use nostr_lmdb::NostrLMDB;
+use nostr_sdk::prelude::*;
+use serde::Serialize;
+
+const LMDB_MAP_SIZES: &[usize] = &[
+    2 * 1024 * 1024 * 1024, // 2 GB
+    1024 * 1024 * 1024,     // 1 GB
+    512 * 1024 * 1024,      // 512 MB
+    256 * 1024 * 1024,      // 256 MB
+];
+
+/// What happened when the store was last opened.
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RecoveryReport {
+    /// `opened`, `staleLockRemoved`, `recovered`, or `failed` when the open
+    /// failed for a reason other than corruption and nothing was touched.
+    action: &'static str,
+    /// Why the store couldn't be opened as it was.
+    open_error: Option<String>,
+    /// Where the unreadable store was moved; it is never deleted
+    /// automatically.
+    broken_path: Option<String>,
+    /// Events copied from the broken store into the new one.
+    salvaged: u64,
+    /// Entries that couldn't be decoded or failed signature verification.
+    unreadable: u64,
+    /// Why salvaging stopped early, if it did.
+    salvage_error: Option<String>,
+    at: u64,
+}
+
+static REPORT: OnceLock<Mutex<Option<RecoveryReport>>> = OnceLock::new();
+
+fn report_state() -> &'static Mutex<Option<RecoveryReport>> {
+    REPORT.get_or_init(|| Mutex::new(None))
+}
+
+pub(crate) fn last_report() -> Option<RecoveryReport> {
+    report_state().lock().unwrap().clone()
+}
+
+const PANIC_ERROR: &str = "LMDB panicked while opening";
+
+/// Open errors that mean the store itself is unreadable: LMDB's own
+/// corruption codes and flatbuffers decode failures. Anything else, e.g. an
+/// env already open with other options, permissions, a full disk or too
+/// little memory, says nothing about the data and must not trigger recovery.
+const CORRUPTION_ERRORS: &[&str] = &[
+    "MDB_CORRUPTED",
+    "MDB_INVALID",
+    "MDB_PAGE_NOTFOUND",
+    "MDB_VERSION_MISMATCH",
+    "Missing required field",
+    "union discriminant",
+    "Utf8 error for string",
+    "missing its null terminator",
+    "is unaligned",
+    "out of bounds",
+    "Too many tables",
+    "Apparent size too large",
+    "depth limit reached",
+    PANIC_ERROR,
+];
+
+fn is_corruption(error: &str) -> bool {
+    CORRUPTION_ERRORS
+        .iter()
+        .any(|marker| error.contains(marker))
+}
+
+/// Open the store, first with the map size it was last opened or grown
+/// with in this process, which heed requires while that env is still
+/// registered, then with each of [`LMDB_MAP_SIZES`]. Stops at the first
+/// corruption error, since a different map size won't help with that.
+fn try_open(path: &str) -> Result<(NostrLMDB, usize), String> {
+    let recorded = crate::storage_quota::map_size();
+    let sizes = std::iter::once(recorded).filter(|&size| size > 0).chain(
+        LMDB_MAP_SIZES
+            .iter()
+            .copied()
+            .filter(|&size| size != recorded),
+    );
+    let mut last_error = String::new();
+    for map_size in sizes {
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            crate::hybrid_database::lmdb_builder(path, map_size).build()
+        }));
+        match result {
+            Ok(Ok(db)) => return Ok((db, map_size)),
+            Ok(Err(e)) => last_error = e.to_string(),
+            Err(_) => last_error = PANIC_ERROR.to_string(),
+        }
+        if is_corruption(&last_error) {
+            break;
+        }
+    }
+    Err(format!("LMDB open failed: {}", last_error))
+}
+
+fn broken_path(path: &str) -> PathBuf {
+    PathBuf::from(format!("{}.broken-{}", path, Timestamp::now().as_secs()))
+}
+
+/// Unreadable stores moved aside by earlier recoveries, next to `path`.
+pub(crate) fn broken_copies(path: &str) -> Vec<PathBuf> {
+    let path = Path::new(path);
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}.broken-", name.to_string_lossy());
+    fs::read_dir(parent)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_name().to_string_lossy().starts_with(&prefix))
+                .map(|e| e.path())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Read every event out of an LMDB store without writing to it. Decoding
+/// stops at the first unreadable page; what was read up to then is kept.
+fn salvage(dir: &Path) -> (Vec<Event>, u64, Option<String>) {
+    let mut events = Vec::new();
+    let mut unreadable = 0u64;
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| -> heed::Result<()> {
+        let data_len = fs::metadata(dir.join("data.mdb"))
+            .map(|m| m.len() as usize)
+            .unwrap_or(0);
+        // SAFETY: the directory was moved aside, so nothing else has it open;
+        // that is what makes NO_LOCK safe here.
+        let env = unsafe {
+            EnvOpenOptions::new()
+                .flags(EnvFlags::READ_ONLY | EnvFlags::NO_LOCK | EnvFlags::NO_TLS)
+                .max_dbs(16)
+                .map_size(data_len.next_multiple_of(4096).max(1024 * 1024))
+                .open(dir)?
+        };
+        let rtxn = env.read_txn()?;
+        let Some(db) = env.open_database::<Bytes, Bytes>(&rtxn, None)? else {
+            return Ok(());
+        };
+        for entry in db.iter(&rtxn)? {
+            let (key, value) = entry?;
+            // The main database also holds the records of the named index
+            // databases; events are keyed by their 32-byte id.
+            if key.len() != 32 {
+                continue;
+            }
+            match Event::decode(value) {
+                Ok(event) if event.verify().is_ok() => events.push(event),
+                _ => unreadable += 1,
+            }
+        }
+        Ok(())
+    }));
+    let error = match result {
+        Ok(Ok(())) => None,
+        Ok(Err(e)) => Some(e.to_string()),
+        Err(_) => Some("LMDB panicked while reading".to_string()),
+    };
+    (events, unreadable, error)
+}
+
+/// Open the LMDB store at `path` without ever deleting data. Failures other
+/// than corruption are returned as they are. On corruption a stale
+/// `lock.mdb` is removed first. If the store still can't be opened it is
+/// moved aside, a fresh store is created in its place and
+/// whatever events can be read from the old one are copied over. The outcome
+/// is kept for [`last_report`]. Returns the store and its map size.
+pub(crate) async fn open(path: &str) -> Result<(NostrLMDB, usize)> {
+    let mut report = RecoveryReport {
+        action: "opened",
+        at: Timestamp::now().as_secs(),
+        ..Default::default()
+    };
+
+    let open_error = match try_open(path) {
+        Ok(db) => {
+            *report_state().lock().unwrap() = Some(report);
+            return Ok(db);
+        }
+        Err(e) => e,
+    };
+    report.open_error = Some(open_error.clone());
+    if !is_corruption(&open_error) {
+        report.action = "failed";
+        *report_state().lock().unwrap() = Some(report);
+        return Err(anyhow!(open_error));
+    }
+
+    let lock_file = Path::new(path).join("lock.mdb");
+    if lock_file.exists() && fs::remove_file(&lock_file).is_ok() {
+        if let Ok(db) = try_open(path) {
+            report.action = "staleLockRemoved";
+            *report_state().lock().unwrap() = Some(report);
+            return Ok(db);
+        }
+    }
+
+    let aside = broken_path(path);
+    fs::rename(path, &aside)
+        .map_err(|e| anyhow!("Failed to move unreadable database aside: {}", e))?;
+    let (db, map_size) = match try_open(path) {
+        Ok(opened) => opened,
+        Err(e) => {
+            let _ = fs::remove_dir_all(path);
+            let _ = fs::rename(&aside, path);
+            return Err(anyhow!(e));
+        }
+    };
+    report.action = "recovered";
+    report.broken_path = Some(aside.to_string_lossy().into_owned());
+
+    let dir = aside.clone();
+    let (events, unreadable, salvage_error) = tokio::task::spawn_blocking(move || salvage(&dir))
+        .await
+        .unwrap_or_else(|e| (Vec::new(), 0, Some(e.to_string())));
+    report.unreadable = unreadable;
+    report.salvage_error = salvage_error;
+    for event in &events {
+        if let Ok(SaveEventStatus::Success) = db.save_event(event).await {
+            report.salvaged += 1;
+        }
+    }
+
+    *report_state().lock().unwrap() = Some(report);
+    Ok((db, map_size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn only_unreadable_stores_are_moved_aside_and_salvaged() {
+        let root = std::env::temp_dir().join(format!("lmdb-recovery-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let good = root.join("good");
+        fs::create_dir_all(&good).unwrap();
+        let keys = Keys::generate();
+        {
+            let (db, _) = try_open(good.to_str().unwrap()).unwrap();
+            for i in 0..3 {
+                let event = EventBuilder::text_note(format!("note {}", i))
+                    .sign_with_keys(&keys)
+                    .unwrap();
+                db.save_event(&event).await.unwrap();
+            }
+        }
+        // Salvage works on a moved-aside copy, never on a store still open.
+        let copy = root.join("copy");
+        fs::create_dir_all(&copy).unwrap();
+        fs::copy(good.join("data.mdb"), copy.join("data.mdb")).unwrap();
+        let (events, unreadable, error) = salvage(&copy);
+        assert_eq!((events.len(), unreadable, error), (3, 0, None));
+
+        let broken = root.join("broken");
+        fs::create_dir_all(&broken).unwrap();
+        fs::write(broken.join("data.mdb"), vec![0xAB; 64 * 1024]).unwrap();
+        let path = broken.to_str().unwrap();
+        let _db = open(path).await.unwrap();
+
+        let report = last_report().unwrap();
+        assert_eq!(report.action, "recovered");
+        let aside = report.broken_path.unwrap();
+        assert_eq!(
+            fs::read(Path::new(&aside).join("data.mdb")).unwrap().len(),
+            64 * 1024
+        );
+        assert_eq!(broken_copies(path), vec![PathBuf::from(aside)]);
+
+        // A store that is merely open with other options is left alone.
+        let busy = root.join("busy");
+        fs::create_dir_all(&busy).unwrap();
+        let path = busy.to_str().unwrap();
+        let _open = NostrLMDB::builder(path)
+            .map_size(3 * 1024 * 1024 + 4096)
+            .build()
+            .unwrap();
+        let error = open(path).await.unwrap_err().to_string();
+        assert!(!is_corruption(&error), "{}", error);
+        assert_eq!(last_report().unwrap().action, "failed");
+        assert!(broken_copies(path).is_empty());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,483 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::api::operations::Operation;
+
+const CHECKPOINTS_FILE: &str = "sync_checkpoints.json";
+const MAX_CHECKPOINTS: usize = 2_000;
+/// Re-fetch a little before the watermark to cover clock skew and events
+/// that reached the relay late.
+const WATERMARK_OVERLAP_SECS: u64 = 120;
+const INITIAL_TIMEOUT: Duration = Duration::from_secs(5);
+const REQ_TIMEOUT: Duration = Duration::from_secs(10);
+const REQ_MAX_PAGES: usize = 10;
+/// NIP-77 negentropy; remembered as broken for relays that rejected it.
+const NIP77: u16 = 77;
+
+/// Sync progress per `(filter, relay)`.
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+struct Checkpoint {
+    /// Everything created up to this time has been synced; 0 if nothing has
+    /// been fully synced yet.
+    synced_until: u64,
+    /// Set while a REQ sync that stopped early still has to page back to
+    /// `synced_until`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    resume: Option<Resume>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+struct Resume {
+    /// Oldest `created_at` reached; paging continues below it.
+    until: u64,
+    /// When the interrupted sync started. The watermark moves here once
+    /// paging reaches it.
+    started_at: u64,
+}
+
+#[derive(Default)]
+struct CheckpointState {
+    path: Option<PathBuf>,
+    checkpoints: HashMap<String, Checkpoint>,
+}
+
+static CHECKPOINTS: OnceLock<Mutex<CheckpointState>> = OnceLock::new();
+
+fn checkpoint_state() -> &'static Mutex<CheckpointState> {
+    CHECKPOINTS.get_or_init(|| Mutex::new(CheckpointState::default()))
+}
+
+/// Load the persisted checkpoints stored next to the database, if any.
+pub(crate) fn load(db_path: Option<&str>) {
+    let path = db_path.map(|p| {
+        PathBuf::from(p)
+            .parent()
+            .unwrap_or_else(|| std::path::Path::new("."))
+            .join(CHECKPOINTS_FILE)
+    });
+    let checkpoints = path
+        .as_ref()
+        .and_then(|p| std::fs::read(p).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+    let mut state = checkpoint_state().lock().unwrap();
+    state.path = path;
+    state.checkpoints = checkpoints;
+}
+
+fn save(state: &CheckpointState) {
+    if let (Some(path), Ok(json)) = (state.path.as_ref(), serde_json::to_vec(&state.checkpoints)) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+/// Identifies a filter regardless of its time window, so consecutive syncs
+/// of "the same" feed share checkpoints.
+fn filter_key(filter: &Filter) -> String {
+    let mut filter = filter.clone();
+    filter.since = None;
+    filter.until = None;
+    filter.limit = None;
+    let digest = Sha256::digest(filter.as_json().as_bytes());
+    hex::encode(&digest[..12])
+}
+
+fn checkpoint_key(filter_key: &str, relay_url: &RelayUrl) -> String {
+    format!("{}|{}", filter_key, relay_url)
+}
+
+fn checkpoint(filter_key: &str, relay_url: &RelayUrl) -> Checkpoint {
+    let state = checkpoint_state().lock().unwrap();
+    state
+        .checkpoints
+        .get(&checkpoint_key(filter_key, relay_url))
+        .copied()
+        .unwrap_or_default()
+}
+
+fn set_checkpoint(filter_key: &str, relay_url: &RelayUrl, checkpoint: Checkpoint) {
+    let mut state = checkpoint_state().lock().unwrap();
+    state
+        .checkpoints
+        .insert(checkpoint_key(filter_key, relay_url), checkpoint);
+    if state.checkpoints.len() > MAX_CHECKPOINTS {
+        let mut entries: Vec<(String, u64)> = state
+            .checkpoints
+            .iter()
+            .map(|(k, c)| (k.clone(), c.synced_until))
+            .collect();
+        entries.sort_by_key(|(_, at)| *at);
+        let excess = entries.len() - MAX_CHECKPOINTS;
+        for (key, _) in entries.into_iter().take(excess) {
+            state.checkpoints.remove(&key);
+        }
+    }
+    save(&state);
+}
+
+pub(crate) fn checkpoints_json() -> serde_json::Value {
+    let state = checkpoint_state().lock().unwrap();
+    let list: Vec<serde_json::Value> = state
+        .checkpoints
+        .iter()
+        .map(|(key, c)| {
+            let (filter, relay) = key.split_once('|').unwrap_or((key.as_str(), ""));
+            serde_json::json!({
+                "filter": filter,
+                "relay": relay,
+                "syncedUntil": c.synced_until,
+                "resumeUntil": c.resume.map(|r| r.until),
+            })
+        })
+        .collect();
+    serde_json::Value::Array(list)
+}
+
+pub(crate) fn reset_checkpoints() {
+    let mut state = checkpoint_state().lock().unwrap();
+    state.checkpoints.clear();
+    save(&state);
+}
+
+/// The caller's filter narrowed to start just before the relay's watermark.
+fn resume_filter(filter: &Filter, watermark: Option<u64>) -> Filter {
+    let Some(watermark) = watermark else {
+        return filter.clone();
+    };
+    let resume = Timestamp::from(watermark.saturating_sub(WATERMARK_OVERLAP_SECS));
+    match filter.since {
+        Some(since) if since >= resume => filter.clone(),
+        _ => filter.clone().since(resume),
+    }
+}
+
+/// Outcome of syncing one relay.
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RelaySyncResult {
+    url: String,
+    /// `negentropy` or `req`.
+    method: &'static str,
+    /// `running`, `ok`, `timeout`, `error`, `cancelled` or `timedOut` (the
+    /// whole operation ran out of time).
+    status: &'static str,
+    received: usize,
+    /// Whether the whole window was synced. REQ paging can stop early, in
+    /// which case the next sync continues where it left off.
+    complete: bool,
+    sent: usize,
+    local: usize,
+    remote: usize,
+    resumed_from: Option<u64>,
+    error: Option<String>,
+    /// Why negentropy was skipped or abandoned before falling back to REQ.
+    negentropy_error: Option<String>,
+}
+
+#[derive(Default)]
+pub(crate) struct SyncReport {
+    pub(crate) relays: Vec<RelaySyncResult>,
+}
+
+impl SyncReport {
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let sum = |f: fn(&RelaySyncResult) -> usize| self.relays.iter().map(f).sum::<usize>();
+        let count = |status: &str| self.relays.iter().filter(|r| r.status == status).count();
+        serde_json::json!({
+            "received": sum(|r| r.received),
+            "sent": sum(|r| r.sent),
+            "local": sum(|r| r.local),
+            "remote": sum(|r| r.remote),
+            "ok": count("ok"),
+            "timedOut": count("timeout"),
+            "failed": count("error"),
+            "relays": self.relays,
+        })
+    }
+}
+
+/// Errors meaning the relay doesn't speak negentropy at all, as opposed to
+/// a sync that started and then failed.
+fn is_unsupported(error: &nostr_sdk::pool::relay::Error) -> bool {
+    use nostr_sdk::pool::relay::Error;
+    matches!(
+        error,
+        Error::NegentropyNotSupported
+            | Error::UnknownNegentropyError
+            | Error::Negentropy(_)
+            | Error::RelayMessage(_)
+    )
+}
+
+/// How far a REQ sync got.
+struct ReqProgress {
+    received: usize,
+    /// Paging reached the filter's `since`, or the relay had nothing more.
+    complete: bool,
+    /// Oldest `created_at` received.
+    oldest: Option<Timestamp>,
+}
+
+/// REQ-based sync: page backwards from `until` until the relay has nothing
+/// new, paging reaches `since`, or the page cap is hit. With a `limit` only
+/// one page is fetched.
+async fn req_sync(
+    relay: &Relay,
+    filter: Filter,
+    mut until: Option<Timestamp>,
+) -> Result<ReqProgress, String> {
+    let mut seen: HashSet<EventId> = HashSet::new();
+    let mut progress = ReqProgress {
+        received: 0,
+        complete: false,
+        oldest: None,
+    };
+    for _ in 0..REQ_MAX_PAGES {
+        let mut page = filter.clone();
+        if let Some(until) = until {
+            page = page.until(until);
+        }
+        let events = relay
+            .fetch_events(page, REQ_TIMEOUT, ReqExitPolicy::ExitOnEOSE)
+            .await
+            .map_err(|e| e.to_string())?;
+        let before = seen.len();
+        let mut oldest: Option<Timestamp> = None;
+        for event in events.iter() {
+            seen.insert(event.id);
+            oldest = Some(oldest.map_or(event.created_at, |o: Timestamp| o.min(event.created_at)));
+        }
+        progress.received = seen.len();
+        if seen.len() == before {
+            progress.complete = true;
+            break;
+        }
+        let Some(oldest) = oldest else {
+            break;
+        };
+        progress.oldest = Some(oldest);
+        if let Some(limit) = filter.limit {
+            progress.complete = events.len() < limit;
+            break;
+        }
+        if filter.since.is_some_and(|since| oldest <= since) {
+            progress.complete = true;
+            break;
+        }
+        until = Some(oldest);
+    }
+    Ok(progress)
+}
+
+/// The checkpoint after a REQ sync started at `started_at` from
+/// `checkpoint` got as far as `progress`: the watermark only moves once
+/// paging is complete, otherwise the oldest event reached is kept to
+/// continue from.
+fn after_req(checkpoint: Checkpoint, started_at: u64, progress: &ReqProgress) -> Checkpoint {
+    let walk_started_at = checkpoint.resume.map_or(started_at, |r| r.started_at);
+    match (progress.complete, progress.oldest) {
+        (true, _) => Checkpoint {
+            synced_until: walk_started_at,
+            resume: None,
+        },
+        (false, Some(oldest)) => Checkpoint {
+            resume: Some(Resume {
+                until: oldest.as_secs(),
+                started_at: walk_started_at,
+            }),
+            ..checkpoint
+        },
+        (false, None) => checkpoint,
+    }
+}
+
+async fn sync_relay<F>(
+    relay: &Relay,
+    filter: &Filter,
+    key: &str,
+    op: &Operation,
+    on_relay: &F,
+) -> RelaySyncResult
+where
+    F: Fn(&RelaySyncResult),
+{
+    let url = relay.url().clone();
+    let started_at = Timestamp::now().as_secs();
+    let checkpoint = checkpoint(key, &url);
+    let resumed_from = (checkpoint.synced_until > 0).then_some(checkpoint.synced_until);
+    let filter = resume_filter(filter, resumed_from);
+    let mut result = RelaySyncResult {
+        url: url.to_string(),
+        method: "negentropy",
+        status: "running",
+        resumed_from,
+        ..Default::default()
+    };
+
+    let skip_negentropy = crate::relay_info::is_broken(&url, NIP77);
+    if !skip_negentropy {
+        on_relay(&result);
+        let opts = SyncOptions::default().initial_timeout(INITIAL_TIMEOUT);
+        match op.run(relay.sync(filter.clone(), &opts)).await {
+            None => {
+                result.status = op.outcome();
+                return result;
+            }
+            Some(Ok(recon)) => {
+                result.status = "ok";
+                result.complete = true;
+                result.received = recon.received.len();
+                result.sent = recon.sent.len();
+                result.local = recon.local.len();
+                result.remote = recon.remote.len();
+                let synced = Checkpoint {
+                    synced_until: started_at,
+                    resume: None,
+                };
+                set_checkpoint(key, &url, synced);
+                return result;
+            }
+            Some(Err(nostr_sdk::pool::relay::Error::Timeout)) => {
+                result.negentropy_error = Some("timeout".to_string());
+            }
+            Some(Err(e)) if is_unsupported(&e) => {
+                crate::relay_info::mark_broken(&url, NIP77);
+                result.negentropy_error = Some(e.to_string());
+            }
+            Some(Err(e)) => {
+                result.status = "error";
+                result.error = Some(e.to_string());
+                return result;
+            }
+        }
+    } else {
+        result.negentropy_error = Some("not supported".to_string());
+    }
+
+    result.method = "req";
+    on_relay(&result);
+    // Continue an interrupted walk below where it stopped.
+    let until = match (checkpoint.resume, filter.until) {
+        (Some(resume), Some(until)) => Some(until.min(Timestamp::from(resume.until))),
+        (Some(resume), None) => Some(Timestamp::from(resume.until)),
+        (None, until) => until,
+    };
+    match op.run(req_sync(relay, filter, until)).await {
+        None => result.status = op.outcome(),
+        Some(Ok(progress)) => {
+            result.status = "ok";
+            result.received = progress.received;
+            result.complete = progress.complete;
+            set_checkpoint(key, &url, after_req(checkpoint, started_at, &progress));
+        }
+        Some(Err(e)) if e.contains("timeout") => {
+            result.status = "timeout";
+            result.error = Some(e);
+        }
+        Some(Err(e)) => {
+            result.status = "error";
+            result.error = Some(e);
+        }
+    }
+    result
+}
+
+/// Sync `filter` with every read relay in parallel: negentropy where the
+/// relay supports it, paged REQ elsewhere. Each relay resumes from its own
+/// watermark for this filter. `on_relay` sees each relay when it starts a
+/// method (status `running`) and its result as soon as it finishes.
+pub(crate) async fn sync<F>(
+    client: &Client,
+    filter: Filter,
+    op: &Operation,
+    on_relay: F,
+) -> SyncReport
+where
+    F: Fn(&RelaySyncResult),
+{
+    let key = filter_key(&filter);
+    let relays: Vec<Relay> = client
+        .relays()
+        .await
+        .into_values()
+        .filter(|r| r.flags().has(RelayServiceFlags::READ, FlagCheck::All))
+        .collect();
+    let total = relays.len() as u64;
+    op.progress(0, Some(total), "sync");
+
+    let mut pending: futures::stream::FuturesUnordered<_> = relays
+        .iter()
+        .map(|relay| sync_relay(relay, &filter, &key, op, &on_relay))
+        .collect();
+
+    let mut report = SyncReport::default();
+    while let Some(result) = futures::StreamExt::next(&mut pending).await {
+        on_relay(&result);
+        report.relays.push(result);
+        op.progress(report.relays.len() as u64, Some(total), "sync");
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watermark_narrows_but_never_widens_the_filter() {
+        let filter = Filter::new()
+            .kind(Kind::TextNote)
+            .since(Timestamp::from(1_000));
+        assert_eq!(
+            resume_filter(&filter, None).since,
+            Some(Timestamp::from(1_000))
+        );
+        assert_eq!(
+            resume_filter(&filter, Some(10_000)).since,
+            Some(Timestamp::from(10_000 - WATERMARK_OVERLAP_SECS))
+        );
+        assert_eq!(
+            resume_filter(&filter, Some(500)).since,
+            Some(Timestamp::from(1_000))
+        );
+
+        let windowed = filter.clone().since(Timestamp::from(50)).limit(10);
+        assert_eq!(filter_key(&filter), filter_key(&windowed));
+    }
+
+    #[test]
+    fn incomplete_req_paging_keeps_a_cursor_instead_of_the_watermark() {
+        let synced = Checkpoint {
+            synced_until: 1_000,
+            resume: None,
+        };
+        let stopped = ReqProgress {
+            received: 500,
+            complete: false,
+            oldest: Some(Timestamp::from(5_000)),
+        };
+        let partial = after_req(synced, 9_000, &stopped);
+        assert_eq!(partial.synced_until, 1_000);
+        let resume = partial.resume.unwrap();
+        assert_eq!((resume.until, resume.started_at), (5_000, 9_000));
+
+        // A later run that reaches the watermark moves it to when the
+        // interrupted walk started, not to its own start.
+        let finished = ReqProgress {
+            received: 20,
+            complete: true,
+            oldest: Some(Timestamp::from(900)),
+        };
+        let done = after_req(partial, 12_000, &finished);
+        assert_eq!(done.synced_until, 9_000);
+        assert!(done.resume.is_none());
+    }
+}
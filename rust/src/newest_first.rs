@@ -0,0 +1,72 @@
+use std::collections::HashSet;
+
+use nostr_database::DatabaseError;
+use nostr_sdk::prelude::*;
+
+/// Pages through stored events matching a filter, newest first. Pages
+/// overlap on their oldest timestamp, so events sharing one aren't skipped.
+pub(crate) struct NewestFirst {
+    filter: Filter,
+    page_size: usize,
+    until: Option<Timestamp>,
+    /// Ids already returned at `until`.
+    boundary: HashSet<EventId>,
+}
+
+impl NewestFirst {
+    /// Start at `until`, or at the newest event.
+    pub(crate) fn new(filter: Filter, page_size: usize, until: Option<Timestamp>) -> Self {
+        Self {
+            filter,
+            page_size,
+            until,
+            boundary: HashSet::new(),
+        }
+    }
+
+    /// Timestamp the next page starts at, for resuming later.
+    pub(crate) fn until(&self) -> Option<Timestamp> {
+        self.until
+    }
+
+    /// The next page of events not returned yet, or `None` at the end.
+    pub(crate) async fn next_page(
+        &mut self,
+        client: &Client,
+    ) -> Result<Option<Vec<Event>>, DatabaseError> {
+        loop {
+            let mut page = self.filter.clone().limit(self.page_size);
+            if let Some(until) = self.until {
+                page = page.until(until);
+            }
+            let events = client.database().query(page).await?;
+            let Some(oldest) = events.iter().map(|e| e.created_at).min() else {
+                return Ok(None);
+            };
+            let fresh: Vec<Event> = events
+                .into_iter()
+                .filter(|e| !self.boundary.contains(&e.id))
+                .collect();
+            if fresh.is_empty() {
+                // A whole page shares one timestamp: step past it.
+                if oldest.as_secs() == 0 {
+                    return Ok(None);
+                }
+                self.until = Some(Timestamp::from(oldest.as_secs() - 1));
+                self.boundary.clear();
+                continue;
+            }
+            if self.until != Some(oldest) {
+                self.boundary.clear();
+            }
+            self.boundary.extend(
+                fresh
+                    .iter()
+                    .filter(|e| e.created_at == oldest)
+                    .map(|e| e.id),
+            );
+            self.until = Some(oldest);
+            return Ok(Some(fresh));
+        }
+    }
+}
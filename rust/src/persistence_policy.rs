@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const POLICY_FILE: &str = "persistence_policy.json";
+const RETENTION_INTERVAL: Duration = Duration::from_secs(6 * 3600);
+
+/// Which kinds `HybridDatabase` keeps in LMDB, how big the memory tier may
+/// grow and how long each kind is kept.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct PersistencePolicy {
+    /// Kinds stored in LMDB; everything else lives in memory only.
+    persistent_kinds: Vec<u16>,
+    /// Inclusive `[from, to]` kind ranges stored in LMDB.
+    persistent_ranges: Vec<(u16, u16)>,
+    /// Events kept in the memory tier.
+    memory_max_events: usize,
+    /// Days to keep events of a kind, keyed by kind. Older events are
+    /// refused on save and pruned periodically.
+    retention_days: BTreeMap<u16, u32>,
+}
+
+impl Default for PersistencePolicy {
+    fn default() -> Self {
+        Self {
+            persistent_kinds: vec![
+                0,    // Metadata
+                1,    // Text note
+                3,    // Contact list
+                5,    // Deletion
+                6,    // Repost
+                7,    // Reaction
+                8,    // Badge award
+                16,   // Generic repost
+                1059, // Gift wrap (NIP-17 DM)
+                1111, // Comment (NIP-22)
+                9735, // Zap receipt
+            ],
+            persistent_ranges: vec![
+                (10_000, 19_999), // Replaceable lists: mutes, pins, relays, bookmarks...
+                (30_000, 39_999), // Addressable: follow sets, articles, badges...
+            ],
+            memory_max_events: 50_000,
+            retention_days: BTreeMap::new(),
+        }
+    }
+}
+
+impl PersistencePolicy {
+    pub(crate) fn persists(&self, kind: Kind) -> bool {
+        let kind = kind.as_u16();
+        self.persistent_kinds.contains(&kind)
+            || self
+                .persistent_ranges
+                .iter()
+                .any(|(from, to)| (*from..=*to).contains(&kind))
+    }
+
+    /// This policy with the top-level fields present in `update_json`
+    /// replaced, e.g. `{"persistentKinds": [...]}` leaves the rest alone.
+    fn merged(&self, update_json: &str) -> Result<Self> {
+        let update: serde_json::Value = serde_json::from_str(update_json)?;
+        let serde_json::Value::Object(update) = update else {
+            return Err(anyhow!("Persistence policy must be a JSON object"));
+        };
+        let mut merged = serde_json::to_value(self)?;
+        for (key, value) in update {
+            if merged.get(&key).is_none() {
+                return Err(anyhow!("Unknown persistence policy field: {}", key));
+            }
+            merged[key] = value;
+        }
+        let policy: Self = serde_json::from_value(merged)?;
+        if policy.persistent_ranges.iter().any(|(from, to)| from > to) {
+            return Err(anyhow!("Kind range starts after it ends"));
+        }
+        if policy.memory_max_events == 0 {
+            return Err(anyhow!("memoryMaxEvents must be greater than 0"));
+        }
+        Ok(policy)
+    }
+
+    fn retention_cutoff(&self, kind: Kind) -> Option<Timestamp> {
+        self.retention_days.get(&kind.as_u16()).map(|days| {
+            Timestamp::from(
+                Timestamp::now()
+                    .as_secs()
+                    .saturating_sub(*days as u64 * 86_400),
+            )
+        })
+    }
+}
+
+#[derive(Default)]
+struct PolicyState {
+    path: Option<PathBuf>,
+    policy: PersistencePolicy,
+}
+
+static POLICY: OnceLock<RwLock<PolicyState>> = OnceLock::new();
+static RETENTION_TASK: OnceLock<Mutex<Option<tokio::task::JoinHandle<()>>>> = OnceLock::new();
+
+fn policy_state() -> &'static RwLock<PolicyState> {
+    POLICY.get_or_init(|| RwLock::new(PolicyState::default()))
+}
+
+/// Load the persisted policy stored next to the database, if any. Must run
+/// before the database is built, which sizes its memory tier from it.
+pub(crate) fn load(db_path: Option<&str>) {
+    let path = db_path.map(|p| {
+        PathBuf::from(p)
+            .parent()
+            .unwrap_or_else(|| std::path::Path::new("."))
+            .join(POLICY_FILE)
+    });
+    let policy = path
+        .as_ref()
+        .and_then(|p| std::fs::read(p).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+    let mut state = policy_state().write().unwrap();
+    state.path = path;
+    state.policy = policy;
+}
+
+pub(crate) fn policy() -> PersistencePolicy {
+    policy_state().read().unwrap().policy.clone()
+}
+
+pub(crate) fn is_persistent(kind: Kind) -> bool {
+    policy_state().read().unwrap().policy.persists(kind)
+}
+
+pub(crate) fn memory_max_events() -> usize {
+    policy_state().read().unwrap().policy.memory_max_events
+}
+
+/// Events of `kind` created before this are past their retention.
+pub(crate) fn retention_cutoff(kind: Kind) -> Option<Timestamp> {
+    policy_state().read().unwrap().policy.retention_cutoff(kind)
+}
+
+/// Apply and persist a partial policy update; see [`PersistencePolicy`].
+pub(crate) fn update(update_json: &str) -> Result<PersistencePolicy> {
+    let mut state = policy_state().write().unwrap();
+    let policy = state.policy.merged(update_json)?;
+    if let Some(path) = state.path.as_ref() {
+        std::fs::write(path, serde_json::to_vec_pretty(&policy)?)?;
+    }
+    state.policy = policy.clone();
+    Ok(policy)
+}
+
+/// Delete stored events past their kind's retention. Returns how many.
+pub(crate) async fn prune_expired(client: &Client) -> Result<usize> {
+    let retention: Vec<u16> = policy().retention_days.keys().copied().collect();
+    let database = client.database();
+    let mut pruned = 0;
+    for kind in retention.into_iter().map(Kind::from) {
+        let Some(cutoff) = retention_cutoff(kind) else {
+            continue;
+        };
+        let filter = Filter::new().kind(kind).until(cutoff);
+        let count = database.count(filter.clone()).await?;
+        if count > 0 {
+            database.delete(filter).await?;
+            pruned += count;
+        }
+    }
+    Ok(pruned)
+}
+
+/// Prune expired events now and every few hours.
+pub(crate) fn start(client: &Client) {
+    let client = client.clone();
+    let handle = tokio::spawn(async move {
+        let mut interval = tokio::time::interval(RETENTION_INTERVAL);
+        loop {
+            interval.tick().await;
+            let _ = prune_expired(&client).await;
+        }
+    });
+    let task = RETENTION_TASK.get_or_init(|| Mutex::new(None));
+    if let Some(previous) = task.lock().unwrap().replace(handle) {
+        previous.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn updates_replace_only_the_given_fields() {
+        let defaults = PersistencePolicy::default();
+        assert!(defaults.persists(Kind::from(1111)));
+        assert!(defaults.persists(Kind::from(30_009)));
+        assert!(!defaults.persists(Kind::from(20_000)));
+
+        let policy = defaults
+            .merged(r#"{"persistentKinds": [20000], "retentionDays": {"7": 30}}"#)
+            .unwrap();
+        assert!(policy.persists(Kind::from(20_000)));
+        assert!(!policy.persists(Kind::TextNote));
+        assert!(policy.persists(Kind::from(10_002)));
+        assert_eq!(policy.memory_max_events, defaults.memory_max_events);
+        assert!(policy.retention_cutoff(Kind::Reaction).is_some());
+        assert!(policy.retention_cutoff(Kind::TextNote).is_none());
+
+        assert!(defaults
+            .merged(r#"{"persistentRanges": [[5, 1]]}"#)
+            .is_err());
+        assert!(defaults.merged(r#"{"memoryMaxEvents": 0}"#).is_err());
+        assert!(defaults.merged(r#"{"persistentKindz": []}"#).is_err());
+    }
+}
@@ -0,0 +1,391 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, OnceLock, RwLock};
+
+use anyhow::Result;
+use nostr_sdk::prelude::*;
+
+use crate::newest_first::NewestFirst;
+
+const BUILD_PAGE: usize = 2_000;
+/// About text is searchable, but only its first words are indexed.
+const MAX_ABOUT_TERMS: usize = 64;
+/// Candidates ranked before the best ones are loaded from the database.
+const MAX_CANDIDATES: usize = 500;
+
+const NAME: u8 = 1;
+const DISPLAY_NAME: u8 = 2;
+const NIP05: u8 = 4;
+const ABOUT: u8 = 8;
+
+fn field_weight(field: u8) -> f64 {
+    match field {
+        NAME => 10.0,
+        DISPLAY_NAME => 9.0,
+        NIP05 => 7.0,
+        _ => 2.0,
+    }
+}
+
+fn terms(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(|term| term.to_lowercase())
+}
+
+/// `name@domain` is searchable by name, or by the domain's first label
+/// for `_@domain`.
+fn nip05_terms(nip05: &str) -> Vec<String> {
+    let (local, domain) = nip05.split_once('@').unwrap_or(("_", nip05));
+    let part = if local == "_" {
+        domain.split('.').next().unwrap_or("")
+    } else {
+        local
+    };
+    terms(part).collect()
+}
+
+/// Edit distance between `a` and `b`, counting a swap of neighbouring
+/// letters as one edit, or `None` if it exceeds `max`.
+fn bounded_distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut d = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d = d.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = d;
+        }
+    }
+    Some(rows[a.len()][b.len()]).filter(|&d| d <= max)
+}
+
+/// Typos tolerated in a query word of `len` characters.
+fn max_typos(len: usize) -> usize {
+    match len {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+struct Entry {
+    created_at: Timestamp,
+    terms: Vec<String>,
+    /// Lowercased name and display name, for whole-name matches.
+    names: Vec<String>,
+}
+
+/// Term index over every stored profile's name, display name, NIP-05 and
+/// the start of its about text.
+#[derive(Default)]
+pub(crate) struct ProfileIndex {
+    entries: HashMap<PublicKey, Entry>,
+    /// Term → profiles using it, with the fields it appears in.
+    terms: BTreeMap<String, HashMap<PublicKey, u8>>,
+}
+
+impl ProfileIndex {
+    /// Index `event` unless a newer profile of its author is indexed.
+    fn insert(&mut self, event: &Event) {
+        if self
+            .entries
+            .get(&event.pubkey)
+            .is_some_and(|entry| entry.created_at >= event.created_at)
+        {
+            return;
+        }
+        let Ok(metadata) = Metadata::from_json(&event.content) else {
+            return;
+        };
+        self.remove(&event.pubkey);
+
+        let mut fields: HashMap<String, u8> = HashMap::new();
+        let mut add = |terms: Vec<String>, field: u8| {
+            for term in terms {
+                *fields.entry(term).or_default() |= field;
+            }
+        };
+        let name = metadata.name.as_deref().unwrap_or("");
+        let display_name = metadata.display_name.as_deref().unwrap_or("");
+        add(terms(name).collect(), NAME);
+        add(terms(display_name).collect(), DISPLAY_NAME);
+        add(nip05_terms(metadata.nip05.as_deref().unwrap_or("")), NIP05);
+        add(
+            terms(metadata.about.as_deref().unwrap_or(""))
+                .take(MAX_ABOUT_TERMS)
+                .collect(),
+            ABOUT,
+        );
+
+        for (term, field) in &fields {
+            self.terms
+                .entry(term.clone())
+                .or_default()
+                .insert(event.pubkey, *field);
+        }
+        let names = [name, display_name]
+            .into_iter()
+            .filter(|n| !n.is_empty())
+            .map(|n| n.to_lowercase())
+            .collect();
+        self.entries.insert(
+            event.pubkey,
+            Entry {
+                created_at: event.created_at,
+                terms: fields.into_keys().collect(),
+                names,
+            },
+        );
+    }
+
+    fn remove(&mut self, pubkey: &PublicKey) {
+        let Some(entry) = self.entries.remove(pubkey) else {
+            return;
+        };
+        for term in entry.terms {
+            if let Some(profiles) = self.terms.get_mut(&term) {
+                profiles.remove(pubkey);
+                if profiles.is_empty() {
+                    self.terms.remove(&term);
+                }
+            }
+        }
+    }
+
+    /// Best score of each profile for one query word: exact term matches
+    /// beat prefixes, which beat typos, weighted by the field matched.
+    fn word_scores(&self, word: &str) -> HashMap<PublicKey, f64> {
+        let mut scores: HashMap<PublicKey, f64> = HashMap::new();
+        let mut add = |profiles: &HashMap<PublicKey, u8>, quality: f64, fields: u8| {
+            for (pubkey, mask) in profiles {
+                let weight = [NAME, DISPLAY_NAME, NIP05, ABOUT]
+                    .into_iter()
+                    .filter(|field| mask & field & fields != 0)
+                    .map(field_weight)
+                    .fold(0.0, f64::max);
+                let score = weight * quality;
+                if score > 0.0 {
+                    let best = scores.entry(*pubkey).or_default();
+                    *best = best.max(score);
+                }
+            }
+        };
+
+        let word_len = word.chars().count();
+        for (term, profiles) in self.terms.range(word.to_string()..) {
+            if !term.starts_with(word) {
+                break;
+            }
+            let quality = if term == word {
+                1.0
+            } else {
+                0.5 + 0.3 * word_len as f64 / term.chars().count() as f64
+            };
+            add(profiles, quality, u8::MAX);
+        }
+
+        // Typos in names only, and not in the first letter, which keeps the
+        // scan to one slice of the term index.
+        let typos = max_typos(word_len);
+        let Some(first) = word.chars().next().filter(|_| typos > 0) else {
+            return scores;
+        };
+        let word_chars: Vec<char> = word.chars().collect();
+        for (term, profiles) in self.terms.range(first.to_string()..) {
+            if !term.starts_with(first) {
+                break;
+            }
+            if term.starts_with(word) {
+                continue;
+            }
+            let term_chars: Vec<char> = term.chars().collect();
+            let compared = &term_chars[..term_chars.len().min(word_chars.len() + typos)];
+            let distance = bounded_distance(&word_chars, compared, typos).or_else(|| {
+                // A typo in a prefix of a longer name.
+                let prefix = &term_chars[..term_chars.len().min(word_chars.len())];
+                bounded_distance(&word_chars, prefix, typos)
+            });
+            if let Some(distance) = distance {
+                let quality = if distance <= 1 { 0.45 } else { 0.25 };
+                add(profiles, quality, NAME | DISPLAY_NAME | NIP05);
+            }
+        }
+        scores
+    }
+
+    /// Profiles matching every word of `query`, by match quality alone.
+    fn matches(&self, query: &str) -> HashMap<PublicKey, f64> {
+        let words: Vec<String> = terms(query).collect();
+        let Some((first, rest)) = words.split_first() else {
+            return HashMap::new();
+        };
+        let mut scores = self.word_scores(first);
+        for word in rest {
+            let word_scores = self.word_scores(word);
+            scores.retain(|pubkey, score| match word_scores.get(pubkey) {
+                Some(s) => {
+                    *score += s;
+                    true
+                }
+                None => false,
+            });
+        }
+        let whole = query.trim().to_lowercase();
+        for (pubkey, score) in scores.iter_mut() {
+            if self.entries[pubkey].names.contains(&whole) {
+                *score += 5.0;
+            }
+        }
+        scores
+    }
+}
+
+static INDEX: OnceLock<RwLock<ProfileIndex>> = OnceLock::new();
+static BUILD_TASK: OnceLock<Mutex<Option<tokio::task::JoinHandle<()>>>> = OnceLock::new();
+
+fn index() -> &'static RwLock<ProfileIndex> {
+    INDEX.get_or_init(|| RwLock::new(ProfileIndex::default()))
+}
+
+/// Index a newly saved profile.
+pub(crate) fn index_event(event: &Event) {
+    if event.kind == Kind::Metadata {
+        index().write().unwrap().insert(event);
+    }
+}
+
+/// Index every stored profile, newest first.
+async fn build(client: &Client) -> Result<()> {
+    let filter = Filter::new().kind(Kind::Metadata);
+    let mut pages = NewestFirst::new(filter, BUILD_PAGE, None);
+    while let Some(events) = pages.next_page(client).await? {
+        let mut index = index().write().unwrap();
+        for event in &events {
+            index.insert(event);
+        }
+    }
+    Ok(())
+}
+
+/// Build the index from the database in the background.
+pub(crate) fn start(client: &Client) {
+    *index().write().unwrap() = ProfileIndex::default();
+    let client = client.clone();
+    let handle = tokio::spawn(async move {
+        let _ = build(&client).await;
+    });
+    let task = BUILD_TASK.get_or_init(|| Mutex::new(None));
+    if let Some(previous) = task.lock().unwrap().replace(handle) {
+        previous.abort();
+    }
+}
+
+/// Score bonus for closeness to the signed-in user in the follow graph.
+async fn proximity_boosts(client: &Client, pubkeys: &[PublicKey]) -> Vec<f64> {
+    let viewer = match client.signer().await {
+        Ok(signer) => signer.get_public_key().await.ok(),
+        Err(_) => None,
+    };
+    let Some(viewer) = viewer else {
+        return vec![0.0; pubkeys.len()];
+    };
+    crate::follow_graph::trust(viewer, pubkeys)
+        .into_iter()
+        .map(|t| match t.distance {
+            Some(0) => 2.0,
+            _ => 6.0 * t.trust,
+        })
+        .collect()
+}
+
+/// Profiles matching `query`, best first: match quality plus closeness to
+/// the signed-in user in the follow graph. Profiles no longer stored are
+/// dropped from the index.
+pub(crate) async fn search(client: &Client, query: &str, limit: usize) -> Result<Vec<Event>> {
+    let mut ranked: Vec<(PublicKey, f64)> =
+        index().read().unwrap().matches(query).into_iter().collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranked.truncate(MAX_CANDIDATES);
+
+    let pubkeys: Vec<PublicKey> = ranked.iter().map(|(pk, _)| *pk).collect();
+    let boosts = proximity_boosts(client, &pubkeys).await;
+    for ((_, score), boost) in ranked.iter_mut().zip(boosts) {
+        *score += boost;
+    }
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let mut profiles: HashMap<PublicKey, Event> = client
+        .database()
+        .query(Filter::new().kind(Kind::Metadata).authors(pubkeys))
+        .await?
+        .into_iter()
+        .map(|event| (event.pubkey, event))
+        .collect();
+    let mut results = Vec::new();
+    let mut missing = Vec::new();
+    for (pubkey, _) in ranked {
+        match profiles.remove(&pubkey) {
+            Some(event) if results.len() < limit => results.push(event),
+            Some(_) => {}
+            None => missing.push(pubkey),
+        }
+    }
+    if !missing.is_empty() {
+        let mut index = index().write().unwrap();
+        for pubkey in &missing {
+            index.remove(pubkey);
+        }
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(index: &mut ProfileIndex, json: &str) -> PublicKey {
+        let keys = Keys::generate();
+        let event = EventBuilder::new(Kind::Metadata, json)
+            .sign_with_keys(&keys)
+            .unwrap();
+        index.insert(&event);
+        keys.public_key()
+    }
+
+    #[test]
+    fn prefix_and_typo_matches_rank_below_exact_names() {
+        let mut index = ProfileIndex::default();
+        let jack = profile(&mut index, r#"{"name": "jack", "about": "bitcoin"}"#);
+        let jackson = profile(&mut index, r#"{"display_name": "Jackson Five"}"#);
+        let about = profile(&mut index, r#"{"name": "bob", "about": "I know jack"}"#);
+        let nip05 = profile(&mut index, r#"{"name": "x", "nip05": "jack@example.com"}"#);
+
+        let scores = index.matches("jack");
+        let mut ranked: Vec<_> = scores.iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(a.1));
+        let order: Vec<PublicKey> = ranked.iter().map(|(pk, _)| **pk).collect();
+        assert_eq!(order, vec![jack, nip05, jackson, about]);
+
+        let typo = index.matches("jakc");
+        assert!(typo.contains_key(&jack) && typo.contains_key(&jackson));
+        assert!(!typo.contains_key(&about));
+        assert!(index.matches("jackson five").contains_key(&jackson));
+        assert!(index.matches("jackson bob").is_empty());
+
+        index.remove(&jack);
+        assert!(!index.matches("jack").contains_key(&jack));
+    }
+}
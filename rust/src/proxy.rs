@@ -0,0 +1,298 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+
+use anyhow::{anyhow, Result};
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+const PROXY_FILE: &str = "proxy.json";
+
+/// SOCKS5 proxy settings applied to every websocket the crate opens.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct ProxyConfig {
+    enabled: bool,
+    /// `host:port` of the SOCKS5 proxy, e.g. `127.0.0.1:9050` for Tor.
+    address: String,
+    /// Only route `.onion` relays through the proxy.
+    onion_only: bool,
+}
+
+#[derive(Default)]
+struct ProxyState {
+    path: Option<PathBuf>,
+    config: ProxyConfig,
+    /// Resolved proxy address while the proxy is enabled.
+    addr: Option<SocketAddr>,
+    /// Set once the config was changed explicitly, so a later `load` from
+    /// disk doesn't overwrite it.
+    dirty: bool,
+}
+
+static PROXY: OnceLock<RwLock<ProxyState>> = OnceLock::new();
+
+fn proxy_state() -> &'static RwLock<ProxyState> {
+    PROXY.get_or_init(|| RwLock::new(ProxyState::default()))
+}
+
+impl ProxyState {
+    /// The proxy to use for `url`, if any.
+    fn proxy_for(&self, url: &RelayUrl) -> Option<SocketAddr> {
+        let addr = self.addr?;
+        if self.config.onion_only && !url.is_onion() {
+            return None;
+        }
+        Some(addr)
+    }
+}
+
+async fn resolve(config: &ProxyConfig) -> Result<Option<SocketAddr>> {
+    if !config.enabled {
+        return Ok(None);
+    }
+    tokio::net::lookup_host(config.address.as_str())
+        .await?
+        .next()
+        .map(Some)
+        .ok_or_else(|| anyhow!("Proxy address {} did not resolve", config.address))
+}
+
+fn file_path(db_path: Option<&str>) -> Option<PathBuf> {
+    db_path.map(|p| {
+        PathBuf::from(p)
+            .parent()
+            .unwrap_or_else(|| std::path::Path::new("."))
+            .join(PROXY_FILE)
+    })
+}
+
+fn save(state: &ProxyState) {
+    if let (Some(path), Ok(json)) = (
+        state.path.as_ref(),
+        serde_json::to_vec_pretty(&state.config),
+    ) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+/// Load the persisted proxy config stored next to the database. A config
+/// set earlier in this session wins and is written out instead.
+pub(crate) async fn load(db_path: Option<&str>) {
+    let path = file_path(db_path);
+    let config: ProxyConfig = path
+        .as_ref()
+        .and_then(|p| std::fs::read(p).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+    let addr = resolve(&config).await.unwrap_or(None);
+
+    let mut state = proxy_state().write().unwrap();
+    state.path = path;
+    if state.dirty {
+        save(&state);
+        return;
+    }
+    state.addr = addr;
+    state.config = config;
+}
+
+pub(crate) fn config_json() -> serde_json::Value {
+    let state = proxy_state().read().unwrap();
+    serde_json::to_value(&state.config).unwrap_or_default()
+}
+
+/// Replace and persist the proxy config. Fails without changing anything if
+/// the proxy is enabled but its address can't be resolved.
+pub(crate) async fn set_config(config_json: &str) -> Result<()> {
+    let config: ProxyConfig = serde_json::from_str(config_json)?;
+    let addr = resolve(&config).await?;
+    let mut state = proxy_state().write().unwrap();
+    state.config = config;
+    state.addr = addr;
+    state.dirty = true;
+    save(&state);
+    Ok(())
+}
+
+fn proxy_for(url: &RelayUrl) -> Option<SocketAddr> {
+    proxy_state().read().unwrap().proxy_for(url)
+}
+
+fn mode_for(proxy: Option<SocketAddr>) -> ConnectionMode {
+    match proxy {
+        Some(addr) => ConnectionMode::proxy(addr),
+        None => ConnectionMode::Direct,
+    }
+}
+
+/// Connection mode for a relay added to any pool.
+pub(crate) fn connection_mode(url: &RelayUrl) -> ConnectionMode {
+    mode_for(proxy_for(url))
+}
+
+/// Open a raw websocket, going through the SOCKS5 proxy when one applies.
+pub(crate) async fn connect_websocket(
+    url: &str,
+) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
+    let relay_url = RelayUrl::parse(url)?;
+    connect_websocket_via(url, &relay_url, proxy_for(&relay_url)).await
+}
+
+async fn connect_websocket_via(
+    url: &str,
+    relay_url: &RelayUrl,
+    proxy: Option<SocketAddr>,
+) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
+    let Some(proxy) = proxy else {
+        let (ws, _) = tokio_tungstenite::connect_async(url).await?;
+        return Ok(ws);
+    };
+
+    let host = relay_url
+        .host()
+        .map(|h| h.to_string())
+        .ok_or_else(|| anyhow!("Relay URL has no host"))?;
+    let port = if url.starts_with("wss://") { 443 } else { 80 };
+    let port = url
+        .split("://")
+        .nth(1)
+        .and_then(|rest| rest.split('/').next())
+        .and_then(|authority| authority.rsplit_once(':'))
+        .and_then(|(_, p)| p.parse::<u16>().ok())
+        .unwrap_or(port);
+
+    let stream = tokio_socks::tcp::Socks5Stream::connect(proxy, (host.as_str(), port)).await?;
+    let (ws, _) = tokio_tungstenite::client_async_tls(url, stream.into_inner()).await?;
+    Ok(ws)
+}
+
+/// HTTP client for requests about `url` (e.g. its NIP-11 document), going
+/// through the SOCKS5 proxy when one applies.
+pub(crate) fn http_client(url: &RelayUrl) -> Result<reqwest::Client> {
+    let builder = reqwest::Client::builder();
+    let builder = match proxy_for(url) {
+        Some(addr) => builder.proxy(reqwest::Proxy::all(format!("socks5h://{}", addr))?),
+        None => builder,
+    };
+    Ok(builder.build()?)
+}
+
+/// Re-add relays whose connection mode no longer matches the proxy config.
+/// Returns how many relays were re-added.
+pub(crate) async fn apply_to_pool(client: &Client) -> usize {
+    let relays = client.pool().all_relays().await;
+    let stale: Vec<(RelayUrl, RelayOptions)> = relays
+        .iter()
+        .filter(|(url, relay)| *relay.connection_mode() != connection_mode(url))
+        .map(|(url, relay)| {
+            let opts = relay
+                .opts()
+                .clone()
+                .flags(crate::relay_scores::current_flags(relay))
+                .connection_mode(connection_mode(url));
+            (url.clone(), opts)
+        })
+        .collect();
+    drop(relays);
+
+    let mut count = 0;
+    for (url, opts) in stale {
+        if client.pool().force_remove_relay(&url).await.is_err() {
+            continue;
+        }
+        if client.pool().add_relay(&url, opts).await.is_ok() {
+            let _ = client.connect_relay(&url).await;
+            count += 1;
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{SinkExt, StreamExt};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Minimal SOCKS5 server: no auth, CONNECT only. Records the requested
+    /// domain and forwards the connection to `upstream` regardless of it.
+    async fn socks5_stand_in(
+        upstream: SocketAddr,
+    ) -> (SocketAddr, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = tokio::spawn(async move {
+            let (mut client, _) = listener.accept().await.unwrap();
+            let mut greeting = [0u8; 3];
+            client.read_exact(&mut greeting).await.unwrap();
+            client.write_all(&[5, 0]).await.unwrap();
+
+            let mut head = [0u8; 5];
+            client.read_exact(&mut head).await.unwrap();
+            assert_eq!(head[3], 3, "expected a domain target");
+            let mut domain = vec![0u8; head[4] as usize + 2];
+            client.read_exact(&mut domain).await.unwrap();
+            let target = String::from_utf8_lossy(&domain[..domain.len() - 2]).to_string();
+            client
+                .write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0, 0])
+                .await
+                .unwrap();
+
+            let mut server = TcpStream::connect(upstream).await.unwrap();
+            let _ = tokio::io::copy_bidirectional(&mut client, &mut server).await;
+            target
+        });
+        (addr, handle)
+    }
+
+    #[tokio::test]
+    async fn raw_websocket_goes_through_socks5() {
+        let ws_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ws_addr = ws_listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = ws_listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            if let Some(Ok(msg)) = ws.next().await {
+                let _ = ws.send(msg).await;
+            }
+        });
+
+        let (proxy_addr, stand_in) = socks5_stand_in(ws_addr).await;
+        let mut state = ProxyState {
+            config: ProxyConfig {
+                enabled: true,
+                address: proxy_addr.to_string(),
+                onion_only: false,
+            },
+            addr: Some(proxy_addr),
+            ..Default::default()
+        };
+
+        let url = "ws://relay.example.invalid";
+        let relay_url = RelayUrl::parse(url).unwrap();
+        let mut ws = connect_websocket_via(url, &relay_url, state.proxy_for(&relay_url))
+            .await
+            .unwrap();
+        ws.send(tokio_tungstenite::tungstenite::Message::Text("ping".into()))
+            .await
+            .unwrap();
+        let echoed = ws.next().await.unwrap().unwrap();
+        assert_eq!(echoed.into_text().unwrap(), "ping");
+        drop(ws);
+
+        assert_eq!(stand_in.await.unwrap(), "relay.example.invalid");
+
+        state.config.onion_only = true;
+        let clearnet = RelayUrl::parse("wss://relay.example.com").unwrap();
+        let onion = RelayUrl::parse("ws://abcdefghijklmnop.onion").unwrap();
+        assert_eq!(mode_for(state.proxy_for(&clearnet)), ConnectionMode::Direct);
+        assert_eq!(
+            mode_for(state.proxy_for(&onion)),
+            ConnectionMode::proxy(proxy_addr)
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use nostr_sdk::prelude::*;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(4);
+const DOCUMENT_TTL: Duration = Duration::from_secs(6 * 3600);
+const FAILURE_TTL: Duration = Duration::from_secs(10 * 60);
+
+struct CachedInfo {
+    /// NIPs from the relay's NIP-11 document; `None` if it couldn't be fetched.
+    supported_nips: Option<Vec<u16>>,
+    /// NIPs the relay advertises but failed to serve this session.
+    broken_nips: Vec<u16>,
+    fetched_at: Instant,
+}
+
+impl CachedInfo {
+    fn is_fresh(&self) -> bool {
+        let ttl = if self.supported_nips.is_some() {
+            DOCUMENT_TTL
+        } else {
+            FAILURE_TTL
+        };
+        self.fetched_at.elapsed() < ttl
+    }
+}
+
+static INFO: OnceLock<Mutex<HashMap<RelayUrl, CachedInfo>>> = OnceLock::new();
+
+fn info_state() -> &'static Mutex<HashMap<RelayUrl, CachedInfo>> {
+    INFO.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+async fn fetch_supported_nips(url: &RelayUrl) -> Option<Vec<u16>> {
+    let http_url = url
+        .as_str()
+        .replacen("wss://", "https://", 1)
+        .replacen("ws://", "http://", 1);
+    let client = crate::proxy::http_client(url).ok()?;
+    let request = client
+        .get(http_url)
+        .header("Accept", "application/nostr+json")
+        .timeout(FETCH_TIMEOUT)
+        .send();
+    let body = request.await.ok()?.text().await.ok()?;
+    let document: RelayInformationDocument = serde_json::from_str(&body).ok()?;
+    Some(document.supported_nips.unwrap_or_default())
+}
+
+/// Whether the relay's NIP-11 document lists `nip` and the relay hasn't
+/// since been caught not honouring it. Documents are cached for a few hours.
+pub(crate) async fn supports_nip(url: &RelayUrl, nip: u16) -> bool {
+    let (supported_nips, broken) = nip_info(url, nip).await;
+    !broken && supported_nips.is_some_and(|nips| nips.contains(&nip))
+}
+
+/// Whether the relay is known not to support `nip`: its NIP-11 document
+/// leaves it out, or it was caught not honouring it. A relay without a
+/// document isn't known either way.
+pub(crate) async fn lacks_nip(url: &RelayUrl, nip: u16) -> bool {
+    let (supported_nips, broken) = nip_info(url, nip).await;
+    broken || supported_nips.is_some_and(|nips| !nips.contains(&nip))
+}
+
+/// The relay's advertised NIPs, fetched if not cached, and whether `nip` is
+/// marked broken for it.
+async fn nip_info(url: &RelayUrl, nip: u16) -> (Option<Vec<u16>>, bool) {
+    let cached = {
+        let info = info_state().lock().unwrap();
+        info.get(url)
+            .filter(|i| i.is_fresh())
+            .map(|i| (i.supported_nips.clone(), i.broken_nips.contains(&nip)))
+    };
+    match cached {
+        Some(cached) => cached,
+        None => {
+            let supported_nips = fetch_supported_nips(url).await;
+            let mut info = info_state().lock().unwrap();
+            let broken_nips = info.remove(url).map(|i| i.broken_nips).unwrap_or_default();
+            let broken = broken_nips.contains(&nip);
+            info.insert(
+                url.clone(),
+                CachedInfo {
+                    supported_nips: supported_nips.clone(),
+                    broken_nips,
+                    fetched_at: Instant::now(),
+                },
+            );
+            (supported_nips, broken)
+        }
+    }
+}
+
+/// Stop using `nip` with this relay for the rest of the session, e.g. after
+/// it ignored a COUNT despite advertising NIP-45.
+pub(crate) fn mark_broken(url: &RelayUrl, nip: u16) {
+    let mut info = info_state().lock().unwrap();
+    let entry = info.entry(url.clone()).or_insert_with(|| CachedInfo {
+        supported_nips: None,
+        broken_nips: Vec::new(),
+        fetched_at: Instant::now(),
+    });
+    if !entry.broken_nips.contains(&nip) {
+        entry.broken_nips.push(nip);
+    }
+}
+
+/// Whether `nip` has been marked broken for this relay this session,
+/// without fetching its NIP-11 document.
+pub(crate) fn is_broken(url: &RelayUrl, nip: u16) -> bool {
+    info_state()
+        .lock()
+        .unwrap()
+        .get(url)
+        .is_some_and(|i| i.broken_nips.contains(&nip))
+}
@@ -0,0 +1,174 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+
+use nostr_sdk::prelude::*;
+
+const HISTORY_PER_RELAY: usize = 50;
+
+/// Why a relay refused an event or closed a subscription. The first nine
+/// variants are the NIP-01/NIP-42 machine-readable prefixes; the rest cover
+/// client-side failures that never reached the relay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RejectReason {
+    Duplicate,
+    Pow,
+    Blocked,
+    RateLimited,
+    Invalid,
+    Error,
+    Unsupported,
+    AuthRequired,
+    Restricted,
+    Timeout,
+    NotConnected,
+    Other,
+}
+
+impl RejectReason {
+    pub(crate) fn parse(message: &str) -> Self {
+        if let Some(prefix) = MachineReadablePrefix::parse(message.trim_start()) {
+            return match prefix {
+                MachineReadablePrefix::Duplicate => Self::Duplicate,
+                MachineReadablePrefix::Pow => Self::Pow,
+                MachineReadablePrefix::Blocked => Self::Blocked,
+                MachineReadablePrefix::RateLimited => Self::RateLimited,
+                MachineReadablePrefix::Invalid => Self::Invalid,
+                MachineReadablePrefix::Error => Self::Error,
+                MachineReadablePrefix::Unsupported => Self::Unsupported,
+                MachineReadablePrefix::AuthRequired => Self::AuthRequired,
+                MachineReadablePrefix::Restricted => Self::Restricted,
+            };
+        }
+
+        let lower = message.to_lowercase();
+        if lower.contains("timeout") || lower.contains("timed out") {
+            Self::Timeout
+        } else if lower.contains("not connected") {
+            Self::NotConnected
+        } else {
+            Self::Other
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::Duplicate => "duplicate",
+            Self::Pow => "pow",
+            Self::Blocked => "blocked",
+            Self::RateLimited => "rate-limited",
+            Self::Invalid => "invalid",
+            Self::Error => "error",
+            Self::Unsupported => "unsupported",
+            Self::AuthRequired => "auth-required",
+            Self::Restricted => "restricted",
+            Self::Timeout => "timeout",
+            Self::NotConnected => "not-connected",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// `{reason, message}` for a rejection string from a relay or the pool.
+pub(crate) fn rejection_json(message: &str) -> serde_json::Value {
+    serde_json::json!({
+        "reason": RejectReason::parse(message).as_str(),
+        "message": message,
+    })
+}
+
+static HISTORY: OnceLock<Mutex<HashMap<String, VecDeque<serde_json::Value>>>> = OnceLock::new();
+
+fn history_state() -> &'static Mutex<HashMap<String, VecDeque<serde_json::Value>>> {
+    HISTORY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn record(relay_url: &RelayUrl, entry: serde_json::Value) {
+    let mut history = history_state().lock().unwrap();
+    let entries = history.entry(relay_url.to_string()).or_default();
+    entries.push_back(entry);
+    if entries.len() > HISTORY_PER_RELAY {
+        entries.pop_front();
+    }
+}
+
+/// Record a NOTICE, CLOSED or rejected OK message. Anything else is ignored.
+pub(crate) fn record_message(relay_url: &RelayUrl, message: &RelayMessage) {
+    let at = Timestamp::now().as_secs();
+    let entry = match message {
+        RelayMessage::Notice(message) => serde_json::json!({
+            "type": "notice",
+            "reason": RejectReason::parse(message).as_str(),
+            "message": message,
+            "at": at,
+        }),
+        RelayMessage::Closed {
+            subscription_id,
+            message,
+        } => serde_json::json!({
+            "type": "closed",
+            "reason": RejectReason::parse(message).as_str(),
+            "message": message,
+            "subscriptionId": subscription_id.to_string(),
+            "at": at,
+        }),
+        RelayMessage::Ok {
+            event_id,
+            status: false,
+            message,
+        } => serde_json::json!({
+            "type": "ok",
+            "reason": RejectReason::parse(message).as_str(),
+            "message": message,
+            "eventId": event_id.to_hex(),
+            "at": at,
+        }),
+        _ => return,
+    };
+    record(relay_url, entry);
+}
+
+/// Recent messages for one relay, oldest first.
+pub(crate) fn relay_history(relay_url: &str) -> Vec<serde_json::Value> {
+    let history = history_state().lock().unwrap();
+    history
+        .get(relay_url)
+        .map(|entries| entries.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+pub(crate) fn all_history() -> HashMap<String, Vec<serde_json::Value>> {
+    let history = history_state().lock().unwrap();
+    history
+        .iter()
+        .map(|(url, entries)| (url.clone(), entries.iter().cloned().collect()))
+        .collect()
+}
+
+pub(crate) fn clear_history() {
+    history_state().lock().unwrap().clear();
+}
+
+/// Score OK replies and record messages worth keeping. Fed by the event
+/// bus dispatcher, the one reader of the pool's notifications.
+pub(crate) fn observe(relay_url: &RelayUrl, message: &RelayMessage) {
+    if let RelayMessage::Ok { status, message, .. } = message {
+        let accepted = *status || RejectReason::parse(message) == RejectReason::Duplicate;
+        crate::relay_scores::record_ok(relay_url, accepted);
+    }
+    record_message(relay_url, message);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RejectReason;
+
+    #[test]
+    fn parses_machine_readable_prefixes() {
+        assert_eq!(RejectReason::parse("rate-limited: slow down"), RejectReason::RateLimited);
+        assert_eq!(RejectReason::parse("auth-required: sign in"), RejectReason::AuthRequired);
+        assert_eq!(RejectReason::parse("pow: difficulty 20"), RejectReason::Pow);
+        assert_eq!(RejectReason::parse("blocked: spam"), RejectReason::Blocked);
+        assert_eq!(RejectReason::parse("timeout"), RejectReason::Timeout);
+        assert_eq!(RejectReason::parse("something odd"), RejectReason::Other);
+    }
+}
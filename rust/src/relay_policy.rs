@@ -0,0 +1,337 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+
+use anyhow::{anyhow, Result};
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const POLICY_FILE: &str = "relay_policy.json";
+
+/// Where a relay URL came from. URLs the user typed are trusted more than
+/// ones pulled from other people's relay lists or entity hints.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RelaySource {
+    User,
+    Discovered,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct RelayOverride {
+    read: Option<bool>,
+    write: Option<bool>,
+    discovery: Option<bool>,
+}
+
+/// Persisted relay policy. Rules are either host patterns (`relay.example.com`,
+/// `*.example.com`, `*.onion`) or full URL patterns (`wss://relay.example.com*`);
+/// `*` matches any run of characters and matching is case-insensitive.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct RelayPolicy {
+    blocklist: Vec<String>,
+    allowlist: Vec<String>,
+    /// Only relays matching `allowlist` may be added.
+    allowlist_only: bool,
+    /// Reject `.onion` hosts found in relay lists and hints.
+    block_discovered_onion: bool,
+    /// Reject localhost, `.local` and private-network hosts found in relay
+    /// lists and hints.
+    block_discovered_local: bool,
+    overrides: HashMap<String, RelayOverride>,
+}
+
+impl Default for RelayPolicy {
+    fn default() -> Self {
+        Self {
+            blocklist: Vec::new(),
+            allowlist: Vec::new(),
+            allowlist_only: false,
+            block_discovered_onion: true,
+            block_discovered_local: true,
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let (p, t): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if pi < p.len() && p[pi] == t[ti] {
+            pi += 1;
+            ti += 1;
+        } else if let Some((sp, st)) = star {
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
+}
+
+fn rule_matches(rule: &str, url: &str, host: &str) -> bool {
+    let rule = rule.trim().to_lowercase();
+    if rule.is_empty() {
+        return false;
+    }
+    if rule.contains("://") {
+        glob_match(rule.trim_end_matches('/'), url)
+    } else {
+        glob_match(&rule, host)
+    }
+}
+
+fn is_local_host(host: &str) -> bool {
+    if host == "localhost" || host.ends_with(".localhost") || host.ends_with(".local") {
+        return true;
+    }
+    match host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_unspecified()
+        }
+        Ok(IpAddr::V6(ip)) => {
+            ip.is_loopback() || ip.is_unspecified() || (ip.segments()[0] & 0xfe00) == 0xfc00
+        }
+        Err(_) => false,
+    }
+}
+
+fn host_of(url: &RelayUrl) -> String {
+    url.host()
+        .map(|h| h.to_string().to_lowercase())
+        .unwrap_or_default()
+}
+
+impl RelayPolicy {
+    /// `Err(reason)` if `url` may not be added from `source`.
+    pub(crate) fn check(&self, url: &RelayUrl, source: RelaySource) -> std::result::Result<(), String> {
+        let full = url.as_str_without_trailing_slash().to_lowercase();
+        let host = host_of(url);
+
+        if let Some(rule) = self.blocklist.iter().find(|r| rule_matches(r, &full, &host)) {
+            return Err(format!("blocked by rule {}", rule));
+        }
+        if self.allowlist_only && !self.allowlist.iter().any(|r| rule_matches(r, &full, &host)) {
+            return Err("not in allowlist".to_string());
+        }
+        if source == RelaySource::Discovered {
+            if self.block_discovered_onion && url.is_onion() {
+                return Err("onion relay from discovery".to_string());
+            }
+            if self.block_discovered_local && is_local_host(&host) {
+                return Err("local relay from discovery".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    fn override_for(&self, url: &RelayUrl) -> Option<&RelayOverride> {
+        self.overrides
+            .get(url.as_str())
+            .or_else(|| self.overrides.get(url.as_str_without_trailing_slash()))
+    }
+}
+
+#[derive(Default)]
+struct PolicyState {
+    path: Option<PathBuf>,
+    policy: RelayPolicy,
+}
+
+static POLICY: OnceLock<RwLock<PolicyState>> = OnceLock::new();
+
+fn policy_state() -> &'static RwLock<PolicyState> {
+    POLICY.get_or_init(|| RwLock::new(PolicyState::default()))
+}
+
+/// Load the persisted policy stored next to the database, if any.
+pub(crate) fn load(db_path: Option<&str>) {
+    let path = db_path.map(|p| {
+        PathBuf::from(p)
+            .parent()
+            .unwrap_or_else(|| std::path::Path::new("."))
+            .join(POLICY_FILE)
+    });
+    let policy = path
+        .as_ref()
+        .and_then(|p| std::fs::read(p).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+    let mut state = policy_state().write().unwrap();
+    state.path = path;
+    state.policy = policy;
+}
+
+pub(crate) fn policy_json() -> serde_json::Value {
+    let state = policy_state().read().unwrap();
+    serde_json::to_value(&state.policy).unwrap_or_default()
+}
+
+/// Replace and persist the policy.
+pub(crate) fn set_policy(policy_json: &str) -> Result<()> {
+    let policy: RelayPolicy = serde_json::from_str(policy_json)?;
+    let mut state = policy_state().write().unwrap();
+    if let Some(path) = state.path.as_ref() {
+        std::fs::write(path, serde_json::to_vec_pretty(&policy)?)?;
+    }
+    state.policy = policy;
+    Ok(())
+}
+
+pub(crate) fn check(url: &RelayUrl, source: RelaySource) -> std::result::Result<(), String> {
+    policy_state().read().unwrap().policy.check(url, source)
+}
+
+/// [`check`] as an error carrying the rejection reason.
+pub(crate) fn ensure_allowed(url: &RelayUrl, source: RelaySource) -> Result<()> {
+    check(url, source).map_err(|reason| anyhow!("Relay {} rejected by policy: {}", url, reason))
+}
+
+/// Apply the per-relay read/write/discovery override, if one is configured.
+pub(crate) fn apply_overrides(url: &RelayUrl, relay: &Relay) {
+    let state = policy_state().read().unwrap();
+    let Some(o) = state.policy.override_for(url) else {
+        return;
+    };
+    for (setting, flag) in [
+        (o.read, RelayServiceFlags::READ),
+        (o.write, RelayServiceFlags::WRITE),
+        (o.discovery, RelayServiceFlags::DISCOVERY),
+    ] {
+        match setting {
+            Some(true) => relay.flags().add(flag),
+            Some(false) => relay.flags().remove(flag),
+            None => {}
+        }
+    }
+}
+
+async fn add_checked(
+    client: &Client,
+    url: &str,
+    source: RelaySource,
+    flags: RelayServiceFlags,
+) -> Result<bool> {
+    let url = RelayUrl::parse(url)?;
+    ensure_allowed(&url, source)?;
+    let added = match client.pool().relay(&url).await {
+        Ok(relay) => {
+            relay.flags().add(flags);
+            apply_overrides(&url, &relay);
+            false
+        }
+        Err(_) => {
+            let opts = RelayOptions::new()
+                .flags(flags)
+                .connection_mode(crate::proxy::connection_mode(&url));
+            let added = client.pool().add_relay(&url, opts).await?;
+            if let Ok(relay) = client.pool().relay(&url).await {
+                apply_overrides(&url, &relay);
+            }
+            added
+        }
+    };
+    Ok(added)
+}
+
+/// `client.add_relay` behind the policy and the proxy config. Every relay
+/// that enters a pool must come through here or [`add_discovery_relay`].
+pub(crate) async fn add_relay(client: &Client, url: &str, source: RelaySource) -> Result<bool> {
+    add_checked(client, url, source, RelayServiceFlags::default()).await
+}
+
+pub(crate) async fn add_discovery_relay(client: &Client, url: &str) -> Result<bool> {
+    add_checked(
+        client,
+        url,
+        RelaySource::User,
+        RelayServiceFlags::PING | RelayServiceFlags::DISCOVERY,
+    )
+    .await
+}
+
+/// Add a relay from an entity hint for a one-off lookup. It only gets the
+/// read flag, so nothing published meanwhile is sent to it.
+pub(crate) async fn add_lookup_relay(client: &Client, url: &str) -> Result<bool> {
+    add_checked(
+        client,
+        url,
+        RelaySource::Discovered,
+        RelayServiceFlags::READ,
+    )
+    .await
+}
+
+/// Add a NIP-50 search relay. Without read or write flags it gets no feed
+/// subscriptions or publishes; the discovery flag is the narrowest one the
+/// pool accepts for targeted requests.
+pub(crate) async fn add_search_relay(client: &Client, url: &str) -> Result<bool> {
+    add_discovery_relay(client, url).await
+}
+
+/// Bring an existing pool in line with the current policy: drop relays that
+/// are no longer allowed and re-apply overrides to the rest. Returns the URLs
+/// that were removed.
+pub(crate) async fn enforce(client: &Client) -> Vec<String> {
+    let relays = client.pool().all_relays().await;
+    let mut blocked: Vec<RelayUrl> = Vec::new();
+    for (url, relay) in relays.iter() {
+        if check(url, RelaySource::User).is_err() {
+            blocked.push(url.clone());
+        } else {
+            apply_overrides(url, relay);
+        }
+    }
+    drop(relays);
+
+    let mut removed = Vec::new();
+    for url in blocked {
+        if client.pool().force_remove_relay(&url).await.is_ok() {
+            removed.push(url.to_string());
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RelayPolicy, RelaySource};
+    use nostr_sdk::prelude::*;
+
+    fn url(s: &str) -> RelayUrl {
+        RelayUrl::parse(s).unwrap()
+    }
+
+    #[test]
+    fn wildcard_blocklist_and_allowlist_only() {
+        let policy: RelayPolicy = serde_json::from_value(serde_json::json!({
+            "blocklist": ["*.spam.example"],
+            "allowlist": ["relay.damus.io", "*.nos.lol"],
+            "allowlistOnly": true,
+        }))
+        .unwrap();
+
+        assert!(policy.check(&url("wss://relay.damus.io"), RelaySource::User).is_ok());
+        assert!(policy.check(&url("wss://eu.nos.lol"), RelaySource::User).is_ok());
+        assert!(policy.check(&url("wss://nos.lol"), RelaySource::User).is_err());
+        assert!(policy.check(&url("wss://a.spam.example"), RelaySource::User).is_err());
+    }
+
+    #[test]
+    fn discovered_local_and_onion_are_rejected_by_default() {
+        let policy = RelayPolicy::default();
+        for u in ["ws://localhost:7777", "ws://192.168.1.4", "ws://abcdef.onion"] {
+            assert!(policy.check(&url(u), RelaySource::Discovered).is_err(), "{}", u);
+            assert!(policy.check(&url(u), RelaySource::User).is_ok(), "{}", u);
+        }
+    }
+}
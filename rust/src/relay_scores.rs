@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+
+const SCORES_FILE: &str = "relay_scores.json";
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+/// Refresh passes in a row with failed connection attempts before a relay is
+/// demoted.
+const DEMOTE_AFTER_FAILURES: u32 = 3;
+const DEMOTE_FOR_SECS: u64 = 6 * 60 * 60;
+/// Weight of the newest sample in the latency and EOSE moving averages.
+const EWMA_ALPHA: f64 = 0.3;
+/// Score given to relays we have no data for.
+pub(crate) const NEUTRAL_SCORE: f64 = 0.5;
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase", default)]
+struct RelayRecord {
+    attempts: u64,
+    successes: u64,
+    latency_ms: f64,
+    ok_accepted: u64,
+    ok_rejected: u64,
+    eose_ms: f64,
+    eose_samples: u64,
+    fetch_failures: u64,
+    consecutive_failures: u32,
+    demoted_until: u64,
+}
+
+fn ewma(prev: f64, sample: f64) -> f64 {
+    if prev <= 0.0 {
+        sample
+    } else {
+        prev * (1.0 - EWMA_ALPHA) + sample * EWMA_ALPHA
+    }
+}
+
+impl RelayRecord {
+    fn score(&self) -> f64 {
+        let success_rate = (self.successes as f64 + 1.0) / (self.attempts as f64 + 2.0);
+        let ok_rate = (self.ok_accepted as f64 + 1.0)
+            / ((self.ok_accepted + self.ok_rejected) as f64 + 2.0);
+        let latency = if self.latency_ms > 0.0 {
+            1.0 / (1.0 + self.latency_ms / 500.0)
+        } else {
+            NEUTRAL_SCORE
+        };
+        let fetches = self.eose_samples + self.fetch_failures;
+        let eose = if fetches == 0 {
+            NEUTRAL_SCORE
+        } else {
+            let reliability = self.eose_samples as f64 / fetches as f64;
+            reliability / (1.0 + self.eose_ms / 2000.0)
+        };
+
+        0.4 * success_rate + 0.2 * ok_rate + 0.25 * latency + 0.15 * eose
+    }
+
+    fn is_demoted(&self, now: u64) -> bool {
+        self.demoted_until > now
+    }
+}
+
+#[derive(Default)]
+struct ScoreBook {
+    path: Option<PathBuf>,
+    records: HashMap<String, RelayRecord>,
+    /// Connection counters last read from each live `Relay`, so only the
+    /// delta since the previous refresh is added to the persisted totals.
+    session: HashMap<String, (usize, usize)>,
+    /// Relays demoted during this session, with the flags taken off them.
+    demoted: HashMap<String, RelayServiceFlags>,
+}
+
+impl ScoreBook {
+    fn save(&self) {
+        let Some(path) = self.path.as_ref() else {
+            return;
+        };
+        if let Ok(json) = serde_json::to_vec(&self.records) {
+            let tmp = path.with_extension("json.tmp");
+            if std::fs::write(&tmp, json).is_ok() {
+                let _ = std::fs::rename(&tmp, path);
+            }
+        }
+    }
+}
+
+static SCORES: OnceLock<Mutex<ScoreBook>> = OnceLock::new();
+static SCORER: OnceLock<Mutex<Option<JoinHandle<()>>>> = OnceLock::new();
+
+fn book() -> &'static Mutex<ScoreBook> {
+    SCORES.get_or_init(|| Mutex::new(ScoreBook::default()))
+}
+
+fn scorer_state() -> &'static Mutex<Option<JoinHandle<()>>> {
+    SCORER.get_or_init(|| Mutex::new(None))
+}
+
+/// Count an OK reply from a relay towards its acceptance rate.
+pub(crate) fn record_ok(relay_url: &RelayUrl, accepted: bool) {
+    let mut book = book().lock().unwrap();
+    let record = book.records.entry(relay_url.to_string()).or_default();
+    if accepted {
+        record.ok_accepted += 1;
+    } else {
+        record.ok_rejected += 1;
+    }
+}
+
+/// Record how long a single-relay fetch took to reach EOSE, or that it failed.
+pub(crate) fn record_fetch(relay_url: &RelayUrl, elapsed: Option<Duration>) {
+    let mut book = book().lock().unwrap();
+    let record = book.records.entry(relay_url.to_string()).or_default();
+    match elapsed {
+        Some(elapsed) => {
+            record.eose_ms = ewma(record.eose_ms, elapsed.as_millis() as f64);
+            record.eose_samples += 1;
+        }
+        None => record.fetch_failures += 1,
+    }
+}
+
+pub(crate) fn score(relay_url: &str) -> f64 {
+    let book = book().lock().unwrap();
+    book.records
+        .get(relay_url)
+        .map(|r| r.score())
+        .unwrap_or(NEUTRAL_SCORE)
+}
+
+pub(crate) fn is_demoted(relay_url: &str) -> bool {
+    let now = Timestamp::now().as_secs();
+    let book = book().lock().unwrap();
+    book.records
+        .get(relay_url)
+        .is_some_and(|r| r.is_demoted(now))
+}
+
+/// The best scored `limit` of `urls`, best first, with demoted relays
+/// dropped unless that would leave nothing to talk to.
+pub(crate) fn rank(urls: Vec<RelayUrl>, limit: usize) -> Vec<RelayUrl> {
+    let now = Timestamp::now().as_secs();
+    let book = book().lock().unwrap();
+    let lookup = |u: &RelayUrl| book.records.get(u.as_str());
+
+    let mut ranked: Vec<(RelayUrl, f64)> = urls
+        .iter()
+        .filter(|u| !lookup(u).is_some_and(|r| r.is_demoted(now)))
+        .map(|u| (u.clone(), lookup(u).map(|r| r.score()).unwrap_or(NEUTRAL_SCORE)))
+        .collect();
+    if ranked.is_empty() {
+        return urls.into_iter().take(limit).collect();
+    }
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranked.into_iter().take(limit).map(|(u, _)| u).collect()
+}
+
+pub(crate) fn scores_json() -> serde_json::Value {
+    let now = Timestamp::now().as_secs();
+    let book = book().lock().unwrap();
+    let mut list: Vec<serde_json::Value> = book
+        .records
+        .iter()
+        .map(|(url, r)| {
+            serde_json::json!({
+                "url": url,
+                "score": r.score(),
+                "attempts": r.attempts,
+                "successes": r.successes,
+                "latencyMs": r.latency_ms.round() as u64,
+                "okAccepted": r.ok_accepted,
+                "okRejected": r.ok_rejected,
+                "eoseMs": r.eose_ms.round() as u64,
+                "fetchFailures": r.fetch_failures,
+                "consecutiveFailures": r.consecutive_failures,
+                "demoted": r.is_demoted(now),
+                "demotedUntil": r.demoted_until,
+            })
+        })
+        .collect();
+    list.sort_by(|a, b| b["score"].as_f64().unwrap_or(0.0).total_cmp(&a["score"].as_f64().unwrap_or(0.0)));
+    serde_json::Value::from(list)
+}
+
+pub(crate) fn reset() {
+    let mut book = book().lock().unwrap();
+    book.records.clear();
+    book.session.clear();
+    book.save();
+}
+
+pub(crate) fn current_flags(relay: &Relay) -> RelayServiceFlags {
+    let mut flags = RelayServiceFlags::NONE;
+    for flag in [
+        RelayServiceFlags::READ,
+        RelayServiceFlags::WRITE,
+        RelayServiceFlags::PING,
+        RelayServiceFlags::DISCOVERY,
+        RelayServiceFlags::GOSSIP,
+    ] {
+        if relay.flags().has(flag, FlagCheck::All) {
+            flags.add(flag);
+        }
+    }
+    flags
+}
+
+/// Take READ and WRITE off `relay` so pool requests and subscriptions skip
+/// it. The relay stays in the pool and connected as before, so status
+/// watchers and its stats keep running. Returns the flags removed.
+fn demote(relay: &Relay) -> RelayServiceFlags {
+    let mut removed = RelayServiceFlags::NONE;
+    for flag in [RelayServiceFlags::READ, RelayServiceFlags::WRITE] {
+        if relay.flags().has(flag, FlagCheck::All) {
+            removed.add(flag);
+        }
+    }
+    relay.flags().remove(removed);
+    removed
+}
+
+/// Give a demoted relay back the flags [`demote`] took.
+async fn restore(client: &Client, url: &RelayUrl, flags: RelayServiceFlags) {
+    if let Ok(relay) = client.pool().relay(url).await {
+        relay.flags().add(flags);
+    }
+}
+
+/// Fold the live connection stats of every relay into the persisted record,
+/// demote relays that keep failing and restore those whose demotion expired.
+async fn refresh(client: &Client) {
+    let now = Timestamp::now().as_secs();
+    // Demoted relays have lost READ and WRITE, so `Client::relays` no
+    // longer lists them.
+    let relays = client.pool().all_relays().await;
+    let mut to_restore: Vec<(RelayUrl, RelayServiceFlags)> = Vec::new();
+
+    {
+        let mut guard = book().lock().unwrap();
+        let book = &mut *guard;
+        for (url, relay) in relays.iter() {
+            let key = url.to_string();
+            let demoted = book.demoted.contains_key(&key);
+            if !demoted
+                && !relay.flags().has(
+                    RelayServiceFlags::READ | RelayServiceFlags::WRITE,
+                    FlagCheck::Any,
+                )
+            {
+                continue;
+            }
+            let stats = relay.stats();
+            let (attempts, success) = (stats.attempts(), stats.success());
+            let (prev_attempts, prev_success) = book.session.get(&key).copied().unwrap_or((0, 0));
+            let (prev_attempts, prev_success) = if attempts < prev_attempts {
+                (0, 0)
+            } else {
+                (prev_attempts, prev_success)
+            };
+            book.session.insert(key.clone(), (attempts, success));
+
+            let record = book.records.entry(key.clone()).or_default();
+            let new_attempts = attempts.saturating_sub(prev_attempts) as u64;
+            let new_success = success.saturating_sub(prev_success) as u64;
+            record.attempts += new_attempts;
+            record.successes += new_success;
+            if let Some(latency) = stats.latency() {
+                record.latency_ms = ewma(record.latency_ms, latency.as_millis() as f64);
+            }
+
+            if new_success > 0 || relay.status() == RelayStatus::Connected {
+                record.consecutive_failures = 0;
+            } else if new_attempts > 0 {
+                record.consecutive_failures += 1;
+            }
+
+            if record.is_demoted(now) {
+                if !demoted {
+                    book.demoted.insert(key, demote(relay));
+                }
+            } else if record.consecutive_failures >= DEMOTE_AFTER_FAILURES {
+                record.demoted_until = now + DEMOTE_FOR_SECS;
+                record.consecutive_failures = 0;
+                if !demoted {
+                    book.demoted.insert(key, demote(relay));
+                }
+            } else if let Some(flags) = book.demoted.remove(&key) {
+                record.demoted_until = 0;
+                to_restore.push((url.clone(), flags));
+            }
+        }
+        book.save();
+    }
+    drop(relays);
+
+    for (url, flags) in to_restore {
+        restore(client, &url, flags).await;
+    }
+}
+
+/// Load persisted scores from next to the database and start the periodic
+/// refresh for `client`, replacing the task of any previous client.
+pub(crate) fn start(client: &Client, db_path: Option<&str>) {
+    {
+        let mut book = book().lock().unwrap();
+        let path = db_path.map(|p| {
+            PathBuf::from(p)
+                .parent()
+                .unwrap_or_else(|| std::path::Path::new("."))
+                .join(SCORES_FILE)
+        });
+        if path != book.path {
+            book.records = path
+                .as_ref()
+                .and_then(|p| std::fs::read(p).ok())
+                .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+                .unwrap_or_default();
+            book.path = path;
+        }
+        book.session.clear();
+        book.demoted.clear();
+    }
+
+    let client = client.clone();
+    let handle = tokio::spawn(async move {
+        let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            refresh(&client).await;
+        }
+    });
+
+    if let Some(previous) = scorer_state().lock().unwrap().replace(handle) {
+        previous.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RelayRecord;
+
+    #[test]
+    fn failing_relay_scores_below_healthy_one() {
+        let healthy = RelayRecord {
+            attempts: 10,
+            successes: 10,
+            latency_ms: 120.0,
+            ok_accepted: 20,
+            eose_ms: 400.0,
+            eose_samples: 5,
+            ..Default::default()
+        };
+        let failing = RelayRecord {
+            attempts: 10,
+            successes: 1,
+            latency_ms: 1500.0,
+            ok_rejected: 5,
+            fetch_failures: 5,
+            ..Default::default()
+        };
+        assert!(healthy.score() > RelayRecord::default().score());
+        assert!(RelayRecord::default().score() > failing.score());
+    }
+}
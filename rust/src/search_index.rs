@@ -0,0 +1,771 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+
+use anyhow::Result;
+use heed::types::{Bytes, SerdeJson, Str};
+use heed::{Database, Env, EnvOpenOptions, RoTxn, RwTxn};
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::newest_first::NewestFirst;
+
+const INDEX_DIR: &str = "search_index";
+/// Initial map size; doubled whenever the map fills up.
+const MAP_SIZE: usize = 64 * 1024 * 1024;
+const MAX_MAP_SIZE: usize = if cfg!(target_pointer_width = "64") {
+    8 * 1024 * 1024 * 1024
+} else {
+    1024 * 1024 * 1024
+};
+/// Notes and long-form articles.
+const INDEXED_KINDS: [Kind; 2] = [Kind::TextNote, Kind::LongFormTextNote];
+const MIN_TERM_CHARS: usize = 2;
+const MAX_TERM_CHARS: usize = 40;
+/// Updates written per transaction.
+const WRITE_BATCH: usize = 512;
+const BACKFILL_PAGE: usize = 500;
+/// Best-ranked candidates checked against the database per query.
+const MAX_CANDIDATES: usize = 2_000;
+/// BM25 parameters.
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+const META_TOTAL_LEN: &str = "totalLen";
+const META_BACKFILL_UNTIL: &str = "backfillUntil";
+const META_BACKFILLED: &str = "backfilled";
+
+/// Indexed document; keeps its terms so it can be removed again.
+#[derive(Serialize, Deserialize)]
+struct Doc {
+    created_at: u64,
+    kind: u16,
+    pubkey: String,
+    len: u32,
+    terms: Vec<String>,
+}
+
+/// Words of `text`, lowercased. Links and `nostr:` references are skipped.
+/// Scripts written without spaces between words are split into overlapping
+/// pairs of characters instead.
+pub(crate) fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let words = text
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .filter(|word| {
+            !(word.starts_with("http://")
+                || word.starts_with("https://")
+                || word.contains("nostr:"))
+        });
+    for word in words {
+        let chars: Vec<char> = word.chars().collect();
+        for run in chars.chunk_by(|a, b| is_spaceless(*a) == is_spaceless(*b)) {
+            if is_spaceless(run[0]) {
+                if run.len() == 1 {
+                    tokens.push(run[0].to_string());
+                }
+                tokens.extend(run.windows(2).map(|pair| pair.iter().collect()));
+                continue;
+            }
+            tokens.extend(
+                run.split(|c| !c.is_alphanumeric())
+                    .filter(|term| (MIN_TERM_CHARS..=MAX_TERM_CHARS).contains(&term.len()))
+                    .map(|term| term.iter().collect()),
+            );
+        }
+    }
+    tokens
+}
+
+/// Whether `c` belongs to a script that doesn't separate words by spaces:
+/// Chinese, Japanese, Thai, Lao, Khmer or Myanmar.
+fn is_spaceless(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3040}'..='\u{30FF}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{20000}'..='\u{2FA1F}'
+            | '\u{0E00}'..='\u{0EFF}'
+            | '\u{1000}'..='\u{109F}'
+            | '\u{1780}'..='\u{17FF}'
+    )
+}
+
+fn indexed_text(event: &Event) -> String {
+    match event
+        .tags
+        .find(TagKind::Title)
+        .and_then(|tag| tag.content())
+    {
+        Some(title) => format!("{}\n{}", title, event.content),
+        None => event.content.clone(),
+    }
+}
+
+fn is_map_full(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<heed::Error>(),
+        Some(heed::Error::Mdb(heed::MdbError::MapFull))
+    )
+}
+
+fn posting_key(term: &str, id: &[u8]) -> Vec<u8> {
+    let mut key = Vec::with_capacity(term.len() + 1 + id.len());
+    key.extend_from_slice(term.as_bytes());
+    key.push(0);
+    key.extend_from_slice(id);
+    key
+}
+
+/// Parsed search text: every term must match, and each quoted phrase must
+/// appear as consecutive words.
+#[derive(Default, Debug, PartialEq, Clone)]
+pub(crate) struct SearchQuery {
+    terms: Vec<String>,
+    phrases: Vec<Vec<String>>,
+    /// The term still being typed: the last one, when the text ends in the
+    /// middle of an unquoted word. It matches any word it starts.
+    prefix: Option<String>,
+}
+
+impl SearchQuery {
+    pub(crate) fn parse(text: &str) -> Self {
+        let mut query = Self::default();
+        let mut last_unquoted = false;
+        for (i, part) in text.split('"').enumerate() {
+            let tokens = tokenize(part);
+            if i % 2 == 1 && tokens.len() > 1 {
+                query.phrases.push(tokens.clone());
+            }
+            last_unquoted = i % 2 == 0;
+            for token in tokens {
+                if !query.terms.contains(&token) {
+                    query.terms.push(token);
+                }
+            }
+        }
+        if last_unquoted && text.ends_with(char::is_alphanumeric) {
+            let tokens = tokenize(text.rsplit('"').next().unwrap_or_default());
+            query.prefix = tokens.last().cloned();
+        }
+        query
+    }
+
+    fn matches_phrases(&self, event: &Event) -> bool {
+        if self.phrases.is_empty() {
+            return true;
+        }
+        let words = tokenize(&indexed_text(event));
+        self.phrases
+            .iter()
+            .all(|phrase| words.windows(phrase.len()).any(|w| w == phrase.as_slice()))
+    }
+}
+
+/// Which indexed events a query may return.
+#[derive(Default, Clone)]
+pub(crate) struct SearchFilter {
+    pub(crate) authors: HashSet<String>,
+    pub(crate) kinds: HashSet<u16>,
+    pub(crate) since: Option<u64>,
+    pub(crate) until: Option<u64>,
+}
+
+impl From<&Filter> for SearchFilter {
+    fn from(filter: &Filter) -> Self {
+        Self {
+            authors: filter
+                .authors
+                .iter()
+                .flatten()
+                .map(|pk| pk.to_hex())
+                .collect(),
+            kinds: filter.kinds.iter().flatten().map(|k| k.as_u16()).collect(),
+            since: filter.since.map(|t| t.as_secs()),
+            until: filter.until.map(|t| t.as_secs()),
+        }
+    }
+}
+
+impl SearchFilter {
+    fn accepts(&self, doc: &Doc) -> bool {
+        (self.authors.is_empty() || self.authors.contains(&doc.pubkey))
+            && (self.kinds.is_empty() || self.kinds.contains(&doc.kind))
+            && self.since.is_none_or(|since| doc.created_at >= since)
+            && self.until.is_none_or(|until| doc.created_at <= until)
+    }
+}
+
+pub(crate) struct SearchIndex {
+    path: PathBuf,
+    env: Env,
+    /// Held shared by every transaction, and exclusively to resize the map,
+    /// which LMDB only allows while no transaction is open.
+    resizing: RwLock<()>,
+    /// `term \0 event id` → term frequency (u32, big endian).
+    postings: Database<Bytes, Bytes>,
+    docs: Database<Bytes, SerdeJson<Doc>>,
+    meta: Database<Str, Bytes>,
+}
+
+impl SearchIndex {
+    fn open(path: &Path) -> Result<Self> {
+        std::fs::create_dir_all(path)?;
+        // SAFETY: the index directory is only opened through this type.
+        let env = unsafe {
+            EnvOpenOptions::new()
+                .map_size(MAP_SIZE)
+                .max_dbs(3)
+                .open(path)?
+        };
+        let mut wtxn = env.write_txn()?;
+        let postings = env.create_database(&mut wtxn, Some("postings"))?;
+        let docs = env.create_database(&mut wtxn, Some("docs"))?;
+        let meta = env.create_database(&mut wtxn, Some("meta"))?;
+        wtxn.commit()?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            env,
+            resizing: RwLock::new(()),
+            postings,
+            docs,
+            meta,
+        })
+    }
+
+    fn meta_u64(&self, txn: &RoTxn, key: &str) -> Result<Option<u64>> {
+        Ok(self
+            .meta
+            .get(txn, key)?
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u64::from_be_bytes))
+    }
+
+    fn put_meta_u64(&self, txn: &mut RwTxn, key: &str, value: u64) -> Result<()> {
+        Ok(self.meta.put(txn, key, &value.to_be_bytes())?)
+    }
+
+    /// Returns whether `event` was newly indexed.
+    fn insert(&self, txn: &mut RwTxn, event: &Event) -> Result<bool> {
+        let id = event.id.as_bytes();
+        if self.docs.get(txn, id)?.is_some() {
+            return Ok(false);
+        }
+        let tokens = tokenize(&indexed_text(event));
+        let mut frequencies: HashMap<&str, u32> = HashMap::new();
+        for token in &tokens {
+            *frequencies.entry(token.as_str()).or_default() += 1;
+        }
+        for (term, frequency) in &frequencies {
+            self.postings
+                .put(txn, &posting_key(term, id), &frequency.to_be_bytes())?;
+        }
+        let doc = Doc {
+            created_at: event.created_at.as_secs(),
+            kind: event.kind.as_u16(),
+            pubkey: event.pubkey.to_hex(),
+            len: tokens.len() as u32,
+            terms: frequencies.keys().map(|t| t.to_string()).collect(),
+        };
+        self.docs.put(txn, id, &doc)?;
+        let total = self.meta_u64(txn, META_TOTAL_LEN)?.unwrap_or(0);
+        self.put_meta_u64(txn, META_TOTAL_LEN, total + doc.len as u64)?;
+        Ok(true)
+    }
+
+    fn remove(&self, txn: &mut RwTxn, id: &EventId) -> Result<()> {
+        let id = id.as_bytes();
+        let Some(doc) = self.docs.get(txn, id)? else {
+            return Ok(());
+        };
+        for term in &doc.terms {
+            self.postings.delete(txn, &posting_key(term, id))?;
+        }
+        self.docs.delete(txn, id)?;
+        let total = self.meta_u64(txn, META_TOTAL_LEN)?.unwrap_or(0);
+        self.put_meta_u64(txn, META_TOTAL_LEN, total.saturating_sub(doc.len as u64))?;
+        Ok(())
+    }
+
+    /// Write `updates` in one transaction, growing the map if it is full.
+    fn apply(&self, updates: &[Update]) -> Result<usize> {
+        loop {
+            match self.try_apply(updates) {
+                Err(e) if is_map_full(&e) => self.grow()?,
+                result => return result,
+            }
+        }
+    }
+
+    fn try_apply(&self, updates: &[Update]) -> Result<usize> {
+        let _resizing = self.resizing.read().unwrap();
+        let mut txn = self.env.write_txn()?;
+        let mut indexed = 0;
+        for update in updates {
+            match update {
+                Update::Index(event) => indexed += self.insert(&mut txn, event)? as usize,
+                Update::Remove(id) => self.remove(&mut txn, id)?,
+                Update::Clear => {
+                    self.postings.clear(&mut txn)?;
+                    self.docs.clear(&mut txn)?;
+                    self.meta.clear(&mut txn)?;
+                }
+            }
+        }
+        txn.commit()?;
+        Ok(indexed)
+    }
+
+    /// Double the map, up to [`MAX_MAP_SIZE`].
+    fn grow(&self) -> Result<()> {
+        let _resizing = self.resizing.write().unwrap();
+        let current = self.env.info().map_size;
+        let next = current.saturating_mul(2).min(MAX_MAP_SIZE);
+        if next <= current {
+            anyhow::bail!("Search index is full at {} bytes", current);
+        }
+        // SAFETY: `resizing` is held exclusively, so no transaction is open.
+        unsafe { self.env.resize(next)? };
+        Ok(())
+    }
+
+    /// Documents containing `term`, or a word starting with it if `prefix`,
+    /// with the term's frequency in each.
+    fn postings(&self, txn: &RoTxn, term: &str, prefix: bool) -> Result<HashMap<[u8; 32], u32>> {
+        let key_prefix = if prefix {
+            term.as_bytes().to_vec()
+        } else {
+            posting_key(term, &[])
+        };
+        let mut postings = HashMap::new();
+        for entry in self.postings.prefix_iter(txn, &key_prefix)? {
+            let (key, value) = entry?;
+            let Some(split) = key.len().checked_sub(33) else {
+                continue;
+            };
+            if key[split] != 0 || (!prefix && split != term.len()) {
+                continue;
+            }
+            let (Ok(id), Ok(frequency)) = (
+                <[u8; 32]>::try_from(&key[split + 1..]),
+                <[u8; 4]>::try_from(value),
+            ) else {
+                continue;
+            };
+            *postings.entry(id).or_default() += u32::from_be_bytes(frequency);
+        }
+        Ok(postings)
+    }
+
+    /// Event ids matching every term and `filter`, best BM25 score first.
+    fn rank(&self, query: &SearchQuery, filter: &SearchFilter) -> Result<Vec<EventId>> {
+        if query.terms.is_empty() {
+            return Ok(Vec::new());
+        }
+        let _resizing = self.resizing.read().unwrap();
+        let txn = self.env.read_txn()?;
+        let doc_count = self.docs.len(&txn)?.max(1) as f64;
+        let avg_len = self.meta_u64(&txn, META_TOTAL_LEN)?.unwrap_or(0) as f64 / doc_count;
+
+        let mut per_term: Vec<HashMap<[u8; 32], u32>> = Vec::with_capacity(query.terms.len());
+        for term in &query.terms {
+            let prefix = query.prefix.as_ref() == Some(term);
+            let postings = self.postings(&txn, term, prefix)?;
+            if postings.is_empty() {
+                return Ok(Vec::new());
+            }
+            per_term.push(postings);
+        }
+        per_term.sort_by_key(|postings| postings.len());
+
+        let mut scored: Vec<(f64, u64, [u8; 32])> = Vec::new();
+        for id in per_term[0].keys() {
+            if !per_term[1..]
+                .iter()
+                .all(|postings| postings.contains_key(id))
+            {
+                continue;
+            }
+            let Some(doc) = self.docs.get(&txn, id)? else {
+                continue;
+            };
+            if !filter.accepts(&doc) {
+                continue;
+            }
+            let len_norm = 1.0 - B + B * doc.len as f64 / avg_len.max(1.0);
+            let score: f64 = per_term
+                .iter()
+                .map(|postings| {
+                    let df = postings.len() as f64;
+                    let idf = ((doc_count - df + 0.5) / (df + 0.5) + 1.0).ln();
+                    let tf = postings[id] as f64;
+                    idf * tf * (K1 + 1.0) / (tf + K1 * len_norm)
+                })
+                .sum();
+            scored.push((score, doc.created_at, *id));
+        }
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0).then(b.1.cmp(&a.1)));
+        Ok(scored
+            .into_iter()
+            .map(|(_, _, id)| EventId::from_byte_array(id))
+            .collect())
+    }
+}
+
+enum Update {
+    Index(Event),
+    Remove(EventId),
+    Clear,
+}
+
+struct Indexer {
+    index: Arc<SearchIndex>,
+    updates: mpsc::UnboundedSender<Update>,
+}
+
+static INDEXER: OnceLock<RwLock<Option<Indexer>>> = OnceLock::new();
+
+fn indexer_state() -> &'static RwLock<Option<Indexer>> {
+    INDEXER.get_or_init(|| RwLock::new(None))
+}
+
+fn current_index() -> Option<Arc<SearchIndex>> {
+    indexer_state()
+        .read()
+        .unwrap()
+        .as_ref()
+        .map(|indexer| indexer.index.clone())
+}
+
+/// Why the index couldn't be opened or written to last, and how many
+/// updates were lost to failed writes.
+#[derive(Default)]
+struct Failures {
+    last_error: Option<String>,
+    dropped_updates: u64,
+}
+
+static FAILURES: OnceLock<Mutex<Failures>> = OnceLock::new();
+
+fn failures() -> &'static Mutex<Failures> {
+    FAILURES.get_or_init(|| Mutex::new(Failures::default()))
+}
+
+fn record_failure(error: &anyhow::Error, dropped_updates: usize) {
+    let mut failures = failures().lock().unwrap();
+    failures.last_error = Some(error.to_string());
+    failures.dropped_updates += dropped_updates as u64;
+}
+
+fn send(update: Update) {
+    if let Some(indexer) = indexer_state().read().unwrap().as_ref() {
+        let _ = indexer.updates.send(update);
+    }
+}
+
+/// Write queued updates in batches, one transaction per batch.
+async fn run_writer(index: Arc<SearchIndex>, mut updates: mpsc::UnboundedReceiver<Update>) {
+    while let Some(first) = updates.recv().await {
+        let mut batch = vec![first];
+        while batch.len() < WRITE_BATCH {
+            match updates.try_recv() {
+                Ok(update) => batch.push(update),
+                Err(_) => break,
+            }
+        }
+        let index = index.clone();
+        let size = batch.len();
+        let result = tokio::task::spawn_blocking(move || index.apply(&batch))
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|result| result);
+        if let Err(e) = result {
+            record_failure(&e, size);
+        }
+    }
+}
+
+/// Open the index stored next to the database and start its writer. Must
+/// run before the database is built so no saved event is missed. If it
+/// can't be opened the error shows in [`status_json`] and searches fall
+/// back to scanning the database.
+pub(crate) fn open(db_path: &str) {
+    let path = PathBuf::from(db_path)
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(INDEX_DIR);
+    let mut state = indexer_state().write().unwrap();
+    if state
+        .as_ref()
+        .is_some_and(|indexer| indexer.index.path == path)
+    {
+        return;
+    }
+    let index = match SearchIndex::open(&path) {
+        Ok(index) => Arc::new(index),
+        Err(e) => {
+            record_failure(&e, 0);
+            *state = None;
+            return;
+        }
+    };
+    let (updates, receiver) = mpsc::unbounded_channel();
+    tokio::spawn(run_writer(index.clone(), receiver));
+    *state = Some(Indexer { index, updates });
+}
+
+/// Stop using the index, e.g. for a client without a database path.
+pub(crate) fn close() {
+    *indexer_state().write().unwrap() = None;
+}
+
+pub(crate) fn is_open() -> bool {
+    current_index().is_some()
+}
+
+pub(crate) fn is_indexed_kind(kind: Kind) -> bool {
+    INDEXED_KINDS.contains(&kind)
+}
+
+/// Queue a newly saved event for indexing.
+pub(crate) fn index_event(event: &Event) {
+    if is_indexed_kind(event.kind) {
+        send(Update::Index(event.clone()));
+    }
+}
+
+/// Queue deleted events for removal from the index.
+pub(crate) fn remove_events(ids: impl IntoIterator<Item = EventId>) {
+    for id in ids {
+        send(Update::Remove(id));
+    }
+}
+
+/// Queue removal of everything indexed, e.g. after the database was wiped.
+pub(crate) fn clear() {
+    send(Update::Clear);
+}
+
+/// The filter narrowed to indexed kinds, or `None` if it matches none.
+pub(crate) fn indexed_part(filter: &Filter) -> Option<Filter> {
+    match &filter.kinds {
+        None => Some(filter.clone().kinds(INDEXED_KINDS)),
+        Some(kinds) if kinds.iter().any(|k| is_indexed_kind(*k)) => Some(filter.clone()),
+        Some(_) => None,
+    }
+}
+
+/// Index events stored before the index existed, newest first, resuming
+/// where an earlier run stopped.
+async fn backfill(client: &Client, index: &Arc<SearchIndex>) -> Result<()> {
+    let until = {
+        let _resizing = index.resizing.read().unwrap();
+        let txn = index.env.read_txn()?;
+        if index.meta.get(&txn, META_BACKFILLED)?.is_some() {
+            return Ok(());
+        }
+        index
+            .meta_u64(&txn, META_BACKFILL_UNTIL)?
+            .map(Timestamp::from)
+    };
+    let filter = Filter::new().kinds(INDEXED_KINDS);
+    let mut pages = NewestFirst::new(filter, BACKFILL_PAGE, until);
+    while let Some(events) = pages.next_page(client).await? {
+        let updates: Vec<Update> = events.into_iter().map(Update::Index).collect();
+        let until = pages.until().map_or(0, |t| t.as_secs());
+        let index = index.clone();
+        tokio::task::spawn_blocking(move || -> Result<()> {
+            index.apply(&updates)?;
+            let _resizing = index.resizing.read().unwrap();
+            let mut txn = index.env.write_txn()?;
+            index.put_meta_u64(&mut txn, META_BACKFILL_UNTIL, until)?;
+            Ok(txn.commit()?)
+        })
+        .await??;
+    }
+    let _resizing = index.resizing.read().unwrap();
+    let mut txn = index.env.write_txn()?;
+    index.meta.put(&mut txn, META_BACKFILLED, &[1])?;
+    txn.commit()?;
+    Ok(())
+}
+
+/// Backfill the index in the background.
+pub(crate) fn start(client: &Client) {
+    let Some(index) = current_index() else {
+        return;
+    };
+    let client = client.clone();
+    tokio::spawn(async move {
+        if let Err(e) = backfill(&client, &index).await {
+            record_failure(&e, 0);
+        }
+    });
+}
+
+/// Documents in the index, whether events stored before it existed have
+/// all been indexed, and the last error with the updates lost to it.
+pub(crate) fn status_json() -> serde_json::Value {
+    let (last_error, dropped_updates) = {
+        let failures = failures().lock().unwrap();
+        (failures.last_error.clone(), failures.dropped_updates)
+    };
+    let Some(index) = current_index() else {
+        return serde_json::json!({ "open": false, "lastError": last_error });
+    };
+    let _resizing = index.resizing.read().unwrap();
+    let status = index.env.read_txn().map(|txn| {
+        (
+            index.docs.len(&txn).unwrap_or(0),
+            index
+                .meta
+                .get(&txn, META_BACKFILLED)
+                .ok()
+                .flatten()
+                .is_some(),
+        )
+    });
+    let (documents, backfilled) = status.unwrap_or((0, false));
+    serde_json::json!({
+        "open": true,
+        "documents": documents,
+        "backfilled": backfilled,
+        "mapSize": index.env.info().map_size,
+        "lastError": last_error,
+        "droppedUpdates": dropped_updates,
+    })
+}
+
+/// One page of search results, best match first.
+pub(crate) struct SearchPage {
+    pub(crate) events: Vec<Event>,
+    /// Matches among the best-ranked candidates that were checked.
+    pub(crate) total: usize,
+    /// More candidates matched than were checked.
+    pub(crate) truncated: bool,
+}
+
+/// Ranked search over indexed notes and articles. Candidates are checked
+/// against the database, so events it deleted or replaced on its own are
+/// dropped here and removed from the index.
+pub(crate) async fn search(
+    client: &Client,
+    text: &str,
+    filter: &SearchFilter,
+    offset: usize,
+    limit: usize,
+) -> Result<SearchPage> {
+    let Some(index) = current_index() else {
+        anyhow::bail!("Search index is not open");
+    };
+    let query = SearchQuery::parse(text);
+    let mut ranked = {
+        let (query, filter) = (query.clone(), filter.clone());
+        tokio::task::spawn_blocking(move || index.rank(&query, &filter)).await??
+    };
+    let truncated = ranked.len() > MAX_CANDIDATES;
+    ranked.truncate(MAX_CANDIDATES);
+
+    let stored: HashMap<EventId, Event> = client
+        .database()
+        .query(Filter::new().ids(ranked.iter().copied()))
+        .await?
+        .into_iter()
+        .map(|event| (event.id, event))
+        .collect();
+    let mut matches = Vec::new();
+    let mut missing = Vec::new();
+    for id in ranked {
+        match stored.get(&id) {
+            Some(event) if query.matches_phrases(event) => matches.push(event.clone()),
+            Some(_) => {}
+            None => missing.push(id),
+        }
+    }
+    remove_events(missing);
+
+    Ok(SearchPage {
+        total: matches.len(),
+        events: matches.into_iter().skip(offset).take(limit).collect(),
+        truncated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_matches_and_forgets_removed_events() {
+        let dir = std::env::temp_dir().join(format!("search-index-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let index = SearchIndex::open(&dir).unwrap();
+
+        let keys = Keys::generate();
+        let note = |text: &str| EventBuilder::text_note(text).sign_with_keys(&keys).unwrap();
+        let focused = note("Rust rust rust: LMDB tips");
+        let passing = note("Some thoughts about rust and a long list of other things to do today");
+        let unrelated = note("Coffee https://rust.example.com");
+        let updates: Vec<Update> = [&focused, &passing, &unrelated]
+            .into_iter()
+            .map(|e| Update::Index(e.clone()))
+            .collect();
+        assert_eq!(index.apply(&updates).unwrap(), 3);
+        assert_eq!(index.apply(&updates).unwrap(), 0);
+
+        let filter = SearchFilter::default();
+        let ranked = index.rank(&SearchQuery::parse("RUST"), &filter).unwrap();
+        assert_eq!(ranked, vec![focused.id, passing.id]);
+        assert!(index
+            .rank(&SearchQuery::parse("rust coffee"), &filter)
+            .unwrap()
+            .is_empty());
+
+        let query = SearchQuery::parse("\"lmdb tips\" rust");
+        assert_eq!(query.terms, vec!["lmdb", "tips", "rust"]);
+        assert!(query.matches_phrases(&focused));
+        assert!(!query.matches_phrases(&note("tips for lmdb")));
+
+        index.apply(&[Update::Remove(focused.id)]).unwrap();
+        let ranked = index.rank(&SearchQuery::parse("rust"), &filter).unwrap();
+        assert_eq!(ranked, vec![passing.id]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn matches_word_prefixes_and_spaceless_scripts() {
+        assert_eq!(
+            tokenize("東京都に行く"),
+            vec!["東京", "京都", "都に", "に行", "行く"]
+        );
+        assert_eq!(tokenize("rust 語"), vec!["rust", "語"]);
+
+        let dir = std::env::temp_dir().join(format!("search-prefix-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let index = SearchIndex::open(&dir).unwrap();
+        let keys = Keys::generate();
+        let note = |text: &str| EventBuilder::text_note(text).sign_with_keys(&keys).unwrap();
+        let rustacean = note("Hello rustaceans");
+        let tokyo = note("明日は東京都に行きます");
+        let updates = [&rustacean, &tokyo].map(|e| Update::Index(e.clone()));
+        index.apply(&updates).unwrap();
+
+        let filter = SearchFilter::default();
+        let rank = |text: &str| index.rank(&SearchQuery::parse(text), &filter).unwrap();
+        assert_eq!(rank("hello rust"), vec![rustacean.id]);
+        assert!(rank("hello rust ").is_empty());
+        assert!(rank("\"hello rust\"").is_empty());
+        assert_eq!(rank("東京都"), vec![tokyo.id]);
+        assert!(rank("京都市").is_empty());
+
+        let map_size = index.env.info().map_size;
+        index.grow().unwrap();
+        assert_eq!(index.env.info().map_size, map_size * 2);
+        assert_eq!(rank("hello rust"), vec![rustacean.id]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
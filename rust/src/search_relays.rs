@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const RELAYS_FILE: &str = "search_relays.json";
+const NIP50: u16 = 50;
+
+/// Relays queried with NIP-50 `search` filters. They are added to the pool
+/// without read or write flags, so feeds and publishing don't use them.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+struct SearchRelayConfig {
+    relays: Vec<String>,
+}
+
+impl Default for SearchRelayConfig {
+    fn default() -> Self {
+        Self {
+            relays: vec![
+                "wss://relay.nostr.band".to_string(),
+                "wss://search.nos.today".to_string(),
+            ],
+        }
+    }
+}
+
+#[derive(Default)]
+struct ConfigState {
+    path: Option<PathBuf>,
+    config: SearchRelayConfig,
+}
+
+static CONFIG: OnceLock<RwLock<ConfigState>> = OnceLock::new();
+
+fn config_state() -> &'static RwLock<ConfigState> {
+    CONFIG.get_or_init(|| RwLock::new(ConfigState::default()))
+}
+
+/// Load the persisted search relays stored next to the database, if any.
+pub(crate) fn load(db_path: Option<&str>) {
+    let path = db_path.map(|p| {
+        PathBuf::from(p)
+            .parent()
+            .unwrap_or_else(|| std::path::Path::new("."))
+            .join(RELAYS_FILE)
+    });
+    let config = path
+        .as_ref()
+        .and_then(|p| std::fs::read(p).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+    let mut state = config_state().write().unwrap();
+    state.path = path;
+    state.config = config;
+}
+
+pub(crate) fn relays() -> Vec<String> {
+    config_state().read().unwrap().config.relays.clone()
+}
+
+/// Replace and persist the search relays.
+pub(crate) fn set_relays(urls: Vec<String>) -> Result<()> {
+    let mut relays = Vec::new();
+    for url in urls {
+        let url = RelayUrl::parse(&url)?.to_string();
+        if !relays.contains(&url) {
+            relays.push(url);
+        }
+    }
+    let mut state = config_state().write().unwrap();
+    let config = SearchRelayConfig { relays };
+    if let Some(path) = state.path.as_ref() {
+        std::fs::write(path, serde_json::to_vec_pretty(&config)?)?;
+    }
+    state.config = config;
+    Ok(())
+}
+
+/// Where a search result came from.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Source {
+    Local,
+    Relay,
+    Both,
+}
+
+impl Source {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Local => "local",
+            Self::Relay => "relay",
+            Self::Both => "both",
+        }
+    }
+}
+
+/// Send `filter` with `search` set to every configured search relay and
+/// save what comes back, all within `timeout`. Relays are connected to in
+/// parallel; those that can't be reached in time are skipped, as are those
+/// known not to support NIP-50, which would answer with unfiltered notes.
+pub(crate) async fn fetch(
+    client: &Client,
+    search: &str,
+    filter: Filter,
+    timeout: Duration,
+) -> Result<Vec<Event>> {
+    let deadline = Instant::now() + timeout;
+    let mut candidates: Vec<RelayUrl> = Vec::new();
+    for url in relays() {
+        if crate::relay_policy::add_search_relay(client, &url)
+            .await
+            .is_err()
+        {
+            continue;
+        }
+        if let Ok(url) = RelayUrl::parse(&url) {
+            candidates.push(url);
+        }
+    }
+    let connected = futures::future::join_all(candidates.into_iter().map(|url| async move {
+        let (connected, lacks_search) = tokio::join!(
+            client.try_connect_relay(&url, timeout),
+            crate::relay_info::lacks_nip(&url, NIP50),
+        );
+        (connected.is_ok() && !lacks_search).then_some(url)
+    }))
+    .await;
+    let urls: Vec<RelayUrl> = connected.into_iter().flatten().collect();
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    if urls.is_empty() || remaining.is_zero() {
+        return Ok(Vec::new());
+    }
+    let events = client
+        .fetch_events_from(urls, filter.search(search), remaining)
+        .await?;
+    let database = client.database();
+    for event in events.iter() {
+        let _ = database.save_event(event).await;
+    }
+    Ok(events.to_vec())
+}
+
+/// Local results followed by relay results not found locally, deduplicated
+/// by `key`. When both sides have an entry the newer event is kept.
+pub(crate) fn merge<K, F>(local: Vec<Event>, remote: Vec<Event>, key: F) -> Vec<(Event, Source)>
+where
+    K: std::hash::Hash + Eq,
+    F: Fn(&Event) -> K,
+{
+    let mut merged: Vec<(Event, Source)> = Vec::with_capacity(local.len() + remote.len());
+    let mut positions: HashMap<K, usize> = HashMap::new();
+    for event in local {
+        if let std::collections::hash_map::Entry::Vacant(entry) = positions.entry(key(&event)) {
+            entry.insert(merged.len());
+            merged.push((event, Source::Local));
+        }
+    }
+    for event in remote {
+        match positions.get(&key(&event)) {
+            Some(&i) => {
+                let (existing, source) = &mut merged[i];
+                if *source == Source::Local {
+                    *source = Source::Both;
+                }
+                if event.created_at > existing.created_at {
+                    *existing = event;
+                }
+            }
+            None => {
+                positions.insert(key(&event), merged.len());
+                merged.push((event, Source::Relay));
+            }
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::Message;
+
+    /// A relay answering NIP-50 `search` filters by substring over `events`.
+    async fn search_relay(events: Vec<Event>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let events = events.clone();
+                tokio::spawn(async move {
+                    let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await else {
+                        return;
+                    };
+                    while let Some(Ok(message)) = ws.next().await {
+                        let Message::Text(text) = message else {
+                            continue;
+                        };
+                        let Ok(serde_json::Value::Array(msg)) = serde_json::from_str(&text) else {
+                            continue;
+                        };
+                        if msg.first().and_then(|v| v.as_str()) != Some("REQ") {
+                            continue;
+                        }
+                        let sub = msg[1].clone();
+                        let search = msg[2]["search"].as_str().unwrap_or("").to_lowercase();
+                        for event in &events {
+                            if !search.is_empty() && event.content.to_lowercase().contains(&search)
+                            {
+                                let reply = serde_json::json!(["EVENT", sub, event]);
+                                let _ = ws.send(Message::Text(reply.to_string())).await;
+                            }
+                        }
+                        let eose = serde_json::json!(["EOSE", sub]);
+                        let _ = ws.send(Message::Text(eose.to_string())).await;
+                    }
+                });
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn relay_results_are_saved_and_merged_with_local_hits() {
+        let keys = Keys::generate();
+        let note = |text: &str| EventBuilder::text_note(text).sign_with_keys(&keys).unwrap();
+        let shared = note("nostr search is neat");
+        let remote_only = note("another search result");
+        let url = search_relay(vec![shared.clone(), remote_only.clone(), note("unrelated")]).await;
+        set_relays(vec![url.clone()]).unwrap();
+
+        let database =
+            nostr_database::MemoryDatabase::with_opts(nostr_database::MemoryDatabaseOptions {
+                events: true,
+                max_events: None,
+            });
+        let client = Client::builder().database(database).build();
+        let fetched = fetch(
+            &client,
+            "search",
+            Filter::new().kind(Kind::TextNote),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+        assert_eq!(fetched.len(), 2);
+        assert!(client
+            .database()
+            .event_by_id(&remote_only.id)
+            .await
+            .unwrap()
+            .is_some());
+
+        let local_only = note("local search hit");
+        let merged = merge(vec![shared.clone(), local_only.clone()], fetched, |e| e.id);
+        let sources: HashMap<EventId, Source> = merged.iter().map(|(e, s)| (e.id, *s)).collect();
+        assert_eq!(sources.len(), 3);
+        assert_eq!(sources[&shared.id], Source::Both);
+        assert_eq!(sources[&local_only.id], Source::Local);
+        assert_eq!(sources[&remote_only.id], Source::Relay);
+
+        crate::relay_info::mark_broken(&RelayUrl::parse(&url).unwrap(), NIP50);
+        let fetched = fetch(
+            &client,
+            "search",
+            Filter::new().kind(Kind::TextNote),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+        assert!(fetched.is_empty());
+    }
+}
@@ -0,0 +1,392 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use anyhow::Result;
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+const QUOTA_FILE: &str = "storage_quota.json";
+const CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// Start evicting once the data file reaches this share of the quota...
+const EVICT_THRESHOLD: f64 = 0.9;
+/// ...and free enough to bring it back to this share.
+const EVICT_TARGET: f64 = 0.8;
+const EVICT_CHUNK: usize = 500;
+const MAX_MAP_SIZE: usize = if cfg!(target_pointer_width = "64") {
+    32 * 1024 * 1024 * 1024
+} else {
+    2 * 1024 * 1024 * 1024
+};
+
+/// Events that can be evicted, least valuable first. Events by the user,
+/// events tagging the user, DMs and the user's lists never are.
+struct Tier {
+    name: &'static str,
+    kinds: &'static [u16],
+    min_age_days: u64,
+    /// `Some(true)`: only followed authors, `Some(false)`: only others.
+    followed: Option<bool>,
+}
+
+const TIERS: &[Tier] = &[
+    Tier {
+        name: "reactions",
+        kinds: &[7],
+        min_age_days: 0,
+        followed: None,
+    },
+    Tier {
+        name: "repostsAndZaps",
+        kinds: &[6, 16, 9735],
+        min_age_days: 1,
+        followed: None,
+    },
+    Tier {
+        name: "strangerNotes",
+        kinds: &[1, 1111, 30023],
+        min_age_days: 3,
+        followed: Some(false),
+    },
+    Tier {
+        name: "strangerContactLists",
+        kinds: &[3],
+        min_age_days: 7,
+        followed: Some(false),
+    },
+    Tier {
+        name: "followedNotes",
+        kinds: &[1, 1111, 30023],
+        min_age_days: 30,
+        followed: Some(true),
+    },
+];
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct QuotaConfig {
+    /// Disk space the event store may use; `None` means unlimited.
+    quota_bytes: Option<u64>,
+}
+
+#[derive(Default)]
+struct StorageState {
+    config_path: Option<PathBuf>,
+    db_path: Option<String>,
+    config: QuotaConfig,
+    map_size: usize,
+    grows: u32,
+    last_grow_error: Option<String>,
+    last_eviction: Option<serde_json::Value>,
+    /// Event count before the last eviction. The data file never shrinks,
+    /// so until the store holds that many events again the freed pages are
+    /// still being reused and there is no point evicting more.
+    count_floor: Option<usize>,
+    task: Option<tokio::task::JoinHandle<()>>,
+}
+
+static STORAGE: OnceLock<Mutex<StorageState>> = OnceLock::new();
+static WAKE: OnceLock<Notify> = OnceLock::new();
+/// Set when a write failed because the map can't grow any further.
+static URGENT: AtomicBool = AtomicBool::new(false);
+
+fn storage_state() -> &'static Mutex<StorageState> {
+    STORAGE.get_or_init(|| Mutex::new(StorageState::default()))
+}
+
+fn wake_signal() -> &'static Notify {
+    WAKE.get_or_init(Notify::new)
+}
+
+pub(crate) fn data_file_size(db_path: &str) -> u64 {
+    std::fs::metadata(Path::new(db_path).join("data.mdb"))
+        .map(|m| m.len())
+        .unwrap_or(0)
+}
+
+/// The map size the store was last opened or grown with, 0 if none yet.
+pub(crate) fn map_size() -> usize {
+    storage_state().lock().unwrap().map_size
+}
+
+pub(crate) fn record_map_size(map_size: usize) {
+    storage_state().lock().unwrap().map_size = map_size;
+}
+
+pub(crate) fn record_growth(map_size: usize, error: Option<String>) {
+    let mut state = storage_state().lock().unwrap();
+    if error.is_none() && map_size > state.map_size {
+        state.grows += 1;
+    }
+    state.map_size = map_size;
+    state.last_grow_error = error;
+}
+
+/// The size to grow a full map of `current` bytes to: double, within the
+/// platform limit and the quota. Not larger than `current` if it can't grow.
+pub(crate) fn next_map_size(current: usize) -> usize {
+    let quota = storage_state().lock().unwrap().config.quota_bytes;
+    let mut next = current.saturating_mul(2).min(MAX_MAP_SIZE);
+    if let Some(quota) = quota {
+        next = next.min((quota as usize).max(current));
+    }
+    next
+}
+
+/// Ask the quota task to free space now, e.g. because the map is full and
+/// can't grow.
+pub(crate) fn wake() {
+    URGENT.store(true, Ordering::Relaxed);
+    wake_signal().notify_one();
+}
+
+pub(crate) fn status_json() -> serde_json::Value {
+    let state = storage_state().lock().unwrap();
+    serde_json::json!({
+        "usedBytes": state.db_path.as_deref().map(data_file_size),
+        "mapSize": state.map_size,
+        "quotaBytes": state.config.quota_bytes,
+        "grows": state.grows,
+        "lastGrowError": state.last_grow_error,
+        "lastEviction": state.last_eviction,
+    })
+}
+
+/// Set and persist the quota; `None` removes it. Evicts right away if the
+/// store is already over the new quota.
+pub(crate) fn set_quota(quota_bytes: Option<u64>) -> Result<()> {
+    let config = QuotaConfig { quota_bytes };
+    {
+        let mut state = storage_state().lock().unwrap();
+        if let Some(path) = state.config_path.as_ref() {
+            std::fs::write(path, serde_json::to_vec_pretty(&config)?)?;
+        }
+        state.config = config;
+        state.count_floor = None;
+    }
+    wake_signal().notify_one();
+    Ok(())
+}
+
+async fn protected_pubkeys(client: &Client) -> (Option<PublicKey>, HashSet<PublicKey>) {
+    let owner = match client.signer().await {
+        Ok(signer) => signer.get_public_key().await.ok(),
+        Err(_) => None,
+    };
+    let follows = match owner {
+        Some(owner) => client
+            .database()
+            .contacts_public_keys(owner)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .collect(),
+        None => HashSet::new(),
+    };
+    (owner, follows)
+}
+
+fn is_evictable(
+    event: &Event,
+    tier: &Tier,
+    owner: Option<PublicKey>,
+    follows: &HashSet<PublicKey>,
+) -> bool {
+    if let Some(owner) = owner {
+        if event.pubkey == owner || event.tags.public_keys().any(|pk| *pk == owner) {
+            return false;
+        }
+    }
+    match tier.followed {
+        Some(followed) => follows.contains(&event.pubkey) == followed,
+        None => true,
+    }
+}
+
+/// Delete up to `target` events, oldest first within each tier and working
+/// through the tiers in order. Returns how many were deleted per tier.
+pub(crate) async fn evict(client: &Client, target: usize) -> Result<serde_json::Value> {
+    let database = client.database();
+    let (owner, follows) = protected_pubkeys(client).await;
+    let now = Timestamp::now().as_secs();
+    let mut deleted = 0usize;
+    let mut by_tier = serde_json::Map::new();
+
+    for tier in TIERS {
+        if deleted >= target {
+            break;
+        }
+        let filter = Filter::new()
+            .kinds(tier.kinds.iter().map(|k| Kind::from(*k)))
+            .until(Timestamp::from(
+                now.saturating_sub(tier.min_age_days * 86_400),
+            ));
+        let mut items = database.negentropy_items(filter).await?;
+        items.sort_by_key(|(_, created_at)| *created_at);
+
+        let mut tier_deleted = 0usize;
+        for chunk in items.chunks(EVICT_CHUNK) {
+            if deleted >= target {
+                break;
+            }
+            let events = database
+                .query(Filter::new().ids(chunk.iter().map(|(id, _)| *id)))
+                .await?;
+            let ids: Vec<EventId> = events
+                .iter()
+                .filter(|e| is_evictable(e, tier, owner, &follows))
+                .map(|e| e.id)
+                .take(target - deleted)
+                .collect();
+            if ids.is_empty() {
+                continue;
+            }
+            let count = ids.len();
+            database.delete(Filter::new().ids(ids)).await?;
+            deleted += count;
+            tier_deleted += count;
+        }
+        if tier_deleted > 0 {
+            by_tier.insert(tier.name.to_string(), tier_deleted.into());
+        }
+    }
+
+    Ok(serde_json::json!({
+        "at": now,
+        "target": target,
+        "deleted": deleted,
+        "byTier": by_tier,
+    }))
+}
+
+/// Evict if the store is over its quota. `force` ignores the count floor,
+/// for when writes are already failing or the user asked.
+pub(crate) async fn enforce(client: &Client, force: bool) -> Result<Option<serde_json::Value>> {
+    let (db_path, quota, floor) = {
+        let state = storage_state().lock().unwrap();
+        (
+            state.db_path.clone(),
+            state.config.quota_bytes,
+            state.count_floor,
+        )
+    };
+    let (Some(db_path), Some(quota)) = (db_path, quota) else {
+        return Ok(None);
+    };
+    let used = data_file_size(&db_path);
+    if !force && (used as f64) < quota as f64 * EVICT_THRESHOLD {
+        return Ok(None);
+    }
+    let total = client.database().count(Filter::new()).await?;
+    if total == 0 || (!force && floor.is_some_and(|floor| total < floor)) {
+        return Ok(None);
+    }
+
+    // Bytes per event, averaged over the whole file, turns the overshoot
+    // into an event count.
+    let over = used.saturating_sub((quota as f64 * EVICT_TARGET) as u64);
+    let per_event = (used / total as u64).max(1);
+    let target = ((over / per_event) as usize + 1).max(EVICT_CHUNK.min(total));
+    let report = evict(client, target).await?;
+
+    let mut state = storage_state().lock().unwrap();
+    state.count_floor = Some(total);
+    state.last_eviction = Some(report.clone());
+    Ok(Some(report))
+}
+
+/// Load the quota stored next to the database and keep enforcing it in the
+/// background.
+pub(crate) fn start(client: &Client, db_path: Option<&str>) {
+    let config_path = db_path.map(|p| {
+        PathBuf::from(p)
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(QUOTA_FILE)
+    });
+    let config = config_path
+        .as_ref()
+        .and_then(|p| std::fs::read(p).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+
+    let client = client.clone();
+    let handle = tokio::spawn(async move {
+        loop {
+            let _ = tokio::time::timeout(CHECK_INTERVAL, wake_signal().notified()).await;
+            let force = URGENT.swap(false, Ordering::Relaxed);
+            let _ = enforce(&client, force).await;
+        }
+    });
+
+    let mut state = storage_state().lock().unwrap();
+    state.config_path = config_path;
+    state.db_path = db_path.map(str::to_string);
+    state.config = config;
+    state.count_floor = None;
+    if let Some(previous) = state.task.replace(handle) {
+        previous.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn eviction_spares_own_and_followed_events() {
+        let database = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            ..Default::default()
+        });
+        let owner = Keys::generate();
+        let client = Client::builder()
+            .signer(owner.clone())
+            .database(database)
+            .build();
+        let friend = Keys::generate();
+        let stranger = Keys::generate();
+        let old = Timestamp::from(Timestamp::now().as_secs() - 40 * 86_400);
+
+        let contacts = EventBuilder::new(Kind::ContactList, "")
+            .tag(Tag::public_key(friend.public_key()))
+            .sign_with_keys(&owner)
+            .unwrap();
+        let own_note = EventBuilder::text_note("mine").custom_created_at(old);
+        let friend_note = EventBuilder::text_note("friend").custom_created_at(old);
+        let stranger_note = EventBuilder::text_note("stranger").custom_created_at(old);
+        let reaction = EventBuilder::new(Kind::Reaction, "+").custom_created_at(old);
+        let reply_to_owner = EventBuilder::text_note("hi")
+            .tag(Tag::public_key(owner.public_key()))
+            .custom_created_at(old);
+        let events = [
+            contacts,
+            own_note.sign_with_keys(&owner).unwrap(),
+            friend_note.sign_with_keys(&friend).unwrap(),
+            stranger_note.sign_with_keys(&stranger).unwrap(),
+            reaction.sign_with_keys(&stranger).unwrap(),
+            reply_to_owner.sign_with_keys(&stranger).unwrap(),
+        ];
+        for event in &events {
+            client.database().save_event(event).await.unwrap();
+        }
+
+        let report = evict(&client, 2).await.unwrap();
+        assert_eq!(report["byTier"]["reactions"], 1);
+        assert_eq!(report["byTier"]["strangerNotes"], 1);
+        assert_eq!(
+            client.database().count(Filter::new()).await.unwrap(),
+            events.len() - 2
+        );
+
+        let report = evict(&client, 10).await.unwrap();
+        assert_eq!(report["byTier"]["followedNotes"], 1);
+        assert_eq!(
+            client.database().count(Filter::new()).await.unwrap(),
+            events.len() - 3
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+
+use anyhow::{anyhow, Result};
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::follow_graph::Trust;
+
+const FILTER_FILE: &str = "wot_filter.json";
+
+/// Which replies, reactions and zaps go to the main list rather than the
+/// "other" bucket, judged by the author's place in the follow graph. Off
+/// until the user turns it on.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct WotFilter {
+    enabled: bool,
+    /// Furthest follow distance let through: 1 for follows only, 2 to also
+    /// admit follows of follows.
+    max_distance: u8,
+    /// Lowest trust score let through; see [`Trust::trust`].
+    min_trust: f64,
+}
+
+impl Default for WotFilter {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_distance: 2,
+            min_trust: 0.0,
+        }
+    }
+}
+
+impl WotFilter {
+    fn passes(&self, trust: &Trust) -> bool {
+        !self.enabled
+            || (trust.distance.is_some_and(|d| d <= self.max_distance)
+                && trust.trust >= self.min_trust)
+    }
+
+    /// This filter with the top-level fields present in `update_json`
+    /// replaced.
+    fn merged(&self, update_json: &str) -> Result<Self> {
+        let update: serde_json::Value = serde_json::from_str(update_json)?;
+        let serde_json::Value::Object(update) = update else {
+            return Err(anyhow!("WoT filter must be a JSON object"));
+        };
+        let mut merged = serde_json::to_value(self)?;
+        for (key, value) in update {
+            if merged.get(&key).is_none() {
+                return Err(anyhow!("Unknown WoT filter field: {}", key));
+            }
+            merged[key] = value;
+        }
+        let filter: Self = serde_json::from_value(merged)?;
+        if !(0.0..=1.0).contains(&filter.min_trust) {
+            return Err(anyhow!("minTrust must be between 0 and 1"));
+        }
+        Ok(filter)
+    }
+}
+
+#[derive(Default)]
+struct FilterState {
+    path: Option<PathBuf>,
+    filter: WotFilter,
+}
+
+static FILTER: OnceLock<RwLock<FilterState>> = OnceLock::new();
+
+fn filter_state() -> &'static RwLock<FilterState> {
+    FILTER.get_or_init(|| RwLock::new(FilterState::default()))
+}
+
+/// Load the persisted filter stored next to the database, if any.
+pub(crate) fn load(db_path: Option<&str>) {
+    let path = db_path.map(|p| {
+        PathBuf::from(p)
+            .parent()
+            .unwrap_or_else(|| std::path::Path::new("."))
+            .join(FILTER_FILE)
+    });
+    let filter = path
+        .as_ref()
+        .and_then(|p| std::fs::read(p).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+    let mut state = filter_state().write().unwrap();
+    state.path = path;
+    state.filter = filter;
+}
+
+pub(crate) fn filter() -> WotFilter {
+    filter_state().read().unwrap().filter.clone()
+}
+
+/// Apply and persist a partial filter update; see [`WotFilter`].
+pub(crate) fn update(update_json: &str) -> Result<WotFilter> {
+    let mut state = filter_state().write().unwrap();
+    let filter = state.filter.merged(update_json)?;
+    if let Some(path) = state.path.as_ref() {
+        std::fs::write(path, serde_json::to_vec_pretty(&filter)?)?;
+    }
+    state.filter = filter.clone();
+    Ok(filter)
+}
+
+/// Set `"bucket"` on each hydrated item to `"main"` or `"other"` depending
+/// on whether the pubkey under `pubkey_field` passes the filter as seen
+/// from `viewer_hex`. Without a viewer, while the follow graph is still
+/// being built, or while the viewer's contact list isn't known, everything
+/// goes to the main bucket.
+pub(crate) fn bucket(
+    viewer_hex: Option<&str>,
+    items: &mut [serde_json::Value],
+    pubkey_field: &str,
+) {
+    let filter = filter();
+    let viewer = viewer_hex.and_then(|h| PublicKey::from_hex(h).ok());
+    let mut passes: HashMap<String, bool> = HashMap::new();
+    if let Some(viewer) = viewer.filter(|_| filter.enabled) {
+        let pubkeys: Vec<PublicKey> = items
+            .iter()
+            .filter_map(|item| item[pubkey_field].as_str())
+            .filter_map(|h| PublicKey::from_hex(h).ok())
+            .collect();
+        if crate::follow_graph::is_built() && crate::follow_graph::follow_count(viewer) > 0 {
+            let trust = crate::follow_graph::trust(viewer, &pubkeys);
+            passes = pubkeys
+                .iter()
+                .zip(trust)
+                .map(|(pk, t)| (pk.to_hex(), filter.passes(&t)))
+                .collect();
+        }
+    }
+    for item in items.iter_mut() {
+        let main = item[pubkey_field]
+            .as_str()
+            .and_then(|pk| passes.get(pk))
+            .copied()
+            .unwrap_or(true);
+        item["bucket"] = serde_json::json!(if main { "main" } else { "other" });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trust(distance: Option<u8>, trust: f64) -> Trust {
+        Trust {
+            distance,
+            followed_by_follows: 0,
+            trust,
+        }
+    }
+
+    #[test]
+    fn distance_and_trust_thresholds_both_apply() {
+        let defaults = WotFilter::default();
+        assert!(defaults.passes(&trust(None, 0.0)));
+
+        let enabled = defaults.merged(r#"{"enabled": true}"#).unwrap();
+        assert!(enabled.passes(&trust(Some(1), 1.0)));
+        assert!(enabled.passes(&trust(Some(2), 0.25)));
+        assert!(!enabled.passes(&trust(None, 0.0)));
+
+        let strict = enabled.merged(r#"{"minTrust": 0.5}"#).unwrap();
+        assert_eq!(strict.max_distance, 2);
+        assert!(!strict.passes(&trust(Some(2), 0.25)));
+        assert!(strict.passes(&trust(Some(2), 0.6)));
+
+        let follows_only = enabled.merged(r#"{"maxDistance": 1}"#).unwrap();
+        assert!(!follows_only.passes(&trust(Some(2), 0.9)));
+
+        let disabled = enabled.merged(r#"{"enabled": false}"#).unwrap();
+        assert!(disabled.passes(&trust(None, 0.0)));
+
+        assert!(defaults.merged(r#"{"minTrust": 2}"#).is_err());
+        assert!(defaults.merged(r#"{"maxDistanse": 1}"#).is_err());
+    }
+}